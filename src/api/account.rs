@@ -0,0 +1,21 @@
+//! Account endpoints
+
+use axum::{extract::State, http::StatusCode, Json};
+use crate::AppState;
+use crate::api::error::error_response;
+use crate::models::MT5AccountInfo;
+
+/// Balance, equity and margin
+#[utoipa::path(
+    get,
+    path = "/account",
+    responses((status = 200, description = "Account information", body = MT5AccountInfo)),
+)]
+pub async fn get_account_info(
+    State(state): State<AppState>,
+) -> Result<Json<MT5AccountInfo>, (StatusCode, String)> {
+    match state.mt5_client.get_account_info().await {
+        Ok(account) => Ok(Json(account)),
+        Err(e) => Err(error_response(e)),
+    }
+}
@@ -0,0 +1,27 @@
+//! Administrative endpoints
+
+use axum::{extract::State, Json};
+use serde::Serialize;
+use utoipa::ToSchema;
+use crate::AppState;
+use crate::mt5::ConnectionState;
+
+#[derive(Serialize, ToSchema)]
+pub struct ReconnectResponse {
+    pub connected: bool,
+    pub state: ConnectionState,
+}
+
+/// Force the bridge connection to be re-established, e.g. after rotating the bridge
+#[utoipa::path(
+    post,
+    path = "/admin/reconnect",
+    responses((status = 200, description = "Resulting connection state", body = ReconnectResponse)),
+)]
+pub async fn reconnect(State(state): State<AppState>) -> Json<ReconnectResponse> {
+    let connection_state = state.mt5_client.reconnect().await;
+    Json(ReconnectResponse {
+        connected: connection_state == ConnectionState::Connected,
+        state: connection_state,
+    })
+}
@@ -0,0 +1,44 @@
+//! API key authentication middleware
+
+use axum::{
+    extract::{Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::Response,
+};
+use ring::hmac;
+use crate::AppState;
+
+/// Header carrying the API key
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Message both keys sign in `key_matches`
+const KEY_CHECK_MESSAGE: &[u8] = b"fks_meta api key";
+
+/// Whether `provided` equals `expected`, in constant time
+///
+/// Each key signs the same message and the tags are compared with
+/// `hmac::verify`, so response timing doesn't reveal how much of the key matched.
+fn key_matches(provided: &[u8], expected: &str) -> bool {
+    let provided = hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, provided), KEY_CHECK_MESSAGE);
+    let expected = hmac::Key::new(hmac::HMAC_SHA256, expected.as_bytes());
+    hmac::verify(&expected, KEY_CHECK_MESSAGE, provided.as_ref()).is_ok()
+}
+
+/// Reject requests whose `X-API-Key` doesn't match `mt5_api_key`
+///
+/// When no key is configured every request is let through.
+pub async fn require_api_key(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let Some(expected) = state.settings.mt5_api_key.as_deref() else {
+        return Ok(next.run(request).await);
+    };
+    
+    match request.headers().get(API_KEY_HEADER) {
+        Some(key) if key_matches(key.as_bytes(), expected) => Ok(next.run(request).await),
+        _ => Err(StatusCode::UNAUTHORIZED),
+    }
+}
@@ -0,0 +1,104 @@
+//! Signed callbacks from the bridge
+//!
+//! The bridge POSTs events (fills, closed positions) to `/bridge/events`
+//! with an `X-Signature` header holding the hex HMAC-SHA256 of the raw body,
+//! keyed with `mt5_bridge_hmac_secret`. Unsigned or mis-signed requests are
+//! rejected with 401 before the body is parsed.
+
+use axum::{
+    body::Bytes,
+    extract::State,
+    http::{HeaderMap, StatusCode},
+};
+use ring::hmac;
+use tracing::{info, warn};
+use crate::models::BridgeEvent;
+use crate::AppState;
+
+/// Header carrying the body signature
+pub const SIGNATURE_HEADER: &str = "x-signature";
+
+/// Optional scheme prefix on the signature, as sent by GitHub-style signers
+const SIGNATURE_PREFIX: &str = "sha256=";
+
+/// Hex HMAC-SHA256 of `body` under `secret`, as the bridge sends it
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+    hmac::sign(&key, body)
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Whether `signature` is the HMAC of `body` under `secret`
+///
+/// The comparison is constant-time so the signature can't be guessed byte
+/// by byte from response timings.
+pub fn verify(secret: &str, body: &[u8], signature: &str) -> bool {
+    let signature = signature.trim();
+    let signature = signature.strip_prefix(SIGNATURE_PREFIX).unwrap_or(signature);
+    let Some(tag) = decode_hex(signature) else {
+        return false;
+    };
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+    hmac::verify(&key, body, &tag).is_ok()
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Verify and dispatch an event pushed by the bridge
+///
+/// Served when `mt5_bridge_hmac_secret` is set.
+#[utoipa::path(
+    post,
+    path = "/bridge/events",
+    security(()),
+    params(("X-Signature" = String, Header, description = "Hex HMAC-SHA256 of the raw body under the shared secret")),
+    request_body = BridgeEvent,
+    responses(
+        (status = 204, description = "Event accepted"),
+        (status = 400, description = "Malformed event"),
+        (status = 401, description = "Missing or invalid signature"),
+    ),
+)]
+pub async fn receive_event(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let Some(secret) = state.settings.mt5_bridge_hmac_secret.as_deref() else {
+        return Err((StatusCode::NOT_FOUND, "Bridge events are disabled".to_string()));
+    };
+    let signature = headers.get(SIGNATURE_HEADER).and_then(|v| v.to_str().ok());
+    if !signature.is_some_and(|signature| verify(secret, &body, signature)) {
+        warn!(signed = signature.is_some(), "Rejected bridge event with invalid signature");
+        return Err((StatusCode::UNAUTHORIZED, "Invalid or missing signature".to_string()));
+    }
+    
+    let event: BridgeEvent = serde_json::from_slice(&body)
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid bridge event: {}", e)))?;
+    
+    match &event {
+        BridgeEvent::Fill { ticket, symbol, volume, price } => {
+            info!(ticket, symbol = %symbol, volume, price, "Bridge reported fill");
+            state.mt5_client.invalidate_order(*ticket);
+        }
+        BridgeEvent::PositionClosed { ticket, symbol, profit } => {
+            info!(ticket, symbol = %symbol, profit = ?profit, "Bridge reported closed position");
+            state.trailing_stops.cancel(*ticket);
+        }
+    }
+    // Nobody listening is fine; the event has already been applied
+    let _ = state.bridge_events.send(event);
+    
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,39 @@
+//! CORS for browser-based dashboards
+
+use axum::http::{header, HeaderName, HeaderValue, Method};
+use tower_http::cors::{AllowOrigin, CorsLayer};
+use crate::config::Settings;
+use crate::correlation::CORRELATION_ID_HEADER;
+
+/// CORS layer for `mt5_cors_allowed_origins`, or `None` when no origins are set
+///
+/// Preflight requests from listed origins are answered directly; other
+/// origins get no CORS headers, so browsers block their requests.
+pub fn cors_layer(settings: &Settings) -> Option<CorsLayer> {
+    let origins = settings.cors_allowed_origins();
+    if origins.is_empty() {
+        return None;
+    }
+    
+    let allow_origin = if origins.iter().any(|origin| origin == "*") {
+        AllowOrigin::any()
+    } else {
+        AllowOrigin::list(
+            origins
+                .iter()
+                .filter_map(|origin| HeaderValue::from_str(origin).ok()),
+        )
+    };
+    
+    Some(
+        CorsLayer::new()
+            .allow_origin(allow_origin)
+            .allow_methods([Method::GET, Method::POST, Method::PATCH, Method::DELETE])
+            .allow_headers([
+                header::CONTENT_TYPE,
+                HeaderName::from_static("x-api-key"),
+                HeaderName::from_static(CORRELATION_ID_HEADER),
+            ])
+            .expose_headers([HeaderName::from_static(CORRELATION_ID_HEADER)]),
+    )
+}
@@ -0,0 +1,25 @@
+//! Mapping of MT5 errors to HTTP responses
+
+use axum::http::StatusCode;
+use crate::MT5Error;
+
+/// HTTP status reported for an MT5 error
+pub fn status_code(error: &MT5Error) -> StatusCode {
+    match error {
+        MT5Error::NotConnected | MT5Error::BridgeUnavailable(_) | MT5Error::Overloaded => {
+            StatusCode::SERVICE_UNAVAILABLE
+        }
+        MT5Error::OrderRejected { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+        MT5Error::Requote { .. } => StatusCode::CONFLICT,
+        MT5Error::NotFound(_) => StatusCode::NOT_FOUND,
+        MT5Error::InvalidOrder(_) | MT5Error::InvalidSymbol(_) => StatusCode::BAD_REQUEST,
+        MT5Error::Timeout => StatusCode::GATEWAY_TIMEOUT,
+        MT5Error::Unsupported(_) => StatusCode::NOT_IMPLEMENTED,
+        MT5Error::Deserialize(_) | MT5Error::Bridge(_) => StatusCode::BAD_GATEWAY,
+    }
+}
+
+/// Convert an MT5 error into a handler error response
+pub fn error_response(error: MT5Error) -> (StatusCode, String) {
+    (status_code(&error), error.to_string())
+}
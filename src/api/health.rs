@@ -1,40 +1,198 @@
 //! Health check endpoints
 
-use axum::{extract::State, http::StatusCode, Json};
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::sse::{Event, KeepAlive, Sse},
+    Json,
+};
+use futures_util::stream::{self, Stream, StreamExt};
 use serde::Serialize;
+use std::convert::Infallible;
+use tokio::sync::broadcast::error::RecvError;
+use utoipa::ToSchema;
 use crate::AppState;
+use crate::mt5::{BridgeCapabilities, CircuitState, ConnectionState};
 
-#[derive(Serialize)]
+/// How the service is running
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RunMode {
+    /// The HTTP service binary
+    Standalone,
+    /// Embedded in fks_execution as an `MT5Plugin`
+    Plugin,
+}
+
+#[derive(Serialize, ToSchema)]
 pub struct HealthResponse {
     pub service: String,
     pub status: String,
     pub version: String,
+    pub mode: RunMode,
+    /// Configured plugin name; `None` when standalone
+    pub plugin_name: Option<String>,
 }
 
-#[derive(Serialize)]
+impl HealthResponse {
+    /// Health of the standalone service
+    pub fn standalone() -> Self {
+        Self::new(RunMode::Standalone, None)
+    }
+
+    /// Health of the plugin named `name`
+    pub fn plugin(name: &str) -> Self {
+        Self::new(RunMode::Plugin, Some(name.to_string()))
+    }
+
+    fn new(mode: RunMode, plugin_name: Option<String>) -> Self {
+        Self {
+            service: "fks_meta".to_string(),
+            status: "healthy".to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            mode,
+            plugin_name,
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
 pub struct StatusResponse {
     pub connected: bool,
     pub mt5_status: String,
+    pub bridge_url: String,
+    pub last_success_ms: Option<u64>,
+    pub avg_latency_ms: Option<f64>,
+    pub circuit_state: CircuitState,
+    pub bridge_version: Option<String>,
+    /// Features the bridge reports; absent when they couldn't be fetched
+    pub capabilities: Option<BridgeCapabilities>,
 }
 
+#[derive(Serialize, ToSchema)]
+pub struct ReadinessResponse {
+    pub ready: bool,
+    pub state: ConnectionState,
+}
+
+/// Liveness probe: healthy whenever the process is serving requests
+#[utoipa::path(
+    get,
+    path = "/health",
+    security(()),
+    responses((status = 200, description = "Service is up", body = HealthResponse)),
+)]
 pub async fn health_check() -> Json<HealthResponse> {
-    Json(HealthResponse {
-        service: "fks_meta".to_string(),
-        status: "healthy".to_string(),
-        version: env!("CARGO_PKG_VERSION").to_string(),
-    })
+    Json(HealthResponse::standalone())
+}
+
+/// Prometheus metrics
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    security(()),
+    responses((status = 200, description = "Metrics", content_type = "text/plain", body = String)),
+)]
+pub async fn metrics() -> (StatusCode, [(header::HeaderName, &'static str); 1], String) {
+    (
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        crate::metrics::metrics().render(),
+    )
 }
 
-pub async fn metrics() -> (StatusCode, &'static str) {
-    // TODO: Implement Prometheus metrics
-    (StatusCode::OK, "# Metrics endpoint - TODO: Implement Prometheus\n")
+/// Readiness probe: 200 once the bridge answers, 503 while it doesn't
+///
+/// Probes the bridge live, so traffic is only routed to instances that can
+/// reach it.
+#[utoipa::path(
+    get,
+    path = "/ready",
+    security(()),
+    responses(
+        (status = 200, description = "Bridge reachable", body = ReadinessResponse),
+        (status = 503, description = "Bridge unreachable", body = ReadinessResponse),
+    ),
+)]
+pub async fn readiness(State(state): State<AppState>) -> (StatusCode, Json<ReadinessResponse>) {
+    let ready = state.mt5_client.health_check().await;
+    let status = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (
+        status,
+        Json(ReadinessResponse {
+            ready,
+            state: state.mt5_client.connection_state().await,
+        }),
+    )
 }
 
+/// Bridge connection status, probing the bridge
+#[utoipa::path(
+    get,
+    path = "/status",
+    responses((status = 200, description = "Connection status", body = StatusResponse)),
+)]
 pub async fn mt5_status(State(state): State<AppState>) -> Json<StatusResponse> {
-    let connected = state.mt5_client.is_connected().await;
+    let connected = state.mt5_client.health_check().await;
+    let stats = state.mt5_client.connection_stats();
+    let capabilities = if connected {
+        state.mt5_client.capabilities().await.ok()
+    } else {
+        None
+    };
     Json(StatusResponse {
         connected,
         mt5_status: if connected { "connected" } else { "disconnected" }.to_string(),
+        bridge_url: stats.bridge_url,
+        last_success_ms: stats.last_success_ms,
+        avg_latency_ms: stats.avg_latency_ms,
+        circuit_state: stats.circuit_state,
+        bridge_version: stats.bridge_version,
+        capabilities,
     })
 }
 
+/// Stream bridge connection state changes as Server-Sent Events
+///
+/// Each change is pushed as a `ConnectionEvent` JSON event named after the
+/// new state (`connected`, `reconnecting` or `disconnected`). Changes missed
+/// by a subscriber too slow to keep up are skipped. The stream ends when the
+/// service shuts down.
+#[utoipa::path(
+    get,
+    path = "/events/connection",
+    responses((status = 200, description = "Event stream", content_type = "text/event-stream", body = String)),
+)]
+pub async fn connection_events(
+    State(state): State<AppState>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let receiver = state.mt5_client.subscribe_connection_events();
+    let shutdown = state.shutdown.token().cancelled_owned();
+    
+    let events = stream::unfold(receiver, |mut receiver| async move {
+        loop {
+            match receiver.recv().await {
+                Ok(change) => {
+                    let name = serde_json::to_value(change.state)
+                        .ok()
+                        .and_then(|state| state.as_str().map(str::to_string))
+                        .unwrap_or_default();
+                    let event = Event::default()
+                        .event(name)
+                        .json_data(&change)
+                        .unwrap_or_else(|e| Event::default().event("error").data(e.to_string()));
+                    return Some((Ok(event), receiver));
+                }
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    })
+    .take_until(shutdown);
+    
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
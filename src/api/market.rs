@@ -1,16 +1,273 @@
 //! Market data endpoints
 
-use axum::{extract::{Path, State}, http::StatusCode, Json};
+use axum::{
+    extract::{
+        ws::{rejection::WebSocketUpgradeRejection, Message, WebSocket, WebSocketUpgrade},
+        Path, Query, State,
+    },
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    Json,
+};
+use futures_util::stream::{self, BoxStream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
+use std::time::Duration;
+use tracing::warn;
+use utoipa::openapi::schema::{ObjectBuilder, Schema, Type};
+use utoipa::openapi::RefOr;
+use utoipa::{IntoParams, PartialSchema, ToSchema};
 use crate::AppState;
-use crate::models::MT5MarketData;
+use crate::api::error::error_response;
+use crate::models::{is_valid_timeframe, MT5Candle, MT5MarketData, TIMEFRAMES};
+use crate::mt5::MT5Error;
 
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct MultiMarketQuery {
+    /// Comma-separated symbols, e.g. `EURUSD,GBPUSD`
+    pub symbols: String,
+}
+
+/// `Warning` header sent with quotes served from cache
+const STALE_WARNING: &str = "110 - \"Response is Stale\"";
+
+/// Upper bound on candles returned by one request
+const MAX_CANDLES: u32 = 5000;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CandlesQuery {
+    /// One of `TIMEFRAMES`, e.g. `H1`
+    #[param(value_type = Timeframe, inline)]
+    pub timeframe: String,
+    /// Number of candles, 100 by default and at most `MAX_CANDLES`
+    #[serde(default = "default_candle_count")]
+    pub count: u32,
+}
+
+fn default_candle_count() -> u32 {
+    100
+}
+
+/// Schema of a timeframe: a string enum of `TIMEFRAMES`
+struct Timeframe;
+
+impl PartialSchema for Timeframe {
+    fn schema() -> RefOr<Schema> {
+        ObjectBuilder::new()
+            .schema_type(Type::String)
+            .enum_values(Some(TIMEFRAMES.iter().copied()))
+            .into()
+    }
+}
+
+impl ToSchema for Timeframe {}
+
+#[derive(Serialize, ToSchema)]
+pub struct MarketDataResult {
+    pub symbol: String,
+    pub data: Option<MT5MarketData>,
+    pub error: Option<String>,
+}
+
+/// Current quote for a symbol
+///
+/// A quote served from cache during a bridge outage carries `stale: true`
+/// and a `Warning: 110` header.
+#[utoipa::path(
+    get,
+    path = "/market/{symbol}",
+    params(("symbol" = String, Path)),
+    responses((status = 200, description = "Market data", body = MT5MarketData)),
+)]
 pub async fn get_market_data(
     State(state): State<AppState>,
     Path(symbol): Path<String>,
-) -> Result<Json<MT5MarketData>, (StatusCode, String)> {
+) -> Result<(HeaderMap, Json<MT5MarketData>), (StatusCode, String)> {
     match state.mt5_client.get_market_data(&symbol).await {
-        Ok(data) => Ok(Json(data)),
-        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+        Ok(data) => {
+            let mut headers = HeaderMap::new();
+            if data.stale {
+                headers.insert(header::WARNING, HeaderValue::from_static(STALE_WARNING));
+            }
+            Ok((headers, Json(data)))
+        }
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// OHLC candles for charting and indicator warmup, oldest first
+#[utoipa::path(
+    get,
+    path = "/market/{symbol}/candles",
+    params(("symbol" = String, Path), CandlesQuery),
+    responses((status = 200, description = "Candles", body = Vec<MT5Candle>)),
+)]
+pub async fn get_candles(
+    State(state): State<AppState>,
+    Path(symbol): Path<String>,
+    Query(query): Query<CandlesQuery>,
+) -> Result<Json<Vec<MT5Candle>>, (StatusCode, String)> {
+    if !is_valid_timeframe(&query.timeframe) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Unknown timeframe {:?}, expected one of {}",
+                query.timeframe,
+                TIMEFRAMES.join(", ")
+            ),
+        ));
+    }
+    if query.count == 0 || query.count > MAX_CANDLES {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("count must be between 1 and {}", MAX_CANDLES),
+        ));
+    }
+    
+    match state.mt5_client.get_candles(&symbol, &query.timeframe, query.count).await {
+        Ok(candles) => Ok(Json(candles)),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// Market data for several symbols, each with its own result
+#[utoipa::path(
+    get,
+    path = "/market",
+    params(MultiMarketQuery),
+    responses((status = 200, description = "Per-symbol results", body = Vec<MarketDataResult>)),
+)]
+pub async fn get_market_data_multi(
+    State(state): State<AppState>,
+    Query(query): Query<MultiMarketQuery>,
+) -> Result<Json<Vec<MarketDataResult>>, (StatusCode, String)> {
+    let symbols: Vec<&str> = query
+        .symbols
+        .split(',')
+        .map(str::trim)
+        .filter(|symbol| !symbol.is_empty())
+        .collect();
+    if symbols.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "`symbols` must list at least one symbol".to_string()));
+    }
+    
+    let results = state.mt5_client.get_market_data_multi(&symbols).await;
+    Ok(Json(
+        results
+            .into_iter()
+            .map(|(symbol, result)| match result {
+                Ok(data) => MarketDataResult {
+                    symbol,
+                    data: Some(data),
+                    error: None,
+                },
+                Err(e) => MarketDataResult {
+                    symbol,
+                    data: None,
+                    error: Some(e.to_string()),
+                },
+            })
+            .collect(),
+    ))
+}
+
+/// Stream market data for a symbol over a WebSocket, or as Server-Sent Events
+///
+/// WebSocket upgrade requests get one `MT5MarketData` JSON text frame per
+/// tick and `{"error": ...}` frames for bridge failures; other requests get
+/// the same as SSE data and `error` events. Ticks are relayed from the
+/// bridge's tick WebSocket when `mt5_bridge_ws_url` is set, and otherwise
+/// polled every `mt5_stream_interval_ms`. Either way the backing stream is
+/// dropped as soon as the client disconnects, and ends when the service
+/// shuts down.
+#[utoipa::path(
+    get,
+    path = "/market/{symbol}/stream",
+    params(("symbol" = String, Path)),
+    responses(
+        (status = 101, description = "WebSocket of MT5MarketData JSON text frames"),
+        (status = 200, description = "Event stream", content_type = "text/event-stream", body = String),
+    ),
+)]
+pub async fn stream_market_data(
+    State(state): State<AppState>,
+    Path(symbol): Path<String>,
+    ws: Result<WebSocketUpgrade, WebSocketUpgradeRejection>,
+) -> Response {
+    let ticks = ticks(&state, &symbol)
+        .await
+        .take_until(state.shutdown.token().cancelled_owned());
+    match ws {
+        Ok(ws) => ws
+            .on_upgrade(move |socket| send_ticks(socket, ticks.boxed()))
+            .into_response(),
+        Err(_) => {
+            let events = ticks.map(|tick| {
+                let event = match tick {
+                    Ok(data) => Event::default()
+                        .json_data(&data)
+                        .unwrap_or_else(|e| Event::default().event("error").data(e.to_string())),
+                    Err(e) => Event::default().event("error").data(e.to_string()),
+                };
+                Ok::<_, Infallible>(event)
+            });
+            Sse::new(events).keep_alive(KeepAlive::default()).into_response()
+        }
     }
 }
 
+/// Quotes for `symbol` from the bridge's tick WebSocket, falling back to polling
+async fn ticks(state: &AppState, symbol: &str) -> BoxStream<'static, Result<MT5MarketData, MT5Error>> {
+    if state.settings.mt5_bridge_ws_url.is_some() {
+        match state.mt5_client.subscribe_ticks(symbol).await {
+            Ok(ticks) => return ticks,
+            Err(e) => warn!(symbol = %symbol, error = %e, "Bridge tick WebSocket unavailable, polling instead"),
+        }
+    }
+    
+    let interval = Duration::from_millis(state.settings.mt5_stream_interval_ms.max(1));
+    let ticker = tokio::time::interval(interval);
+    stream::unfold(
+        (state.clone(), symbol.to_string(), ticker),
+        |(state, symbol, mut ticker)| async move {
+            ticker.tick().await;
+            let tick = state.mt5_client.get_market_data(&symbol).await;
+            Some((tick, (state, symbol, ticker)))
+        },
+    )
+    .boxed()
+}
+
+/// Forward ticks to a WebSocket client until either side ends
+async fn send_ticks(mut socket: WebSocket, mut ticks: BoxStream<'static, Result<MT5MarketData, MT5Error>>) {
+    loop {
+        tokio::select! {
+            tick = ticks.next() => {
+                let Some(tick) = tick else {
+                    break;
+                };
+                let frame = match tick {
+                    Ok(data) => serde_json::to_string(&data),
+                    Err(e) => serde_json::to_string(&serde_json::json!({ "error": e.to_string() })),
+                };
+                let Ok(frame) = frame else {
+                    continue;
+                };
+                if socket.send(Message::Text(frame.into())).await.is_err() {
+                    return;
+                }
+            }
+            message = socket.recv() => match message {
+                // Pings are answered by axum; anything else the client sends is ignored
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+    let _ = socket.send(Message::Close(None)).await;
+}
@@ -1,7 +1,114 @@
 //! API endpoints for FKS Meta service
 
+pub mod account;
+pub mod admin;
+pub mod auth;
+pub mod bridge_events;
+pub mod cors;
+pub mod error;
 pub mod health;
 pub mod orders;
 pub mod positions;
+pub mod rate_limit;
 pub mod market;
+pub mod openapi;
+pub mod symbols;
 
+use axum::{
+    extract::DefaultBodyLimit,
+    middleware,
+    routing::{delete, get, patch, post},
+    Router,
+};
+use tower_http::compression::predicate::{DefaultPredicate, Predicate, SizeAbove};
+use tower_http::compression::CompressionLayer;
+use tower_http::limit::RequestBodyLimitLayer;
+use crate::correlation;
+use crate::AppState;
+
+/// Responses smaller than this are sent uncompressed, e.g. `/health`
+const MIN_COMPRESSED_BYTES: u16 = 1024;
+
+/// Build the HTTP router for the standalone service
+///
+/// Everything except `/health`, `/ready`, `/metrics` and the API docs sits
+/// behind the API key check. `/bridge/events` is only served when
+/// `mt5_bridge_hmac_secret` is set and is authenticated by its signature
+/// instead. With `mt5_route_prefix` set, all routes are nested under it, and
+/// `/health` and `/ready` are also kept at the root if `mt5_health_at_root`
+/// is set. Every response carries the request's `X-Correlation-Id`, and
+/// bodies over `mt5_max_body_bytes` are rejected with 413. Responses of at
+/// least `MIN_COMPRESSED_BYTES` are gzip or deflate compressed when the
+/// client accepts it; event streams never are.
+pub fn router(state: AppState) -> Router {
+    let limit_orders = middleware::from_fn_with_state(state.clone(), rate_limit::limit_orders);
+    let protected = Router::new()
+        .route("/status", get(health::mt5_status))
+        .route("/admin/reconnect", post(admin::reconnect))
+        .route("/events/connection", get(health::connection_events))
+        .route("/orders", get(orders::list_orders))
+        .route("/orders", post(orders::create_order).route_layer(limit_orders))
+        .route("/orders/batch", post(orders::create_orders_batch))
+        .route("/orders/simulate", post(orders::simulate_order))
+        .route("/orders/cancel-all", post(orders::cancel_all_orders))
+        .route("/orders/{order_id}", get(orders::get_order))
+        .route("/orders/{order_id}", delete(orders::cancel_order))
+        .route("/orders/{order_id}", patch(orders::modify_order))
+        .route("/orders/{order_id}/status", get(orders::get_order_status))
+        .route("/history/orders", get(orders::get_order_history))
+        .route("/positions", get(positions::list_positions))
+        .route("/positions/summary", get(positions::get_positions_summary))
+        .route("/positions/close-all", post(positions::close_all_positions))
+        .route("/positions/ticket/{ticket}", get(positions::get_position_by_ticket))
+        .route("/positions/ticket/{ticket}", delete(positions::close_position_by_ticket))
+        .route("/positions/{symbol}", get(positions::get_position))
+        .route("/positions/{symbol}", delete(positions::close_position))
+        .route("/positions/{symbol}/net", get(positions::get_net_position))
+        .route("/positions/{ticket}/trailing-stop", post(positions::start_trailing_stop))
+        .route("/positions/{ticket}/trailing-stop", delete(positions::cancel_trailing_stop))
+        .route("/market", get(market::get_market_data_multi))
+        .route("/market/{symbol}", get(market::get_market_data))
+        .route("/market/{symbol}/candles", get(market::get_candles))
+        .route("/market/{symbol}/stream", get(market::stream_market_data))
+        .route("/symbols", get(symbols::list_symbols))
+        .route("/symbols/{symbol}", get(symbols::get_symbol_info))
+        .route("/account", get(account::get_account_info))
+        .route_layer(middleware::from_fn_with_state(state.clone(), auth::require_api_key));
+    
+    let routes = Router::new()
+        .route("/health", get(health::health_check))
+        .route("/ready", get(health::readiness))
+        .route("/metrics", get(health::metrics))
+        .route("/openapi.json", get(openapi::openapi_json))
+        .route("/docs", get(openapi::docs_redirect))
+        .route("/docs/", get(openapi::docs))
+        .route("/docs/{*file}", get(openapi::docs))
+        .merge(protected);
+    let routes = if state.settings.mt5_bridge_hmac_secret.is_some() {
+        routes.route("/bridge/events", post(bridge_events::receive_event))
+    } else {
+        routes
+    };
+    
+    let app = match state.settings.route_prefix() {
+        Some(prefix) => {
+            let mut app = Router::new().nest(&prefix, routes);
+            if state.settings.mt5_health_at_root {
+                app = app
+                    .route("/health", get(health::health_check))
+                    .route("/ready", get(health::readiness));
+            }
+            app
+        }
+        None => routes,
+    };
+    // The configured limit replaces axum's built-in 2MB one for extractors
+    let body_limit = state.settings.mt5_max_body_bytes;
+    app.layer(DefaultBodyLimit::disable())
+        .layer(RequestBodyLimitLayer::new(body_limit))
+        .layer(middleware::from_fn(correlation::propagate))
+        .layer(CompressionLayer::new().compress_when(
+            DefaultPredicate::new().and(SizeAbove::new(MIN_COMPRESSED_BYTES)),
+        ))
+        .with_state(state)
+}
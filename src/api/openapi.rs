@@ -0,0 +1,107 @@
+//! OpenAPI document and Swagger UI for the HTTP API
+//!
+//! The spec is generated from the `#[utoipa::path]` annotations on the
+//! handlers and the `ToSchema` derives on their request/response types; a new
+//! route only needs its handler listed in `ApiDoc`. The Swagger UI assets are
+//! bundled into the binary at build time, so `/docs` works without internet
+//! access.
+
+use std::sync::Arc;
+use axum::{
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Redirect, Response},
+    Json,
+};
+use utoipa::openapi::security::{ApiKey, ApiKeyValue, SecurityScheme};
+use utoipa::openapi::{OpenApi as OpenApiDocument, Server};
+use utoipa::{Modify, OpenApi};
+use utoipa_swagger_ui::Config;
+use crate::api::{account, admin, bridge_events, health, market, orders, positions, symbols};
+use crate::AppState;
+
+/// Spec location as seen from the Swagger UI page at `docs/`, relative so it
+/// still resolves under `mt5_route_prefix`
+const SPEC_URL: &str = "../openapi.json";
+
+/// The OpenAPI 3 document describing every route
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "FKS Meta", description = "MetaTrader 5 execution service"),
+    paths(
+        health::health_check,
+        health::readiness,
+        health::metrics,
+        bridge_events::receive_event,
+        health::mt5_status,
+        admin::reconnect,
+        health::connection_events,
+        orders::list_orders,
+        orders::create_order,
+        orders::create_orders_batch,
+        orders::simulate_order,
+        orders::cancel_all_orders,
+        orders::get_order,
+        orders::cancel_order,
+        orders::modify_order,
+        orders::get_order_status,
+        orders::get_order_history,
+        positions::list_positions,
+        positions::get_positions_summary,
+        positions::close_all_positions,
+        positions::get_position_by_ticket,
+        positions::close_position_by_ticket,
+        positions::get_position,
+        positions::close_position,
+        positions::get_net_position,
+        positions::start_trailing_stop,
+        positions::cancel_trailing_stop,
+        market::get_market_data_multi,
+        market::get_market_data,
+        market::get_candles,
+        market::stream_market_data,
+        symbols::list_symbols,
+        symbols::get_symbol_info,
+        account::get_account_info,
+    ),
+    modifiers(&ApiKeyAuth),
+    security(("api_key" = [])),
+)]
+pub struct ApiDoc;
+
+/// Registers the `X-API-Key` header scheme the document's `security` refers to
+struct ApiKeyAuth;
+
+impl Modify for ApiKeyAuth {
+    fn modify(&self, openapi: &mut OpenApiDocument) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "api_key",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("X-API-Key"))),
+        );
+    }
+}
+
+pub async fn openapi_json(State(state): State<AppState>) -> Json<OpenApiDocument> {
+    let mut spec = ApiDoc::openapi();
+    if let Some(prefix) = state.settings.route_prefix() {
+        spec.servers = Some(vec![Server::new(prefix)]);
+    }
+    Json(spec)
+}
+
+/// Send `/docs` to `docs/`, so the page's relative asset links resolve
+/// inside it
+pub async fn docs_redirect() -> Redirect {
+    Redirect::permanent("docs/")
+}
+
+/// Swagger UI page and assets, rendering `/openapi.json`
+pub async fn docs(file: Option<Path<String>>) -> Response {
+    let file = file.as_ref().map_or("", |Path(file)| file.as_str());
+    match utoipa_swagger_ui::serve(file, Arc::new(Config::from(SPEC_URL))) {
+        Ok(Some(file)) => ([(header::CONTENT_TYPE, file.content_type)], file.bytes).into_response(),
+        Ok(None) => StatusCode::NOT_FOUND.into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
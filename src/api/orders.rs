@@ -1,72 +1,529 @@
 //! Order management endpoints
 
-use axum::{extract::{Path, State}, http::StatusCode, Json};
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
 use serde::{Deserialize, Serialize};
 use crate::AppState;
-use crate::MT5Order;
+use crate::api::error::error_response;
+use crate::api::rate_limit::rate_limited;
+use crate::{MT5Order, MT5OrderType, Settings};
+use crate::models::{
+    convert_from_quote, estimate_margin, quote_currency, retcode_description, MT5OrderResult, MT5Side, OrderState,
+    PriceSource,
+};
+use std::collections::HashMap;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct CreateOrderRequest {
     pub symbol: String,
+    #[schema(value_type = MT5OrderType, inline)]
     pub order_type: String,
+    /// Zero or omitted uses `MT5_DEFAULT_VOLUME`
+    #[serde(default)]
     pub volume: f64,
-    pub price: f64,
+    /// Required for pending orders; market orders fill at market without one
+    pub price: Option<f64>,
+    pub stop_limit: Option<f64>,
     pub stop_loss: Option<f64>,
     pub take_profit: Option<f64>,
     pub comment: Option<String>,
+    /// Overrides the configured magic number for this order
+    pub magic: Option<u32>,
+    /// Idempotency key; a UUID is generated when omitted
+    pub client_order_id: Option<String>,
+    /// Pending order expiry (epoch seconds)
+    pub expiration: Option<i64>,
+    /// Maximum slippage for market orders in points; defaults to `MT5_DEFAULT_DEVIATION`
+    pub deviation: Option<u32>,
+    /// Bypass the `MT5_MAX_SPREAD_POINTS` check for this order
+    #[serde(default)]
+    pub skip_spread_check: bool,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct ModifyOrderRequest {
+    pub stop_loss: Option<f64>,
+    pub take_profit: Option<f64>,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListOrdersQuery {
+    pub symbol: Option<String>,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct OrderHistoryQuery {
+    /// Start of the range (epoch seconds)
+    pub from: i64,
+    /// End of the range (epoch seconds)
+    pub to: i64,
+    pub symbol: Option<String>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct OrderResponse {
     pub ticket: u64,
     pub symbol: String,
+    pub client_order_id: Option<String>,
+    /// `filled`, `pending` or `dry_run`
     pub status: String,
+    pub retcode: Option<u32>,
+    pub retcode_description: Option<String>,
+    /// Average fill price, absent while the order is pending
+    pub average_price: Option<f64>,
+    pub filled_volume: Option<f64>,
 }
 
-pub async fn create_order(
-    State(state): State<AppState>,
-    Json(request): Json<CreateOrderRequest>,
-) -> Result<Json<OrderResponse>, (StatusCode, String)> {
+/// Just an order's lifecycle state, for cheap polling
+#[derive(Serialize, ToSchema)]
+pub struct OrderStatusResponse {
+    pub ticket: u64,
+    pub state: OrderState,
+    /// The order's volume once filled, otherwise 0
+    pub filled_volume: f64,
+}
+
+/// Estimated cost of an order that was not submitted
+#[derive(Serialize, ToSchema)]
+pub struct SimulateOrderResponse {
+    pub symbol: String,
+    pub order_type: MT5OrderType,
+    /// Volume after snapping to the symbol's volume step
+    pub volume: f64,
+    /// Order price, or the current ask/bid for market orders
+    pub price: f64,
+    /// Margin in the symbol's quote currency
+    pub margin_required: f64,
+    /// Margin in the account `currency`; `None` when no conversion rate is available
+    pub margin_required_account: Option<f64>,
+    /// Value of a one-point move on `volume`
+    pub pip_value: f64,
+    pub free_margin: f64,
+    /// Whether `free_margin` covers the order; `None` when the margin
+    /// couldn't be converted into the account currency
+    pub sufficient_margin: Option<bool>,
+    /// Account currency
+    pub currency: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct CancelOrderResult {
+    pub ticket: u64,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct BatchOrderResult {
+    pub symbol: String,
+    pub ticket: Option<u64>,
+    /// `filled`, `pending`, `dry_run` or `rejected`
+    pub status: String,
+    pub error: Option<String>,
+}
+
+impl BatchOrderResult {
+    /// Result for an order that was refused, by validation or by the bridge
+    fn rejected(symbol: String, error: String) -> Self {
+        Self {
+            symbol,
+            ticket: None,
+            status: "rejected".to_string(),
+            error: Some(error),
+        }
+    }
+}
+
+/// Reported status of an order the bridge accepted
+fn order_status(result: &MT5OrderResult) -> &'static str {
+    if result.dry_run {
+        "dry_run"
+    } else if result.is_filled() {
+        "filled"
+    } else {
+        "pending"
+    }
+}
+
+/// Convert an API order request into a validated MT5 order
+fn build_order(request: CreateOrderRequest, settings: &Settings) -> Result<MT5Order, String> {
+    let order_type = request
+        .order_type
+        .parse::<MT5OrderType>()
+        .map_err(|e| e.to_string())?;
+    let volume = settings.order_volume(request.volume)?;
+    
     let order = MT5Order {
         ticket: 0,
         symbol: request.symbol,
-        order_type: request.order_type,
-        volume: request.volume,
-        price: request.price,
+        order_type,
+        volume,
+        price: request.price.unwrap_or(0.0),
+        stop_limit: request.stop_limit,
         stop_loss: request.stop_loss,
         take_profit: request.take_profit,
         comment: request.comment,
-        magic: 123456,
-        expiration: None,
+        magic: request.magic.unwrap_or(settings.mt5_magic_number),
+        expiration: request.expiration,
+        client_order_id: Some(
+            request
+                .client_order_id
+                .unwrap_or_else(|| Uuid::new_v4().to_string()),
+        ),
+        deviation: request.deviation.or(settings.mt5_default_deviation),
+        skip_spread_check: request.skip_spread_check,
+        state: OrderState::Pending,
     };
     
+    order.validate().map_err(|e| e.to_string())?;
+    Ok(order)
+}
+
+/// Execute an order
+#[utoipa::path(
+    post,
+    path = "/orders",
+    request_body = CreateOrderRequest,
+    responses((status = 200, description = "Order accepted", body = OrderResponse)),
+)]
+pub async fn create_order(
+    State(state): State<AppState>,
+    Json(request): Json<CreateOrderRequest>,
+) -> Result<Json<OrderResponse>, (StatusCode, String)> {
+    let order = build_order(request, &state.settings)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    
     match state.mt5_client.execute_order(&order).await {
-        Ok(ticket) => Ok(Json(OrderResponse {
-            ticket,
+        Ok(result) => Ok(Json(OrderResponse {
+            ticket: result.ticket,
             symbol: order.symbol,
-            status: "pending".to_string(),
+            client_order_id: order.client_order_id,
+            status: order_status(&result).to_string(),
+            retcode: result.retcode,
+            retcode_description: result
+                .retcode
+                .and_then(retcode_description)
+                .map(str::to_string),
+            average_price: result.fill_price,
+            filled_volume: result.fill_volume,
         })),
-        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+        Err(e) => Err(error_response(e)),
     }
 }
 
+/// Estimate margin and pip value of an order without submitting it
+#[utoipa::path(
+    post,
+    path = "/orders/simulate",
+    request_body = CreateOrderRequest,
+    responses((status = 200, description = "Order estimate", body = SimulateOrderResponse)),
+)]
+pub async fn simulate_order(
+    State(state): State<AppState>,
+    Json(request): Json<CreateOrderRequest>,
+) -> Result<Json<SimulateOrderResponse>, (StatusCode, String)> {
+    let mut order = build_order(request, &state.settings)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    
+    let client = &state.mt5_client;
+    let (symbol_info, account) = tokio::try_join!(
+        client.get_symbol_info(&order.symbol),
+        client.get_account_info(),
+    )
+    .map_err(error_response)?;
+    symbol_info
+        .normalize_order(&mut order)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+    
+    if !order.order_type.is_pending() {
+        let market = client.get_market_data(&order.symbol).await.map_err(error_response)?;
+        order.price = if order.order_type.is_buy() { market.ask } else { market.bid };
+    }
+    
+    let margin_required = estimate_margin(order.volume, order.price, &symbol_info, account.leverage);
+    let rates = conversion_rates(&state, &order.symbol, &account.currency).await;
+    let margin_required_account = convert_from_quote(margin_required, &order.symbol, &account.currency, &rates);
+    Ok(Json(SimulateOrderResponse {
+        symbol: order.symbol,
+        order_type: order.order_type,
+        volume: order.volume,
+        price: order.price,
+        margin_required,
+        margin_required_account,
+        pip_value: symbol_info.pip_value(order.volume),
+        free_margin: account.free_margin,
+        sufficient_margin: margin_required_account.map(|margin| margin <= account.free_margin),
+        currency: account.currency,
+    }))
+}
+
+/// Mid prices of the pairs that convert `symbol`'s quote currency into
+/// `account_currency`, keyed by pair
+///
+/// Empty when no conversion is needed; pairs the bridge can't quote are left out.
+async fn conversion_rates(state: &AppState, symbol: &str, account_currency: &str) -> HashMap<String, f64> {
+    let account = account_currency.to_ascii_uppercase();
+    let Some(quote) = quote_currency(symbol).filter(|quote| *quote != account) else {
+        return HashMap::new();
+    };
+    
+    let direct = format!("{}{}", quote, account);
+    let inverse = format!("{}{}", account, quote);
+    state
+        .mt5_client
+        .get_market_data_multi(&[direct.as_str(), inverse.as_str()])
+        .await
+        .into_iter()
+        .filter_map(|(pair, market)| {
+            let market = market.ok()?;
+            Some((pair, market.price_for(MT5Side::Buy, PriceSource::Mid)))
+        })
+        .collect()
+}
+
+/// Submit several orders at once
+///
+/// Orders that fail validation are reported as rejected at their index and
+/// the rest are still sent. Each valid order draws on
+/// `mt5_max_orders_per_sec`; a batch the limiter can't cover in full is
+/// rejected with 429 before any order is sent, and one with more orders than
+/// the per-second limit, which it could never cover, with 413.
+#[utoipa::path(
+    post,
+    path = "/orders/batch",
+    request_body = Vec<CreateOrderRequest>,
+    responses(
+        (status = 200, description = "Per-order results", body = Vec<BatchOrderResult>),
+        (status = 413, description = "More orders than the per-second limit"),
+        (status = 429, description = "Rate limit exceeded"),
+    ),
+)]
+pub async fn create_orders_batch(
+    State(state): State<AppState>,
+    Json(requests): Json<Vec<CreateOrderRequest>>,
+) -> Result<Json<Vec<BatchOrderResult>>, Response> {
+    if let Some(burst) = state.order_limiter.burst() {
+        if requests.len() > burst as usize {
+            return Err((
+                StatusCode::PAYLOAD_TOO_LARGE,
+                format!(
+                    "Batch of {} orders exceeds the limit of {} orders per second",
+                    requests.len(),
+                    burst
+                ),
+            )
+                .into_response());
+        }
+    }
+    
+    // `None` marks an order that goes to the bridge, filled in from its result
+    let mut outcomes = Vec::with_capacity(requests.len());
+    let mut orders = Vec::with_capacity(requests.len());
+    for request in requests {
+        let symbol = request.symbol.clone();
+        match build_order(request, &state.settings) {
+            Ok(order) => {
+                orders.push(order);
+                outcomes.push(None);
+            }
+            Err(error) => outcomes.push(Some(BatchOrderResult::rejected(symbol, error))),
+        }
+    }
+    
+    let results = if orders.is_empty() {
+        Vec::new()
+    } else {
+        let count = u32::try_from(orders.len()).unwrap_or(u32::MAX);
+        state.order_limiter.try_acquire_n(count).map_err(rate_limited)?;
+        state
+            .mt5_client
+            .execute_orders(&orders)
+            .await
+            .map_err(|e| error_response(e).into_response())?
+    };
+    
+    let mut sent = orders.into_iter().zip(results).map(|(order, result)| match result {
+        Ok(result) => BatchOrderResult {
+            symbol: order.symbol,
+            ticket: Some(result.ticket),
+            status: order_status(&result).to_string(),
+            error: None,
+        },
+        Err(error) => BatchOrderResult::rejected(order.symbol, error),
+    });
+    Ok(Json(
+        outcomes
+            .into_iter()
+            .map(|outcome| outcome.unwrap_or_else(|| sent.next().expect("one result per sent order")))
+            .collect(),
+    ))
+}
+
+/// Open and pending orders, optionally only those for `?symbol=`
+#[utoipa::path(
+    get,
+    path = "/orders",
+    params(ListOrdersQuery),
+    responses((status = 200, description = "Orders", body = Vec<MT5Order>)),
+)]
+pub async fn list_orders(
+    State(state): State<AppState>,
+    Query(query): Query<ListOrdersQuery>,
+) -> Result<Json<Vec<MT5Order>>, (StatusCode, String)> {
+    match state.mt5_client.get_orders().await {
+        Ok(orders) => Ok(Json(
+            orders
+                .into_iter()
+                .filter(|order| query.symbol.as_ref().is_none_or(|symbol| &order.symbol == symbol))
+                .collect(),
+        )),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// Get an order
+#[utoipa::path(
+    get,
+    path = "/orders/{order_id}",
+    params(("order_id" = u64, Path)),
+    responses((status = 200, description = "Order", body = MT5Order)),
+)]
 pub async fn get_order(
     State(state): State<AppState>,
     Path(ticket): Path<u64>,
 ) -> Result<Json<MT5Order>, (StatusCode, String)> {
     match state.mt5_client.get_order(ticket).await {
         Ok(order) => Ok(Json(order)),
-        Err(e) => Err((StatusCode::NOT_FOUND, e.to_string())),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// Get just an order's state, served from the order cache when enabled
+#[utoipa::path(
+    get,
+    path = "/orders/{order_id}/status",
+    params(("order_id" = u64, Path)),
+    responses((status = 200, description = "Order state", body = OrderStatusResponse)),
+)]
+pub async fn get_order_status(
+    State(state): State<AppState>,
+    Path(ticket): Path<u64>,
+) -> Result<Json<OrderStatusResponse>, (StatusCode, String)> {
+    match state.mt5_client.get_order(ticket).await {
+        Ok(order) => Ok(Json(OrderStatusResponse {
+            ticket: order.ticket,
+            state: order.state,
+            filled_volume: if order.state == OrderState::Filled { order.volume } else { 0.0 },
+        })),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// Historical orders in a time range
+#[utoipa::path(
+    get,
+    path = "/history/orders",
+    params(OrderHistoryQuery),
+    responses((status = 200, description = "Orders", body = Vec<MT5Order>)),
+)]
+pub async fn get_order_history(
+    State(state): State<AppState>,
+    Query(query): Query<OrderHistoryQuery>,
+) -> Result<Json<Vec<MT5Order>>, (StatusCode, String)> {
+    if query.from > query.to {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("`from` ({}) must not be after `to` ({})", query.from, query.to),
+        ));
+    }
+    
+    match state
+        .mt5_client
+        .get_order_history(query.from, query.to, query.symbol.as_deref())
+        .await
+    {
+        Ok(orders) => Ok(Json(orders)),
+        Err(e) => Err(error_response(e)),
     }
 }
 
+/// Cancel a pending order
+#[utoipa::path(
+    delete,
+    path = "/orders/{order_id}",
+    params(("order_id" = u64, Path)),
+    responses((status = 204, description = "Order cancelled")),
+)]
 pub async fn cancel_order(
     State(state): State<AppState>,
     Path(ticket): Path<u64>,
 ) -> Result<StatusCode, (StatusCode, String)> {
     match state.mt5_client.cancel_order(ticket).await {
         Ok(_) => Ok(StatusCode::NO_CONTENT),
-        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+        Err(e) => Err(error_response(e)),
     }
 }
 
+/// Cancel all pending orders, optionally only those for `?symbol=`
+#[utoipa::path(
+    post,
+    path = "/orders/cancel-all",
+    params(ListOrdersQuery),
+    responses((status = 200, description = "Per-order results", body = Vec<CancelOrderResult>)),
+)]
+pub async fn cancel_all_orders(
+    State(state): State<AppState>,
+    Query(query): Query<ListOrdersQuery>,
+) -> Result<Json<Vec<CancelOrderResult>>, (StatusCode, String)> {
+    match state.mt5_client.cancel_all_orders(query.symbol.as_deref()).await {
+        Ok(results) => Ok(Json(
+            results
+                .into_iter()
+                .map(|(ticket, result)| CancelOrderResult {
+                    ticket,
+                    success: result.is_ok(),
+                    error: result.err(),
+                })
+                .collect(),
+        )),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// Modify an order's stop loss and/or take profit
+#[utoipa::path(
+    patch,
+    path = "/orders/{order_id}",
+    params(("order_id" = u64, Path)),
+    request_body = ModifyOrderRequest,
+    responses((status = 204, description = "Order modified")),
+)]
+pub async fn modify_order(
+    State(state): State<AppState>,
+    Path(ticket): Path<u64>,
+    Json(request): Json<ModifyOrderRequest>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    if request.stop_loss.is_none() && request.take_profit.is_none() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "At least one of stop_loss or take_profit is required".to_string(),
+        ));
+    }
+    
+    match state
+        .mt5_client
+        .modify_order(ticket, request.stop_loss, request.take_profit)
+        .await
+    {
+        Ok(_) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => Err(error_response(e)),
+    }
+}
@@ -1,19 +1,101 @@
 //! Position management endpoints
 
-use axum::{extract::{Path, State}, http::StatusCode, Json};
-use serde::Serialize;
+use axum::{extract::{Path, Query, State}, http::StatusCode, Json};
+use serde::{Deserialize, Serialize};
 use crate::AppState;
-use crate::models::MT5Position;
+use crate::api::error::error_response;
+use crate::models::{summarize, MT5Position, NetPosition, PositionFilter, PositionSummary};
+use crate::mt5::TrailingStop;
+use std::time::Duration;
+use utoipa::{IntoParams, ToSchema};
 
+/// Most positions returned in one `GET /positions` page
+pub const MAX_POSITIONS_LIMIT: usize = 1000;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PageQuery {
+    /// Page size, capped at `MAX_POSITIONS_LIMIT` (the default)
+    pub limit: Option<usize>,
+    #[serde(default)]
+    pub offset: usize,
+}
+
+/// One page of positions and the number matching overall
+#[derive(Serialize, ToSchema)]
+pub struct PositionsPage {
+    pub total: usize,
+    pub offset: usize,
+    pub limit: usize,
+    pub positions: Vec<MT5Position>,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CloseAllQuery {
+    pub symbol: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ClosePositionResult {
+    pub ticket: u64,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+/// List open positions, optionally only those matching `?symbol=` and `?magic=`
+///
+/// `?limit=` and `?offset=` select a page of the matching positions; an
+/// offset past the end gives an empty page.
+#[utoipa::path(
+    get,
+    path = "/positions",
+    params(PositionFilter, PageQuery),
+    responses((status = 200, description = "Page of positions", body = PositionsPage)),
+)]
 pub async fn list_positions(
     State(state): State<AppState>,
-) -> Result<Json<Vec<MT5Position>>, (StatusCode, String)> {
+    Query(filter): Query<PositionFilter>,
+    Query(page): Query<PageQuery>,
+) -> Result<Json<PositionsPage>, (StatusCode, String)> {
+    if page.limit == Some(0) {
+        return Err((StatusCode::BAD_REQUEST, "`limit` must be at least 1".to_string()));
+    }
+    let limit = page.limit.unwrap_or(MAX_POSITIONS_LIMIT).min(MAX_POSITIONS_LIMIT);
+    
+    match state.mt5_client.get_positions_matching(&filter).await {
+        Ok(positions) => Ok(Json(PositionsPage {
+            total: positions.len(),
+            offset: page.offset,
+            limit,
+            positions: positions.into_iter().skip(page.offset).take(limit).collect(),
+        })),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// Totals and per-symbol exposure across open positions
+#[utoipa::path(
+    get,
+    path = "/positions/summary",
+    responses((status = 200, description = "Summary", body = PositionSummary)),
+)]
+pub async fn get_positions_summary(
+    State(state): State<AppState>,
+) -> Result<Json<PositionSummary>, (StatusCode, String)> {
     match state.mt5_client.get_positions().await {
-        Ok(positions) => Ok(Json(positions)),
-        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+        Ok(positions) => Ok(Json(summarize(&positions))),
+        Err(e) => Err(error_response(e)),
     }
 }
 
+/// Get the position for a symbol
+#[utoipa::path(
+    get,
+    path = "/positions/{symbol}",
+    params(("symbol" = String, Path)),
+    responses((status = 200, description = "Position", body = MT5Position)),
+)]
 pub async fn get_position(
     State(state): State<AppState>,
     Path(symbol): Path<String>,
@@ -21,17 +103,167 @@ pub async fn get_position(
     match state.mt5_client.get_position(&symbol).await {
         Ok(Some(position)) => Ok(Json(position)),
         Ok(None) => Err((StatusCode::NOT_FOUND, "Position not found".to_string())),
-        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+        Err(e) => Err(error_response(e)),
     }
 }
 
+/// Net volume and volume-weighted entry across every position in a symbol
+#[utoipa::path(
+    get,
+    path = "/positions/{symbol}/net",
+    params(("symbol" = String, Path)),
+    responses((status = 200, description = "Net position", body = NetPosition)),
+)]
+pub async fn get_net_position(
+    State(state): State<AppState>,
+    Path(symbol): Path<String>,
+) -> Result<Json<NetPosition>, (StatusCode, String)> {
+    match state.mt5_client.get_net_position(&symbol).await {
+        Ok(Some(position)) => Ok(Json(position)),
+        Ok(None) => Err((StatusCode::NOT_FOUND, "Position not found".to_string())),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// Get a position by ticket
+#[utoipa::path(
+    get,
+    path = "/positions/ticket/{ticket}",
+    params(("ticket" = u64, Path)),
+    responses((status = 200, description = "Position", body = MT5Position)),
+)]
+pub async fn get_position_by_ticket(
+    State(state): State<AppState>,
+    Path(ticket): Path<u64>,
+) -> Result<Json<MT5Position>, (StatusCode, String)> {
+    match state.mt5_client.get_position_by_ticket(ticket).await {
+        Ok(Some(position)) => Ok(Json(position)),
+        Ok(None) => Err((StatusCode::NOT_FOUND, "Position not found".to_string())),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// Close every position for a symbol
+#[utoipa::path(
+    delete,
+    path = "/positions/{symbol}",
+    params(("symbol" = String, Path)),
+    responses((status = 204, description = "Positions closed")),
+)]
 pub async fn close_position(
+    State(state): State<AppState>,
+    Path(symbol): Path<String>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    match state.mt5_client.close_position_by_symbol(&symbol).await {
+        Ok(_) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// Close a position by ticket
+#[utoipa::path(
+    delete,
+    path = "/positions/ticket/{ticket}",
+    params(("ticket" = u64, Path)),
+    responses((status = 204, description = "Position closed")),
+)]
+pub async fn close_position_by_ticket(
     State(state): State<AppState>,
     Path(ticket): Path<u64>,
 ) -> Result<StatusCode, (StatusCode, String)> {
     match state.mt5_client.close_position(ticket).await {
         Ok(_) => Ok(StatusCode::NO_CONTENT),
-        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+        Err(e) => Err(error_response(e)),
     }
 }
 
+/// Close all positions, optionally only those for `?symbol=`
+#[utoipa::path(
+    post,
+    path = "/positions/close-all",
+    params(CloseAllQuery),
+    responses((status = 200, description = "Per-position results", body = Vec<ClosePositionResult>)),
+)]
+pub async fn close_all_positions(
+    State(state): State<AppState>,
+    Query(query): Query<CloseAllQuery>,
+) -> Result<Json<Vec<ClosePositionResult>>, (StatusCode, String)> {
+    match state.mt5_client.close_all_positions(query.symbol.as_deref()).await {
+        Ok(results) => Ok(Json(
+            results
+                .into_iter()
+                .map(|(ticket, result)| ClosePositionResult {
+                    ticket,
+                    success: result.is_ok(),
+                    error: result.err(),
+                })
+                .collect(),
+        )),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// Start trailing a position's stop loss, replacing any existing trailing stop
+#[utoipa::path(
+    post,
+    path = "/positions/{ticket}/trailing-stop",
+    params(("ticket" = u64, Path)),
+    request_body = TrailingStop,
+    responses((status = 201, description = "Trailing stop started")),
+)]
+pub async fn start_trailing_stop(
+    State(state): State<AppState>,
+    Path(ticket): Path<u64>,
+    Json(trailing): Json<TrailingStop>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    if !(trailing.distance_points.is_finite() && trailing.distance_points > 0.0) {
+        return Err((StatusCode::BAD_REQUEST, "`distance_points` must be positive".to_string()));
+    }
+    if !(trailing.step_points.is_finite() && trailing.step_points >= 0.0) {
+        return Err((StatusCode::BAD_REQUEST, "`step_points` must not be negative".to_string()));
+    }
+    
+    let position = state
+        .mt5_client
+        .get_position_by_ticket(ticket)
+        .await
+        .map_err(error_response)?;
+    let Some(position) = position else {
+        return Err((StatusCode::NOT_FOUND, format!("Position {} not found", ticket)));
+    };
+    let symbol_info = state
+        .mt5_client
+        .get_symbol_info(&position.symbol)
+        .await
+        .map_err(error_response)?;
+    
+    state.trailing_stops.start(
+        state.mt5_client.clone(),
+        &position,
+        trailing,
+        symbol_info,
+        Duration::from_millis(state.settings.mt5_stream_interval_ms.max(1)),
+    );
+    Ok(StatusCode::CREATED)
+}
+
+/// Stop trailing a position's stop loss, leaving the stop where it is
+#[utoipa::path(
+    delete,
+    path = "/positions/{ticket}/trailing-stop",
+    params(("ticket" = u64, Path)),
+    responses(
+        (status = 204, description = "Trailing stop cancelled"),
+        (status = 404, description = "No trailing stop for the position"),
+    ),
+)]
+pub async fn cancel_trailing_stop(
+    State(state): State<AppState>,
+    Path(ticket): Path<u64>,
+) -> StatusCode {
+    if state.trailing_stops.cancel(ticket) {
+        StatusCode::NO_CONTENT
+    } else {
+        StatusCode::NOT_FOUND
+    }
+}
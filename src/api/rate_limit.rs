@@ -0,0 +1,97 @@
+//! Order rate limiting middleware
+
+use axum::{
+    extract::{Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use crate::AppState;
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+/// Token bucket refilled at a fixed rate, allowing bursts of one second's worth
+#[derive(Debug)]
+pub struct RateLimiter {
+    per_sec: u32,
+    bucket: Mutex<Bucket>,
+}
+
+impl RateLimiter {
+    /// Create a limiter admitting `per_sec` requests per second; 0 disables it
+    pub fn new(per_sec: u32) -> Self {
+        Self {
+            per_sec,
+            bucket: Mutex::new(Bucket {
+                tokens: per_sec as f64,
+                refilled_at: Instant::now(),
+            }),
+        }
+    }
+    
+    /// Most tokens one `try_acquire_n` can take, or `None` when disabled
+    pub fn burst(&self) -> Option<u32> {
+        (self.per_sec > 0).then_some(self.per_sec)
+    }
+    
+    /// Take a token, or return how long until one is available
+    pub fn try_acquire(&self) -> Result<(), Duration> {
+        self.try_acquire_n(1)
+    }
+    
+    /// Take `n` tokens at once, or none and return how long until `n` are available
+    ///
+    /// `n` above the per-second limit is never admitted.
+    pub fn try_acquire_n(&self, n: u32) -> Result<(), Duration> {
+        if self.per_sec == 0 || n == 0 {
+            return Ok(());
+        }
+        
+        let rate = self.per_sec as f64;
+        let needed = n as f64;
+        let mut bucket = self.bucket.lock().unwrap();
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(rate);
+        bucket.refilled_at = now;
+        
+        if bucket.tokens >= needed {
+            bucket.tokens -= needed;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((needed - bucket.tokens) / rate))
+        }
+    }
+}
+
+/// 429 response asking the client to retry after `wait`
+pub fn rate_limited(wait: Duration) -> Response {
+    // Retry-After is in whole seconds
+    let retry_after = wait.as_secs_f64().ceil().max(1.0) as u64;
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        [(header::RETRY_AFTER, retry_after.to_string())],
+        "Order rate limit exceeded",
+    )
+        .into_response()
+}
+
+/// Reject order submissions beyond `mt5_max_orders_per_sec` with 429
+///
+/// Batches are charged one token per order by their handler instead.
+pub async fn limit_orders(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    match state.order_limiter.try_acquire() {
+        Ok(()) => next.run(request).await,
+        Err(wait) => rate_limited(wait),
+    }
+}
@@ -0,0 +1,49 @@
+//! Symbol specification endpoints
+
+use axum::{extract::{Path, Query, State}, http::StatusCode, Json};
+use serde::Deserialize;
+use utoipa::IntoParams;
+use crate::AppState;
+use crate::api::error::error_response;
+use crate::models::MT5SymbolInfo;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SymbolsQuery {
+    /// Bridge symbol group, e.g. `forex` or `metals`
+    pub group: Option<String>,
+}
+
+/// Symbols the account can trade (cached for five minutes)
+#[utoipa::path(
+    get,
+    path = "/symbols",
+    params(SymbolsQuery),
+    responses((status = 200, description = "Symbol names", body = Vec<String>)),
+)]
+pub async fn list_symbols(
+    State(state): State<AppState>,
+    Query(query): Query<SymbolsQuery>,
+) -> Result<Json<Vec<String>>, (StatusCode, String)> {
+    match state.mt5_client.list_symbols(query.group.as_deref()).await {
+        Ok(symbols) => Ok(Json(symbols)),
+        Err(e) => Err(error_response(e)),
+    }
+}
+
+/// Symbol specification
+#[utoipa::path(
+    get,
+    path = "/symbols/{symbol}",
+    params(("symbol" = String, Path)),
+    responses((status = 200, description = "Symbol specification", body = MT5SymbolInfo)),
+)]
+pub async fn get_symbol_info(
+    State(state): State<AppState>,
+    Path(symbol): Path<String>,
+) -> Result<Json<MT5SymbolInfo>, (StatusCode, String)> {
+    match state.mt5_client.get_symbol_info(&symbol).await {
+        Ok(info) => Ok(Json(info)),
+        Err(e) => Err(error_response(e)),
+    }
+}
@@ -1,12 +1,216 @@
 //! Configuration management for FKS Meta
 
+use crate::logging::LogFormat;
+use crate::models::PriceSource;
+use anyhow::{bail, Context};
+use ::config::{Config, File, FileFormat};
+use reqwest::header::{HeaderName, HeaderValue};
 use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::env;
+use std::fmt;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Settings left out of `Settings::summary` entirely
+const SECRET_FIELDS: [&str; 4] = [
+    "mt5_api_key",
+    "mt5_password",
+    "mt5_bridge_token",
+    "mt5_bridge_hmac_secret",
+];
+
+/// `url` without credentials, and cut to its origin when `origin_only`
+pub(crate) fn loggable_url(url: &str, origin_only: bool) -> String {
+    match reqwest::Url::parse(url) {
+        Ok(url) if origin_only => url.origin().ascii_serialization(),
+        Ok(mut parsed) if !parsed.username().is_empty() || parsed.password().is_some() => {
+            let _ = parsed.set_username("");
+            let _ = parsed.set_password(None);
+            parsed.to_string()
+        }
+        Ok(_) => url.to_string(),
+        // Unparseable URLs are rejected by `validate`; don't echo one
+        Err(_) => "[invalid]".to_string(),
+    }
+}
+
+/// Retry attempts above this are almost certainly a typo
+const MAX_RETRY_ATTEMPTS: u32 = 20;
+
+/// Longest an order may wait for the bridge to reconnect, so callers never hang
+const MAX_ORDER_QUEUE_WAIT_MS: u64 = 60_000;
+
+/// Parse a `--listen` value: one or more comma-separated socket addresses
+///
+/// IPv6 addresses are bracketed, e.g. `0.0.0.0:8005,[::]:8005`.
+pub fn parse_listen_addrs(listen: &str) -> anyhow::Result<Vec<SocketAddr>> {
+    let mut addrs: Vec<SocketAddr> = Vec::new();
+    for entry in listen.split(',').map(str::trim) {
+        if entry.is_empty() {
+            bail!("--listen {:?} contains an empty address", listen);
+        }
+        let addr: SocketAddr = entry.parse().with_context(|| {
+            format!(
+                "--listen address {:?} is not a valid socket address, expected e.g. 0.0.0.0:8005 or [::]:8005",
+                entry
+            )
+        })?;
+        if addrs.contains(&addr) {
+            bail!("--listen address {} is listed more than once", addr);
+        }
+        addrs.push(addr);
+    }
+    Ok(addrs)
+}
+
+/// Parse a boolean flag such as `MT5_DRY_RUN`
+///
+/// Accepts `true`/`false`, `1`/`0` and `yes`/`no` in any case; anything else
+/// is an error rather than a silent `false`.
+pub fn parse_flag(name: &str, value: &str) -> anyhow::Result<bool> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "true" | "1" | "yes" => Ok(true),
+        "false" | "0" | "no" => Ok(false),
+        _ => bail!("{} {:?} is not a boolean, expected true/false, 1/0 or yes/no", name, value),
+    }
+}
+
+/// Parse an `MT5_MAGIC_LABELS` value such as `123456=trend,654321=meanrev`
+pub fn parse_magic_labels(labels: &str) -> anyhow::Result<HashMap<u32, String>> {
+    let mut map = HashMap::new();
+    for entry in labels.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+        let Some((magic, label)) = entry.split_once('=') else {
+            bail!("MT5_MAGIC_LABELS entry {:?} must look like 123456=label", entry);
+        };
+        let magic: u32 = magic
+            .trim()
+            .parse()
+            .with_context(|| format!("MT5_MAGIC_LABELS entry {:?} has an invalid magic number", entry))?;
+        let label = label.trim();
+        if label.is_empty() {
+            bail!("MT5_MAGIC_LABELS entry {:?} has an empty label", entry);
+        }
+        if map.insert(magic, label.to_string()).is_some() {
+            bail!("MT5_MAGIC_LABELS lists magic number {} more than once", magic);
+        }
+    }
+    Ok(map)
+}
+
+/// Keeps `mt5_magic_labels` in its `123456=label` spec form when (de)serialized
+mod magic_labels_spec {
+    use serde::{de, Deserialize, Deserializer, Serializer};
+    use std::collections::HashMap;
+    
+    pub fn serialize<S: Serializer>(labels: &HashMap<u32, String>, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut entries: Vec<_> = labels.iter().collect();
+        entries.sort();
+        let spec: Vec<String> = entries
+            .into_iter()
+            .map(|(magic, label)| format!("{}={}", magic, label))
+            .collect();
+        serializer.serialize_str(&spec.join(","))
+    }
+    
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<HashMap<u32, String>, D::Error> {
+        let spec = String::deserialize(deserializer)?;
+        super::parse_magic_labels(&spec).map_err(|e| de::Error::custom(format!("{:#}", e)))
+    }
+}
+
+/// Parse an `MT5_BRIDGE_EXTRA_HEADERS` value such as `X-Desk:fx,X-Region:eu`
+pub fn parse_header_specs(specs: &str) -> anyhow::Result<Vec<(HeaderName, HeaderValue)>> {
+    specs
+        .split(',')
+        .map(str::trim)
+        .filter(|spec| !spec.is_empty())
+        .map(|spec| {
+            let Some((name, value)) = spec.split_once(':') else {
+                bail!("MT5_BRIDGE_EXTRA_HEADERS entry {:?} must look like Name:Value", spec);
+            };
+            let name = HeaderName::from_bytes(name.trim().as_bytes()).with_context(|| {
+                format!("MT5_BRIDGE_EXTRA_HEADERS entry {:?} has an invalid header name", spec)
+            })?;
+            let value = HeaderValue::from_str(value.trim()).with_context(|| {
+                format!("MT5_BRIDGE_EXTRA_HEADERS entry {:?} has an invalid header value", spec)
+            })?;
+            Ok((name, value))
+        })
+        .collect()
+}
+
+/// Coherent timeout, retry and backoff defaults chosen by `MT5_OPERATION_PROFILE`
+///
+/// Each field is only a default: the matching `MT5_*` variable still wins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OperationProfile {
+    pub timeout_ms: u64,
+    /// `None` leaves order submissions on `timeout_ms`
+    pub order_timeout_ms: Option<u64>,
+    /// `None` leaves market data requests on `timeout_ms`
+    pub market_timeout_ms: Option<u64>,
+    pub retry_attempts: u32,
+    /// First retry delay, also the starting reconnect backoff
+    pub retry_delay_ms: u64,
+}
+
+/// The built-in defaults, used when `MT5_OPERATION_PROFILE` is unset
+const BALANCED_PROFILE: OperationProfile = OperationProfile {
+    timeout_ms: 5000,
+    order_timeout_ms: None,
+    market_timeout_ms: None,
+    retry_attempts: 3,
+    retry_delay_ms: 1000,
+};
+
+/// Defaults for an `MT5_OPERATION_PROFILE` name
+///
+/// - `aggressive`: fail fast for latency-sensitive strategies
+/// - `balanced`: the built-in defaults
+/// - `conservative`: wait out a slow bridge rather than fail
+pub fn operation_profile(name: &str) -> anyhow::Result<OperationProfile> {
+    match name.trim().to_ascii_lowercase().as_str() {
+        "aggressive" => Ok(OperationProfile {
+            timeout_ms: 2000,
+            order_timeout_ms: Some(3000),
+            market_timeout_ms: Some(1000),
+            retry_attempts: 1,
+            retry_delay_ms: 200,
+        }),
+        "balanced" => Ok(BALANCED_PROFILE),
+        "conservative" => Ok(OperationProfile {
+            timeout_ms: 10_000,
+            order_timeout_ms: Some(30_000),
+            market_timeout_ms: Some(5000),
+            retry_attempts: 5,
+            retry_delay_ms: 2000,
+        }),
+        _ => bail!(
+            "MT5_OPERATION_PROFILE {:?} is not one of aggressive, balanced or conservative",
+            name
+        ),
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Settings {
     pub service_name: String,
     pub service_port: u16,
+    pub mt5_api_key: Option<String>,
+    /// Path prefix all routes are served under, e.g. `/mt5`
+    pub mt5_route_prefix: Option<String>,
+    /// Also serve `/health` at the root when a route prefix is set
+    pub mt5_health_at_root: bool,
+    /// Comma-separated browser origins allowed by CORS, or `*` for any
+    pub mt5_cors_allowed_origins: Option<String>,
+    /// Largest request body the API accepts; larger ones get 413
+    pub mt5_max_body_bytes: usize,
+    /// Log output: human-readable `text` or one JSON object per line
+    pub mt5_log_format: LogFormat,
     
     // MT5 Configuration
     pub mt5_terminal_path: Option<String>,
@@ -15,56 +219,554 @@ pub struct Settings {
     pub mt5_password: Option<String>,
     pub mt5_server: Option<String>,
     pub mt5_symbol_prefix: String,
+    /// Uppercase symbols before sending them to case-sensitive bridges
+    pub mt5_symbol_uppercase: bool,
+    pub mt5_magic_number: u32,
+    /// Strategy names by magic number, parsed from `MT5_MAGIC_LABELS` such as
+    /// `123456=trend,654321=meanrev`, used to label logs and metrics
+    #[serde(with = "magic_labels_spec")]
+    pub mt5_magic_labels: HashMap<u32, String>,
+    /// Plugin order comment with `{confidence}`, `{symbol}` and `{side}` placeholders
+    pub mt5_order_comment_template: Option<String>,
     
     // Connection Settings
+    /// `aggressive`, `balanced` or `conservative`; sets the defaults of the
+    /// timeout and retry settings below (see `operation_profile`)
+    pub mt5_operation_profile: Option<String>,
     pub mt5_timeout_ms: u64,
+    /// Timeout for order submissions (defaults to `mt5_timeout_ms`)
+    pub mt5_order_timeout_ms: Option<u64>,
+    /// Timeout for market data requests (defaults to `mt5_timeout_ms`)
+    pub mt5_market_timeout_ms: Option<u64>,
+    /// Reject market orders while the spread is wider than this many points
+    pub mt5_max_spread_points: Option<f64>,
+    /// Largest volume a single order may have
+    pub mt5_max_order_volume: Option<f64>,
+    /// Volume used when an order is submitted with none
+    pub mt5_default_volume: Option<f64>,
+    /// Slippage in points allowed on market orders that don't set their own
+    pub mt5_default_deviation: Option<u32>,
+    /// Quote that prices market orders sent without a price: the order's
+    /// side (ask for buys, bid for sells) unless set to `bid`, `ask` or `mid`
+    pub mt5_price_source: PriceSource,
+    /// How long an order waits for a disconnected bridge to come back (0 fails at once)
+    pub mt5_order_queue_wait_ms: u64,
     pub mt5_retry_attempts: u32,
     pub mt5_retry_delay_ms: u64,
     pub mt5_testnet: bool,
+    /// Validate orders and return synthetic fills without sending them to the bridge
+    pub mt5_dry_run: bool,
+    pub mt5_stream_interval_ms: u64,
+    /// Concurrent bridge requests when fetching market data for several symbols
+    pub mt5_market_data_concurrency: usize,
+    /// How long `get_order` results are cached (0 disables the cache)
+    pub mt5_order_cache_ttl_ms: u64,
+    /// How old cached market data may be when served while the circuit is open (0 disables it)
+    pub mt5_market_stale_ttl_ms: u64,
+    /// Warn when the bridge returns a quote older than this
+    pub mt5_max_quote_age_ms: Option<u64>,
+    /// How long shutdown waits for background tasks to finish
+    pub mt5_shutdown_grace_ms: u64,
+    /// How often open positions are reconciled against the last snapshot (0 disables it)
+    pub mt5_reconcile_interval_ms: u64,
+    /// Order submissions admitted per second across all clients (0 disables the limit)
+    pub mt5_max_orders_per_sec: u32,
+    /// Consecutive bridge failures that open the circuit breaker (0 disables it)
+    pub mt5_circuit_failure_threshold: u32,
+    /// How long the open circuit fails fast before probing the bridge again
+    pub mt5_circuit_cooldown_ms: u64,
     
     // Bridge Service (if using HTTP bridge)
     pub mt5_bridge_url: Option<String>,
+    /// Base URL of the bridge's tick WebSocket; market streams poll when unset
+    pub mt5_bridge_ws_url: Option<String>,
+    pub mt5_bridge_token: Option<String>,
+    /// Static headers sent on every bridge request, as comma-separated `Name:Value` pairs
+    pub mt5_bridge_extra_headers: Option<String>,
+    /// Trace bridge request/response bodies, with credentials redacted
+    pub mt5_log_bridge_bodies: bool,
+    /// Idle connections kept per bridge host (reqwest default: unlimited)
+    pub mt5_bridge_pool_max_idle: usize,
+    /// How long an idle bridge connection is kept open (reqwest default: 90s)
+    pub mt5_bridge_pool_idle_timeout_ms: u64,
+    /// Block startup until the bridge answers, for up to `mt5_bridge_wait_timeout_ms`
+    pub mt5_wait_for_bridge: bool,
+    pub mt5_bridge_wait_timeout_ms: u64,
+    /// Bridge requests in flight at once; further calls queue (0 disables the limit)
+    pub mt5_max_concurrent_requests: usize,
+    /// How long a queued bridge call waits for a slot before failing as overloaded
+    pub mt5_request_queue_timeout_ms: u64,
+    
+    // Notifications
+    /// URL that filled plugin orders are POSTed to
+    pub mt5_fill_webhook_url: Option<String>,
+    /// Shared secret the bridge signs `POST /bridge/events` bodies with;
+    /// the route is disabled when unset
+    pub mt5_bridge_hmac_secret: Option<String>,
+    
+    /// Variables whose values failed to parse, and unknown settings file
+    /// keys, reported by `validate`
+    #[serde(skip)]
+    invalid_vars: Vec<String>,
+}
+
+/// Typed lookups of settings variables that record unparseable values
+///
+/// A bad value reads as unset so loading can carry on, and is listed for
+/// `Settings::validate` to reject. Empty values count as unset.
+struct ParsedVars<'a, F> {
+    var: &'a F,
+    errors: RefCell<Vec<String>>,
+}
+
+impl<'a, F> ParsedVars<'a, F>
+where
+    F: Fn(&str) -> Result<String, env::VarError>,
+{
+    fn new(var: &'a F) -> Self {
+        Self { var, errors: RefCell::new(Vec::new()) }
+    }
+    
+    fn value(&self, name: &str) -> Option<String> {
+        (self.var)(name).ok().filter(|value| !value.trim().is_empty())
+    }
+    
+    fn parse<T>(&self, name: &str) -> Option<T>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        let value = self.value(name)?;
+        match value.trim().parse() {
+            Ok(parsed) => Some(parsed),
+            Err(e) => {
+                self.errors.borrow_mut().push(format!("{} {:?} is invalid: {}", name, value, e));
+                None
+            }
+        }
+    }
+    
+    fn flag(&self, name: &str) -> Option<bool> {
+        let value = self.value(name)?;
+        match parse_flag(name, &value) {
+            Ok(flag) => Some(flag),
+            Err(e) => {
+                self.errors.borrow_mut().push(e.to_string());
+                None
+            }
+        }
+    }
 }
 
 impl Settings {
     pub fn from_env() -> anyhow::Result<Self> {
+        Self::from_vars(|name| env::var(name))
+    }
+    
+    /// Load settings from a TOML or YAML file, with env vars taking precedence
+    ///
+    /// The file uses the `Settings` field names (`mt5_bridge_url`,
+    /// `mt5_timeout_ms`, ...); fields it omits keep their env or default
+    /// values, and keys that aren't fields are reported by `validate`.
+    /// `.yaml`/`.yml` files are read as YAML, anything else as TOML.
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let format = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("yaml" | "yml") => FileFormat::Yaml,
+            _ => FileFormat::Toml,
+        };
+        let context = || format!("failed to load settings from {}", path.display());
+        let file: serde_json::Map<String, serde_json::Value> = Config::builder()
+            .add_source(File::from(path).format(format))
+            .build()
+            .and_then(Config::try_deserialize)
+            .with_context(context)?;
+        
+        // Layer the file over the defaults and deserialize, rejecting mistyped values
+        let provided: Vec<String> = file.keys().cloned().collect();
+        let defaults = Self::from_vars(|_| Err(env::VarError::NotPresent))?;
+        let mut layered = serde_json::to_value(defaults)?;
+        // Keys that aren't settings (usually misspelt ones) would otherwise be ignored
+        let unknown: Vec<String> = provided
+            .iter()
+            .filter(|key| layered.get(key.as_str()).is_none())
+            .map(|key| format!("{} in {} is not a known setting", key, path.display()))
+            .collect();
+        if let serde_json::Value::Object(values) = &mut layered {
+            values.extend(file);
+        }
+        let file_settings: Settings = serde_json::from_value(layered).with_context(context)?;
+        let file_values = serde_json::to_value(file_settings)?;
+        
+        // File values stand in for unset env vars, so both go through the same parsing;
+        // fields the file omits stay unset so defaults such as the operation profile apply
+        let mut settings = Self::from_vars(|name| {
+            env::var(name).or_else(|_| {
+                let field = name.to_ascii_lowercase();
+                if !provided.contains(&field) {
+                    return Err(env::VarError::NotPresent);
+                }
+                match file_values.get(field) {
+                    Some(serde_json::Value::String(value)) => Ok(value.clone()),
+                    Some(serde_json::Value::Null) | None => Err(env::VarError::NotPresent),
+                    Some(value) => Ok(value.to_string()),
+                }
+            })
+        })?;
+        settings.invalid_vars.extend(unknown);
+        Ok(settings)
+    }
+    
+    /// Build settings from a variable lookup keyed by env var name
+    pub fn from_vars<F>(var: F) -> anyhow::Result<Self>
+    where
+        F: Fn(&str) -> Result<String, env::VarError>,
+    {
+        // An unknown profile is rejected by `validate`
+        let mt5_operation_profile = var("MT5_OPERATION_PROFILE").ok();
+        let profile = mt5_operation_profile
+            .as_deref()
+            .and_then(|name| operation_profile(name).ok())
+            .unwrap_or(BALANCED_PROFILE);
+        let vars = ParsedVars::new(&var);
+        
         Ok(Self {
-            service_name: env::var("SERVICE_NAME")
+            service_name: var("SERVICE_NAME")
                 .unwrap_or_else(|_| "fks_meta".to_string()),
-            service_port: env::var("SERVICE_PORT")
-                .unwrap_or_else(|_| "8005".to_string())
-                .parse()
-                .unwrap_or(8005),
+            service_port: vars.parse("SERVICE_PORT").unwrap_or(8005),
+            mt5_api_key: var("MT5_API_KEY").ok(),
+            mt5_route_prefix: var("MT5_ROUTE_PREFIX").ok(),
+            mt5_health_at_root: vars.flag("MT5_HEALTH_AT_ROOT").unwrap_or(false),
+            mt5_cors_allowed_origins: var("MT5_CORS_ALLOWED_ORIGINS").ok(),
+            mt5_max_body_bytes: vars.parse("MT5_MAX_BODY_BYTES").unwrap_or(262_144),
+            mt5_log_format: vars.parse("MT5_LOG_FORMAT").unwrap_or_default(),
             
-            mt5_terminal_path: env::var("MT5_TERMINAL_PATH").ok(),
-            mt5_data_path: env::var("MT5_DATA_PATH").ok(),
-            mt5_account_number: env::var("MT5_ACCOUNT_NUMBER")
-                .ok()
-                .and_then(|s| s.parse().ok()),
-            mt5_password: env::var("MT5_PASSWORD").ok(),
-            mt5_server: env::var("MT5_SERVER").ok(),
-            mt5_symbol_prefix: env::var("MT5_SYMBOL_PREFIX")
+            mt5_terminal_path: var("MT5_TERMINAL_PATH").ok(),
+            mt5_data_path: var("MT5_DATA_PATH").ok(),
+            mt5_account_number: vars.parse("MT5_ACCOUNT_NUMBER"),
+            mt5_password: var("MT5_PASSWORD").ok(),
+            mt5_server: var("MT5_SERVER").ok(),
+            mt5_symbol_prefix: var("MT5_SYMBOL_PREFIX")
                 .unwrap_or_else(|_| String::new()),
+            mt5_symbol_uppercase: vars.flag("MT5_SYMBOL_UPPERCASE").unwrap_or(false),
+            mt5_magic_number: vars.parse("MT5_MAGIC_NUMBER").unwrap_or(123456),
+            mt5_magic_labels: match var("MT5_MAGIC_LABELS") {
+                Ok(labels) => parse_magic_labels(&labels)?,
+                Err(_) => HashMap::new(),
+            },
+            mt5_order_comment_template: var("MT5_ORDER_COMMENT_TEMPLATE").ok(),
             
-            mt5_timeout_ms: env::var("MT5_TIMEOUT_MS")
-                .unwrap_or_else(|_| "5000".to_string())
-                .parse()
-                .unwrap_or(5000),
-            mt5_retry_attempts: env::var("MT5_RETRY_ATTEMPTS")
-                .unwrap_or_else(|_| "3".to_string())
-                .parse()
-                .unwrap_or(3),
-            mt5_retry_delay_ms: env::var("MT5_RETRY_DELAY_MS")
-                .unwrap_or_else(|_| "1000".to_string())
-                .parse()
-                .unwrap_or(1000),
-            mt5_testnet: env::var("MT5_TESTNET")
-                .unwrap_or_else(|_| "false".to_string())
-                .parse()
-                .unwrap_or(false),
+            mt5_operation_profile,
+            mt5_timeout_ms: vars.parse("MT5_TIMEOUT_MS").unwrap_or(profile.timeout_ms),
+            mt5_order_timeout_ms: vars.parse("MT5_ORDER_TIMEOUT_MS").or(profile.order_timeout_ms),
+            mt5_market_timeout_ms: vars.parse("MT5_MARKET_TIMEOUT_MS").or(profile.market_timeout_ms),
+            mt5_max_spread_points: vars.parse("MT5_MAX_SPREAD_POINTS"),
+            mt5_max_order_volume: vars.parse("MT5_MAX_ORDER_VOLUME"),
+            mt5_default_volume: vars.parse("MT5_DEFAULT_VOLUME"),
+            mt5_default_deviation: vars.parse("MT5_DEFAULT_DEVIATION"),
+            mt5_price_source: vars.parse("MT5_PRICE_SOURCE").unwrap_or_default(),
+            mt5_order_queue_wait_ms: vars.parse("MT5_ORDER_QUEUE_WAIT_MS").unwrap_or(0),
+            mt5_retry_attempts: vars.parse("MT5_RETRY_ATTEMPTS").unwrap_or(profile.retry_attempts),
+            mt5_retry_delay_ms: vars.parse("MT5_RETRY_DELAY_MS").unwrap_or(profile.retry_delay_ms),
+            mt5_testnet: vars.flag("MT5_TESTNET").unwrap_or(false),
+            mt5_dry_run: vars.flag("MT5_DRY_RUN").unwrap_or(false),
+            mt5_stream_interval_ms: vars.parse("MT5_STREAM_INTERVAL_MS").unwrap_or(1000),
+            mt5_market_data_concurrency: vars.parse("MT5_MARKET_DATA_CONCURRENCY").unwrap_or(8),
+            mt5_order_cache_ttl_ms: vars.parse("MT5_ORDER_CACHE_TTL_MS").unwrap_or(0),
+            mt5_max_quote_age_ms: vars.parse("MT5_MAX_QUOTE_AGE_MS"),
+            mt5_market_stale_ttl_ms: vars.parse("MT5_MARKET_STALE_TTL_MS").unwrap_or(30_000),
+            mt5_shutdown_grace_ms: vars.parse("MT5_SHUTDOWN_GRACE_MS").unwrap_or(10_000),
+            mt5_reconcile_interval_ms: vars.parse("MT5_RECONCILE_INTERVAL_MS").unwrap_or(0),
+            mt5_max_orders_per_sec: vars.parse("MT5_MAX_ORDERS_PER_SEC").unwrap_or(0),
+            mt5_circuit_failure_threshold: vars.parse("MT5_CIRCUIT_FAILURE_THRESHOLD").unwrap_or(5),
+            mt5_circuit_cooldown_ms: vars.parse("MT5_CIRCUIT_COOLDOWN_MS").unwrap_or(30_000),
             
-            mt5_bridge_url: env::var("MT5_BRIDGE_URL").ok(),
+            mt5_bridge_url: var("MT5_BRIDGE_URL").ok(),
+            mt5_bridge_ws_url: var("MT5_BRIDGE_WS_URL").ok(),
+            mt5_bridge_token: var("MT5_BRIDGE_TOKEN").ok(),
+            mt5_bridge_extra_headers: var("MT5_BRIDGE_EXTRA_HEADERS").ok(),
+            mt5_log_bridge_bodies: vars.flag("MT5_LOG_BRIDGE_BODIES").unwrap_or(false),
+            mt5_bridge_pool_max_idle: vars.parse("MT5_BRIDGE_POOL_MAX_IDLE").unwrap_or(usize::MAX),
+            mt5_bridge_pool_idle_timeout_ms: vars.parse("MT5_BRIDGE_POOL_IDLE_TIMEOUT_MS").unwrap_or(90_000),
+            mt5_wait_for_bridge: vars.flag("MT5_WAIT_FOR_BRIDGE").unwrap_or(false),
+            mt5_bridge_wait_timeout_ms: vars.parse("MT5_BRIDGE_WAIT_TIMEOUT_MS").unwrap_or(30_000),
+            mt5_max_concurrent_requests: vars.parse("MT5_MAX_CONCURRENT_REQUESTS").unwrap_or(0),
+            mt5_request_queue_timeout_ms: vars.parse("MT5_REQUEST_QUEUE_TIMEOUT_MS").unwrap_or(5000),
+            
+            mt5_fill_webhook_url: var("MT5_FILL_WEBHOOK_URL").ok(),
+            mt5_bridge_hmac_secret: var("MT5_BRIDGE_HMAC_SECRET").ok(),
+            invalid_vars: vars.errors.into_inner(),
         })
     }
+    
+    /// Effective settings, safe to log
+    ///
+    /// Passwords, tokens and secrets are left out entirely, the account
+    /// number is masked to its last four digits, credentials in the bridge
+    /// URL are dropped, the fill webhook (whose path is often its secret) is
+    /// cut to its origin and extra bridge headers are listed by name only.
+    pub fn summary(&self) -> serde_json::Value {
+        let mut summary = serde_json::to_value(self).unwrap_or_default();
+        let Some(fields) = summary.as_object_mut() else {
+            return summary;
+        };
+        for field in SECRET_FIELDS {
+            fields.remove(field);
+        }
+        if let Some(account) = self.mt5_account_number {
+            let account = account.to_string();
+            let visible = account.len().saturating_sub(4);
+            let masked = format!("{}{}", "*".repeat(visible), &account[visible..]);
+            fields.insert("mt5_account_number".to_string(), masked.into());
+        }
+        if let Some(url) = &self.mt5_bridge_url {
+            fields.insert("mt5_bridge_url".to_string(), loggable_url(url, false).into());
+        }
+        if let Some(url) = &self.mt5_bridge_ws_url {
+            fields.insert("mt5_bridge_ws_url".to_string(), loggable_url(url, false).into());
+        }
+        if let Some(url) = &self.mt5_fill_webhook_url {
+            fields.insert("mt5_fill_webhook_url".to_string(), loggable_url(url, true).into());
+        }
+        if let Some(headers) = &self.mt5_bridge_extra_headers {
+            let names: Vec<&str> = headers
+                .split(',')
+                .filter_map(|spec| spec.split_once(':'))
+                .map(|(name, _)| name.trim())
+                .collect();
+            fields.insert("mt5_bridge_extra_headers".to_string(), names.into());
+        }
+        summary
+    }
+    
+    /// Normalized route prefix: a leading `/`, no trailing `/`, `None` if empty
+    pub fn route_prefix(&self) -> Option<String> {
+        let prefix = self.mt5_route_prefix.as_deref()?.trim_matches('/');
+        (!prefix.is_empty()).then(|| format!("/{}", prefix))
+    }
+    
+    /// Origins listed in `mt5_cors_allowed_origins`, empty when CORS is off
+    pub fn cors_allowed_origins(&self) -> Vec<String> {
+        self.mt5_cors_allowed_origins
+            .as_deref()
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|origin| !origin.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+    
+    /// Strategy label for `magic`: its `mt5_magic_labels` entry, or the
+    /// number itself when it has none
+    pub fn magic_label(&self, magic: u32) -> String {
+        self.mt5_magic_labels
+            .get(&magic)
+            .cloned()
+            .unwrap_or_else(|| magic.to_string())
+    }
+    
+    /// Timeout for order submissions
+    pub fn order_timeout(&self) -> Duration {
+        Duration::from_millis(self.mt5_order_timeout_ms.unwrap_or(self.mt5_timeout_ms))
+    }
+    
+    /// Timeout for market data requests
+    pub fn market_timeout(&self) -> Duration {
+        Duration::from_millis(self.mt5_market_timeout_ms.unwrap_or(self.mt5_timeout_ms))
+    }
+    
+    /// Volume to submit for a requested `volume`
+    ///
+    /// A zero volume is replaced by `mt5_default_volume` when one is set; the
+    /// result must not exceed `mt5_max_order_volume`.
+    pub fn order_volume(&self, volume: f64) -> Result<f64, String> {
+        let volume = match self.mt5_default_volume {
+            Some(default) if volume == 0.0 => default,
+            _ => volume,
+        };
+        if let Some(max_volume) = self.mt5_max_order_volume {
+            if volume > max_volume {
+                return Err(format!(
+                    "volume {} exceeds the maximum order volume of {}",
+                    volume, max_volume
+                ));
+            }
+        }
+        Ok(volume)
+    }
+    
+    /// Reject settings that can't work, so misconfiguration fails at startup
+    ///
+    /// Every variable `from_env` couldn't parse and every unknown key in a
+    /// settings file is named here, along with the combinations that would
+    /// otherwise only surface as runtime errors.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if !self.invalid_vars.is_empty() {
+            bail!("{}", self.invalid_vars.join("; "));
+        }
+        if let Some(bridge_url) = &self.mt5_bridge_url {
+            match reqwest::Url::parse(bridge_url) {
+                Ok(url) if matches!(url.scheme(), "http" | "https") => {}
+                Ok(url) => bail!(
+                    "MT5_BRIDGE_URL must use http or https, got scheme {:?}",
+                    url.scheme()
+                ),
+                Err(e) => bail!("MT5_BRIDGE_URL {:?} is not a valid URL: {}", bridge_url, e),
+            }
+        }
+        if let Some(ws_url) = &self.mt5_bridge_ws_url {
+            match reqwest::Url::parse(ws_url) {
+                Ok(url) if matches!(url.scheme(), "ws" | "wss") => {}
+                Ok(url) => bail!(
+                    "MT5_BRIDGE_WS_URL must use ws or wss, got scheme {:?}",
+                    url.scheme()
+                ),
+                Err(e) => bail!("MT5_BRIDGE_WS_URL {:?} is not a valid URL: {}", ws_url, e),
+            }
+        }
+        if let Some(webhook_url) = &self.mt5_fill_webhook_url {
+            match reqwest::Url::parse(webhook_url) {
+                Ok(url) if matches!(url.scheme(), "http" | "https") => {}
+                Ok(url) => bail!(
+                    "MT5_FILL_WEBHOOK_URL must use http or https, got scheme {:?}",
+                    url.scheme()
+                ),
+                Err(e) => bail!("MT5_FILL_WEBHOOK_URL {:?} is not a valid URL: {}", webhook_url, e),
+            }
+        }
+        if self.service_port == 0 {
+            bail!("SERVICE_PORT must be non-zero");
+        }
+        if self.mt5_timeout_ms == 0 {
+            bail!("MT5_TIMEOUT_MS must be greater than 0");
+        }
+        if self.mt5_order_timeout_ms == Some(0) {
+            bail!("MT5_ORDER_TIMEOUT_MS must be greater than 0");
+        }
+        if self.mt5_market_timeout_ms == Some(0) {
+            bail!("MT5_MARKET_TIMEOUT_MS must be greater than 0");
+        }
+        if self.mt5_wait_for_bridge && self.mt5_bridge_wait_timeout_ms == 0 {
+            bail!("MT5_BRIDGE_WAIT_TIMEOUT_MS must be greater than 0 when MT5_WAIT_FOR_BRIDGE is set");
+        }
+        if self.mt5_max_quote_age_ms == Some(0) {
+            bail!("MT5_MAX_QUOTE_AGE_MS must be greater than 0");
+        }
+        if self.mt5_retry_attempts > MAX_RETRY_ATTEMPTS {
+            bail!(
+                "MT5_RETRY_ATTEMPTS is {}, expected at most {}",
+                self.mt5_retry_attempts,
+                MAX_RETRY_ATTEMPTS
+            );
+        }
+        if let Some(max_spread) = self.mt5_max_spread_points {
+            if !max_spread.is_finite() || max_spread < 0.0 {
+                bail!("MT5_MAX_SPREAD_POINTS must be a non-negative number, got {}", max_spread);
+            }
+        }
+        if let Some(max_volume) = self.mt5_max_order_volume {
+            if !max_volume.is_finite() || max_volume <= 0.0 {
+                bail!("MT5_MAX_ORDER_VOLUME must be a positive number, got {}", max_volume);
+            }
+        }
+        if let Some(default_volume) = self.mt5_default_volume {
+            if !default_volume.is_finite() || default_volume <= 0.0 {
+                bail!("MT5_DEFAULT_VOLUME must be a positive number, got {}", default_volume);
+            }
+            if self.mt5_max_order_volume.is_some_and(|max_volume| default_volume > max_volume) {
+                bail!("MT5_DEFAULT_VOLUME exceeds MT5_MAX_ORDER_VOLUME");
+            }
+        }
+        if self.mt5_max_body_bytes == 0 {
+            bail!("MT5_MAX_BODY_BYTES must be greater than 0");
+        }
+        if self.mt5_stream_interval_ms == 0 {
+            bail!("MT5_STREAM_INTERVAL_MS must be greater than 0");
+        }
+        if self.mt5_order_queue_wait_ms > MAX_ORDER_QUEUE_WAIT_MS {
+            bail!(
+                "MT5_ORDER_QUEUE_WAIT_MS must be at most {}, got {}",
+                MAX_ORDER_QUEUE_WAIT_MS,
+                self.mt5_order_queue_wait_ms
+            );
+        }
+        if self.mt5_api_key.as_deref().is_some_and(str::is_empty) {
+            bail!("MT5_API_KEY is set but empty");
+        }
+        if let Some(profile) = &self.mt5_operation_profile {
+            operation_profile(profile)?;
+        }
+        if let Some(headers) = &self.mt5_bridge_extra_headers {
+            parse_header_specs(headers)?;
+        }
+        if self.mt5_bridge_hmac_secret.as_deref().is_some_and(str::is_empty) {
+            bail!("MT5_BRIDGE_HMAC_SECRET is set but empty");
+        }
+        for origin in self.cors_allowed_origins() {
+            if origin == "*" {
+                continue;
+            }
+            match reqwest::Url::parse(&origin) {
+                Ok(url) if matches!(url.scheme(), "http" | "https") && url.path() == "/" => {}
+                _ => bail!(
+                    "MT5_CORS_ALLOWED_ORIGINS entry {:?} must be `*` or an origin like https://dashboard.example.com",
+                    origin
+                ),
+            }
+        }
+        Ok(())
+    }
+    
+    /// Override settings with values from a plugin JSON config
+    ///
+    /// Keys mirror the plugin configuration format (`bridge_url`,
+    /// `terminal_path`, `account_number`, ...). Missing or mistyped keys leave
+    /// the env-loaded value untouched.
+    pub fn apply_overrides(&mut self, config: &serde_json::Value) {
+        let str_value = |key: &str| config.get(key).and_then(|v| v.as_str()).map(str::to_string);
+        
+        if let Some(terminal_path) = str_value("terminal_path") {
+            self.mt5_terminal_path = Some(terminal_path);
+        }
+        if let Some(data_path) = str_value("data_path") {
+            self.mt5_data_path = Some(data_path);
+        }
+        if let Some(account_number) = config.get("account_number").and_then(|v| v.as_u64()) {
+            self.mt5_account_number = Some(account_number);
+        }
+        if let Some(password) = str_value("password") {
+            self.mt5_password = Some(password);
+        }
+        if let Some(server) = str_value("server") {
+            self.mt5_server = Some(server);
+        }
+        if let Some(symbol_prefix) = str_value("symbol_prefix") {
+            self.mt5_symbol_prefix = symbol_prefix;
+        }
+        if let Some(magic_number) = config
+            .get("magic_number")
+            .and_then(|v| v.as_u64())
+            .and_then(|v| u32::try_from(v).ok())
+        {
+            self.mt5_magic_number = magic_number;
+        }
+        if let Some(template) = str_value("order_comment_template") {
+            self.mt5_order_comment_template = Some(template);
+        }
+        if let Some(timeout_ms) = config.get("timeout_ms").and_then(|v| v.as_u64()) {
+            self.mt5_timeout_ms = timeout_ms;
+        }
+        if let Some(testnet) = config.get("testnet").and_then(|v| v.as_bool()) {
+            self.mt5_testnet = testnet;
+        }
+        if let Some(dry_run) = config.get("dry_run").and_then(|v| v.as_bool()) {
+            self.mt5_dry_run = dry_run;
+        }
+        if let Some(bridge_url) = str_value("bridge_url") {
+            self.mt5_bridge_url = Some(bridge_url);
+        }
+        if let Some(webhook_url) = str_value("fill_webhook_url") {
+            self.mt5_fill_webhook_url = Some(webhook_url);
+        }
+    }
 }
-
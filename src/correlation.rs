@@ -0,0 +1,73 @@
+//! Correlation ids for tracing a request across services
+//!
+//! Every API request carries an `X-Correlation-Id`, taken from the caller or
+//! generated when absent. The id is echoed on the response, recorded on the
+//! request's tracing span, and scoped to the handler's task so the bridge
+//! client can forward it on every bridge call made while serving it.
+
+use axum::{
+    extract::Request,
+    http::HeaderValue,
+    middleware::Next,
+    response::Response,
+};
+use std::future::Future;
+use tracing::Instrument;
+use uuid::Uuid;
+
+/// Header carrying the correlation id
+pub const CORRELATION_ID_HEADER: &str = "x-correlation-id";
+
+/// Longest caller-supplied id accepted; longer ones are replaced
+const MAX_CORRELATION_ID_LEN: usize = 128;
+
+tokio::task_local! {
+    static CURRENT: CorrelationId;
+}
+
+/// Correlation id of the request being served, also stored as a request extension
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CorrelationId(pub String);
+
+impl CorrelationId {
+    /// Use the caller's id if it is a sane header value, otherwise a new UUID
+    fn from_header(value: Option<&HeaderValue>) -> Self {
+        let id = value
+            .and_then(|value| value.to_str().ok())
+            .map(str::trim)
+            .filter(|id| !id.is_empty() && id.len() <= MAX_CORRELATION_ID_LEN);
+        match id {
+            Some(id) => Self(id.to_string()),
+            None => Self(Uuid::new_v4().to_string()),
+        }
+    }
+}
+
+/// Correlation id of the request the current task is serving, if any
+pub fn current() -> Option<String> {
+    CURRENT.try_with(|id| id.0.clone()).ok()
+}
+
+/// Run `future` with `id` as the current correlation id
+pub async fn scope<F: Future>(id: CorrelationId, future: F) -> F::Output {
+    CURRENT.scope(id, future).await
+}
+
+/// Assign the request a correlation id and propagate it
+pub async fn propagate(mut request: Request, next: Next) -> Response {
+    let id = CorrelationId::from_header(request.headers().get(CORRELATION_ID_HEADER));
+    request.extensions_mut().insert(id.clone());
+
+    let span = tracing::info_span!(
+        "request",
+        correlation_id = %id.0,
+        method = %request.method(),
+        path = %request.uri().path(),
+    );
+    let header = HeaderValue::from_str(&id.0).ok();
+    let mut response = scope(id, next.run(request)).instrument(span).await;
+    if let Some(header) = header {
+        response.headers_mut().insert(CORRELATION_ID_HEADER, header);
+    }
+    response
+}
@@ -4,20 +4,60 @@
 
 pub mod api;
 pub mod config;
+pub mod correlation;
+pub mod logging;
+pub mod metrics;
 pub mod models;
 pub mod mt5;
+pub mod shutdown;
 
-pub use models::{MT5Order, MT5Position, MT5MarketData};
-pub use mt5::{MT5Client, MT5Plugin};
+pub use models::{
+    BridgeEvent, MT5AccountInfo, MT5Candle, MT5Order, MT5OrderResult, MT5OrderType, MT5Position, MT5MarketData, MT5Side,
+    MT5SymbolInfo, NetPosition, OrderState,
+};
+pub use mt5::{MT5Client, MT5Error, MT5Plugin};
 pub use config::Settings;
 
+use api::rate_limit::RateLimiter;
+use mt5::{Reconciler, TrailingStops};
+use shutdown::Shutdown;
 use std::sync::Arc;
+use tokio::sync::broadcast;
+
+/// Bridge events buffered for slow subscribers before they start missing some
+const BRIDGE_EVENT_CAPACITY: usize = 256;
 
 /// Application state shared across handlers
 #[derive(Clone)]
 pub struct AppState {
     pub mt5_client: Arc<MT5Client>,
     pub settings: Arc<Settings>,
+    /// Shared limit on order submissions (`MT5_MAX_ORDERS_PER_SEC`)
+    pub order_limiter: Arc<RateLimiter>,
+    /// Trailing stops managed by this service, by position ticket
+    pub trailing_stops: Arc<TrailingStops>,
+    /// Last position snapshot from the reconciliation task
+    pub reconciler: Arc<Reconciler>,
+    /// Cancelled on shutdown; background tasks are spawned through it
+    pub shutdown: Shutdown,
+    /// Verified events received from the bridge on `POST /bridge/events`
+    pub bridge_events: broadcast::Sender<BridgeEvent>,
+}
+
+impl AppState {
+    pub fn new(mt5_client: Arc<MT5Client>, settings: Arc<Settings>) -> Self {
+        let order_limiter = Arc::new(RateLimiter::new(settings.mt5_max_orders_per_sec));
+        let shutdown = Shutdown::new();
+        Self {
+            mt5_client,
+            settings,
+            order_limiter,
+            trailing_stops: Arc::new(TrailingStops::new(shutdown.clone())),
+            reconciler: Arc::new(Reconciler::new()),
+            shutdown,
+            bridge_events: broadcast::channel(BRIDGE_EVENT_CAPACITY).0,
+        }
+    }
 }
 
 /// Plugin name identifier
@@ -3,81 +3,143 @@
 //! Standalone service that provides MT5 integration via HTTP API
 //! Can be used directly or as a plugin for fks_execution
 
-use axum::{
-    extract::State,
-    http::StatusCode,
-    routing::{get, post, delete},
-    Json, Router,
-};
+use anyhow::Context;
 use clap::Parser;
+use futures_util::future::try_join_all;
+use socket2::{Domain, Protocol, Socket, Type};
+use std::future::IntoFuture;
 use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::signal;
-use tracing::{info, error};
+use tracing::{info, warn};
 
+use fks_meta::config::parse_listen_addrs;
 use fks_meta::{AppState, Settings, MT5Client};
 
+/// Pending connections queued per listener
+const LISTEN_BACKLOG: i32 = 1024;
+
 #[derive(Parser, Debug)]
 #[command(version, about = "FKS Meta - MetaTrader 5 Plugin Service")]
 struct Cli {
-    #[arg(long, default_value = "0.0.0.0:8005")]
-    listen: String,
+    /// Address(es) to listen on, comma-separated, e.g. `0.0.0.0:8005,[::]:8005`
+    /// (default: 0.0.0.0 on SERVICE_PORT)
+    #[arg(long)]
+    listen: Option<String>,
+
+    /// TOML or YAML settings file; env vars override its values
+    #[arg(long)]
+    config: Option<PathBuf>,
 }
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    // Initialize tracing
-    tracing_subscriber::fmt::init();
-
     let cli = Cli::parse();
-    let settings = Arc::new(Settings::from_env()?);
+    let settings = match &cli.config {
+        Some(path) => Settings::from_file(path)?,
+        None => Settings::from_env()?,
+    };
+    settings.validate().context("invalid configuration")?;
+    let addrs = match &cli.listen {
+        Some(listen) => parse_listen_addrs(listen)?,
+        None => vec![SocketAddr::from(([0, 0, 0, 0], settings.service_port))],
+    };
+    let settings = Arc::new(settings);
+    
+    // Initialize tracing
+    fks_meta::logging::init(settings.mt5_log_format, &settings.service_name)?;
     
     info!(
         service = "fks_meta",
         version = env!("CARGO_PKG_VERSION"),
         "Starting FKS Meta service"
     );
+    info!(settings = %settings.summary(), "Effective settings");
+
+    if settings.mt5_api_key.is_none() {
+        warn!("MT5_API_KEY is not set, the HTTP API is unauthenticated");
+    }
 
     // Initialize MT5 client
     let mt5_client = Arc::new(MT5Client::new(settings.clone()).await?);
     
-    let app_state = AppState {
-        mt5_client,
-        settings,
-    };
+    let shutdown_grace = Duration::from_millis(settings.mt5_shutdown_grace_ms);
+    let reconcile_interval = settings.mt5_reconcile_interval_ms;
+    let cors = fks_meta::api::cors::cors_layer(&settings);
+    let app_state = AppState::new(mt5_client.clone(), settings);
+    let shutdown = app_state.shutdown.clone();
+    
+    if reconcile_interval > 0 {
+        app_state.reconciler.start(
+            mt5_client,
+            &shutdown,
+            Duration::from_millis(reconcile_interval),
+        );
+    }
 
     // Build router
-    let app = Router::new()
-        .route("/health", get(fks_meta::api::health::health_check))
-        .route("/metrics", get(fks_meta::api::health::metrics))
-        .route("/status", get(fks_meta::api::health::mt5_status))
-        .route("/orders", post(fks_meta::api::orders::create_order))
-        .route("/orders/{order_id}", get(fks_meta::api::orders::get_order))
-        .route("/orders/{order_id}", delete(fks_meta::api::orders::cancel_order))
-        .route("/positions", get(fks_meta::api::positions::list_positions))
-        .route("/positions/{symbol}", get(fks_meta::api::positions::get_position))
-        .route("/positions/{symbol}", delete(fks_meta::api::positions::close_position))
-        .route("/market/{symbol}", get(fks_meta::api::market::get_market_data))
-        .with_state(app_state);
-
-    // Parse address
-    let addr: SocketAddr = cli.listen.parse()?;
-    
-    info!(
-        service = "fks_meta",
-        address = %addr,
-        "Listening on"
-    );
+    let mut app = fks_meta::api::router(app_state);
+    if let Some(cors) = cors {
+        app = app.layer(cors);
+    }
 
-    // Start server
-    let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal())
-        .await?;
+    // Bind every address before serving any, so a bad one fails startup
+    // With IPv4 addresses also listed, IPv6 sockets must not claim IPv4 traffic too
+    let v6_only = addrs.iter().any(SocketAddr::is_ipv4);
+    let mut listeners = Vec::with_capacity(addrs.len());
+    for addr in &addrs {
+        listeners.push(bind(*addr, v6_only).with_context(|| format!("failed to listen on {}", addr))?);
+        info!(
+            service = "fks_meta",
+            address = %addr,
+            "Listening on"
+        );
+    }
+
+    // Start one server per address, all sharing the router and state
+    {
+        let shutdown = shutdown.clone();
+        tokio::spawn(async move {
+            shutdown_signal().await;
+            // End streams and background tasks so in-flight requests can drain
+            shutdown.trigger();
+        });
+    }
+    let servers = listeners.into_iter().map(|listener| {
+        let token = shutdown.token();
+        axum::serve(listener, app.clone())
+            .with_graceful_shutdown(async move { token.cancelled().await })
+            .into_future()
+    });
+    try_join_all(servers).await?;
+
+    if !shutdown.drain(shutdown_grace).await {
+        warn!(
+            running = shutdown.running(),
+            grace_ms = shutdown_grace.as_millis() as u64,
+            "Background tasks still running after the shutdown grace period"
+        );
+    }
 
     Ok(())
 }
 
+/// Bind a TCP listener, optionally keeping an IPv6 socket off IPv4
+fn bind(addr: SocketAddr, v6_only: bool) -> std::io::Result<tokio::net::TcpListener> {
+    let socket = Socket::new(Domain::for_address(addr), Type::STREAM, Some(Protocol::TCP))?;
+    if addr.is_ipv6() && v6_only {
+        socket.set_only_v6(true)?;
+    }
+    #[cfg(unix)]
+    socket.set_reuse_address(true)?;
+    socket.bind(&addr.into())?;
+    socket.listen(LISTEN_BACKLOG)?;
+    socket.set_nonblocking(true)?;
+    tokio::net::TcpListener::from_std(socket.into())
+}
+
 async fn shutdown_signal() {
     let ctrl_c = async {
         signal::ctrl_c()
@@ -0,0 +1,197 @@
+//! Prometheus metrics for FKS Meta
+//!
+//! A small process-wide registry of counters and histograms rendered in the
+//! Prometheus text exposition format by the `/metrics` endpoint.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Upper bounds (seconds) of the bridge latency histogram buckets
+const LATENCY_BUCKETS: [f64; 11] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// Monotonic counter
+pub struct Counter(AtomicU64);
+
+impl Counter {
+    const fn new() -> Self {
+        Self(AtomicU64::new(0))
+    }
+
+    pub fn inc(&self) {
+        self.inc_by(1);
+    }
+
+    pub fn inc_by(&self, value: u64) {
+        self.0.fetch_add(value, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Counters keyed by the value of one label
+pub struct LabeledCounter(Mutex<BTreeMap<String, u64>>);
+
+impl LabeledCounter {
+    const fn new() -> Self {
+        Self(Mutex::new(BTreeMap::new()))
+    }
+
+    pub fn inc(&self, label: &str) {
+        *self.0.lock().unwrap().entry(label.to_string()).or_default() += 1;
+    }
+
+    pub fn get(&self, label: &str) -> u64 {
+        self.0.lock().unwrap().get(label).copied().unwrap_or_default()
+    }
+}
+
+/// Cumulative histogram with fixed buckets
+pub struct Histogram {
+    buckets: [AtomicU64; LATENCY_BUCKETS.len()],
+    sum_micros: AtomicU64,
+    count: AtomicU64,
+}
+
+impl Histogram {
+    const fn new() -> Self {
+        Self {
+            buckets: [const { AtomicU64::new(0) }; LATENCY_BUCKETS.len()],
+            sum_micros: AtomicU64::new(0),
+            count: AtomicU64::new(0),
+        }
+    }
+
+    pub fn observe(&self, duration: Duration) {
+        let seconds = duration.as_secs_f64();
+        for (bucket, bound) in self.buckets.iter().zip(LATENCY_BUCKETS) {
+            if seconds <= bound {
+                bucket.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        self.sum_micros
+            .fetch_add(duration.as_micros() as u64, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+}
+
+/// Service metrics registry
+pub struct Metrics {
+    pub orders_submitted: Counter,
+    /// Orders submitted, by strategy label of their magic number
+    pub orders_by_strategy: LabeledCounter,
+    pub orders_failed: Counter,
+    pub positions_closed: Counter,
+    pub bridge_reconnects: Counter,
+    /// Bridge request retries, by operation
+    pub bridge_retries: LabeledCounter,
+    pub bridge_request_duration: Histogram,
+}
+
+static METRICS: Metrics = Metrics {
+    orders_submitted: Counter::new(),
+    orders_by_strategy: LabeledCounter::new(),
+    orders_failed: Counter::new(),
+    positions_closed: Counter::new(),
+    bridge_reconnects: Counter::new(),
+    bridge_retries: LabeledCounter::new(),
+    bridge_request_duration: Histogram::new(),
+};
+
+/// Global metrics registry
+pub fn metrics() -> &'static Metrics {
+    &METRICS
+}
+
+impl Metrics {
+    /// Render all metrics in Prometheus text exposition format
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+
+        render_counter(
+            &mut out,
+            "mt5_orders_submitted_total",
+            "Orders submitted to the MT5 bridge",
+            &self.orders_submitted,
+        );
+        render_labeled_counter(
+            &mut out,
+            "mt5_orders_by_strategy_total",
+            "Orders submitted to the MT5 bridge, by strategy",
+            "strategy",
+            &self.orders_by_strategy,
+        );
+        render_counter(
+            &mut out,
+            "mt5_orders_failed_total",
+            "Orders that failed to execute",
+            &self.orders_failed,
+        );
+        render_counter(
+            &mut out,
+            "mt5_positions_closed_total",
+            "Positions closed via the MT5 bridge",
+            &self.positions_closed,
+        );
+        render_counter(
+            &mut out,
+            "mt5_bridge_reconnects_total",
+            "Reconnection attempts to the MT5 bridge",
+            &self.bridge_reconnects,
+        );
+        render_labeled_counter(
+            &mut out,
+            "mt5_bridge_retries_total",
+            "Retried MT5 bridge requests, by operation",
+            "operation",
+            &self.bridge_retries,
+        );
+
+        let histogram = &self.bridge_request_duration;
+        let name = "mt5_bridge_request_duration_seconds";
+        let _ = writeln!(out, "# HELP {} Latency of MT5 bridge HTTP requests", name);
+        let _ = writeln!(out, "# TYPE {} histogram", name);
+        for (bucket, bound) in histogram.buckets.iter().zip(LATENCY_BUCKETS) {
+            let _ = writeln!(
+                out,
+                "{}_bucket{{le=\"{}\"}} {}",
+                name,
+                bound,
+                bucket.load(Ordering::Relaxed)
+            );
+        }
+        let _ = writeln!(out, "{}_bucket{{le=\"+Inf\"}} {}", name, histogram.count());
+        let _ = writeln!(
+            out,
+            "{}_sum {}",
+            name,
+            histogram.sum_micros.load(Ordering::Relaxed) as f64 / 1_000_000.0
+        );
+        let _ = writeln!(out, "{}_count {}", name, histogram.count());
+
+        out
+    }
+}
+
+fn render_counter(out: &mut String, name: &str, help: &str, counter: &Counter) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} counter", name);
+    let _ = writeln!(out, "{} {}", name, counter.get());
+}
+
+fn render_labeled_counter(out: &mut String, name: &str, help: &str, label: &str, counter: &LabeledCounter) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} counter", name);
+    for (value, count) in counter.0.lock().unwrap().iter() {
+        let value = value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n");
+        let _ = writeln!(out, "{}{{{}=\"{}\"}} {}", name, label, value, count);
+    }
+}
@@ -1,28 +1,463 @@
 //! Data models for MT5 integration
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+use utoipa::{IntoParams, ToSchema};
+
+/// MT5 order type, serialized as the MQL `OP_*` codes used on the wire
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum MT5OrderType {
+    #[serde(rename = "OP_BUY")]
+    Buy,
+    #[serde(rename = "OP_SELL")]
+    Sell,
+    #[serde(rename = "OP_BUYLIMIT")]
+    BuyLimit,
+    #[serde(rename = "OP_SELLLIMIT")]
+    SellLimit,
+    #[serde(rename = "OP_BUYSTOP")]
+    BuyStop,
+    #[serde(rename = "OP_SELLSTOP")]
+    SellStop,
+    #[serde(rename = "OP_BUYSTOPLIMIT")]
+    BuyStopLimit,
+    #[serde(rename = "OP_SELLSTOPLIMIT")]
+    SellStopLimit,
+}
+
+impl MT5OrderType {
+    /// All supported order types
+    pub const ALL: [MT5OrderType; 8] = [
+        MT5OrderType::Buy,
+        MT5OrderType::Sell,
+        MT5OrderType::BuyLimit,
+        MT5OrderType::SellLimit,
+        MT5OrderType::BuyStop,
+        MT5OrderType::SellStop,
+        MT5OrderType::BuyStopLimit,
+        MT5OrderType::SellStopLimit,
+    ];
+
+    /// Whether this order type buys the symbol
+    pub fn is_buy(&self) -> bool {
+        matches!(
+            self,
+            MT5OrderType::Buy
+                | MT5OrderType::BuyLimit
+                | MT5OrderType::BuyStop
+                | MT5OrderType::BuyStopLimit
+        )
+    }
+
+    /// Direction of the trade this order opens
+    pub fn side(&self) -> MT5Side {
+        if self.is_buy() {
+            MT5Side::Buy
+        } else {
+            MT5Side::Sell
+        }
+    }
+
+    /// Whether this is a pending order rather than a market execution
+    pub fn is_pending(&self) -> bool {
+        !matches!(self, MT5OrderType::Buy | MT5OrderType::Sell)
+    }
+
+    /// Whether this is a stop-limit order, which needs a `stop_limit` price
+    pub fn is_stop_limit(&self) -> bool {
+        matches!(self, MT5OrderType::BuyStopLimit | MT5OrderType::SellStopLimit)
+    }
+
+    /// MT5 trade request action and order type for this order type
+    pub fn trade_type(&self) -> MT5TradeType {
+        let (action, order_type) = match self {
+            MT5OrderType::Buy => (TRADE_ACTION_DEAL, 0),
+            MT5OrderType::Sell => (TRADE_ACTION_DEAL, 1),
+            MT5OrderType::BuyLimit => (TRADE_ACTION_PENDING, 2),
+            MT5OrderType::SellLimit => (TRADE_ACTION_PENDING, 3),
+            MT5OrderType::BuyStop => (TRADE_ACTION_PENDING, 4),
+            MT5OrderType::SellStop => (TRADE_ACTION_PENDING, 5),
+            MT5OrderType::BuyStopLimit => (TRADE_ACTION_PENDING, 6),
+            MT5OrderType::SellStopLimit => (TRADE_ACTION_PENDING, 7),
+        };
+        MT5TradeType { action, order_type }
+    }
+
+    /// Wire code for this order type (e.g. "OP_BUY")
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MT5OrderType::Buy => "OP_BUY",
+            MT5OrderType::Sell => "OP_SELL",
+            MT5OrderType::BuyLimit => "OP_BUYLIMIT",
+            MT5OrderType::SellLimit => "OP_SELLLIMIT",
+            MT5OrderType::BuyStop => "OP_BUYSTOP",
+            MT5OrderType::SellStop => "OP_SELLSTOP",
+            MT5OrderType::BuyStopLimit => "OP_BUYSTOPLIMIT",
+            MT5OrderType::SellStopLimit => "OP_SELLSTOPLIMIT",
+        }
+    }
+}
+
+/// MT5 `TRADE_ACTION_DEAL`: execute at market
+pub const TRADE_ACTION_DEAL: u32 = 1;
+/// MT5 `TRADE_ACTION_PENDING`: place a pending order
+pub const TRADE_ACTION_PENDING: u32 = 5;
+
+/// `action` and `type` fields of an MT5 trade request
+///
+/// Market buys and sells share `TRADE_ACTION_DEAL` and differ only in
+/// `order_type` (`ORDER_TYPE_BUY` = 0, `ORDER_TYPE_SELL` = 1); pending orders
+/// use `TRADE_ACTION_PENDING` with `ORDER_TYPE_BUY_LIMIT` (2) through
+/// `ORDER_TYPE_SELL_STOP_LIMIT` (7).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MT5TradeType {
+    pub action: u32,
+    pub order_type: u32,
+}
+
+impl fmt::Display for MT5OrderType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Error returned when parsing an unknown order type code
+#[derive(Debug, Clone, PartialEq, Error)]
+#[error("Unknown order type: {0}")]
+pub struct UnknownOrderType(pub String);
+
+impl FromStr for MT5OrderType {
+    type Err = UnknownOrderType;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        MT5OrderType::ALL
+            .into_iter()
+            .find(|order_type| order_type.as_str() == s)
+            .ok_or_else(|| UnknownOrderType(s.to_string()))
+    }
+}
+
+impl TryFrom<&str> for MT5OrderType {
+    type Error = UnknownOrderType;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+/// Trade direction of a position or order, serialized as `OP_BUY` / `OP_SELL`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum MT5Side {
+    #[serde(rename = "OP_BUY")]
+    Buy,
+    #[serde(rename = "OP_SELL")]
+    Sell,
+}
+
+impl MT5Side {
+    /// Wire code for this side (e.g. "OP_BUY")
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MT5Side::Buy => "OP_BUY",
+            MT5Side::Sell => "OP_SELL",
+        }
+    }
+
+    /// The side that closes a position on this side
+    pub fn opposite(&self) -> MT5Side {
+        match self {
+            MT5Side::Buy => MT5Side::Sell,
+            MT5Side::Sell => MT5Side::Buy,
+        }
+    }
+}
+
+impl fmt::Display for MT5Side {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Epoch values at or above this are taken to be milliseconds
+///
+/// 1e11 seconds is in the year 5138, while 1e11 milliseconds is in 1973, so
+/// any realistic timestamp is unambiguous.
+const EPOCH_MILLIS_THRESHOLD: i64 = 100_000_000_000;
+
+/// Convert an MT5 epoch timestamp to UTC
+///
+/// MT5 timestamps are epoch seconds, but some bridges send milliseconds;
+/// these are detected by magnitude. Out-of-range values map to the epoch.
+pub fn epoch_to_utc(timestamp: i64) -> DateTime<Utc> {
+    let datetime = if timestamp.abs() >= EPOCH_MILLIS_THRESHOLD {
+        DateTime::from_timestamp_millis(timestamp)
+    } else {
+        DateTime::from_timestamp(timestamp, 0)
+    };
+    datetime.unwrap_or_default()
+}
+
+/// Where an order is in its lifecycle
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum OrderState {
+    /// Live on the terminal: a pending order, or one not yet executed
+    #[default]
+    Pending,
+    /// Executed; the order now lives in history as a deal
+    Filled,
+    #[serde(alias = "canceled")]
+    Cancelled,
+    Expired,
+}
 
 /// MT5 Order representation
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct MT5Order {
     pub ticket: u64,
     pub symbol: String,
-    pub order_type: String, // "OP_BUY", "OP_SELL", "OP_BUYLIMIT", etc.
+    pub order_type: MT5OrderType,
     pub volume: f64,
     pub price: f64,
+    /// Limit price placed once a stop-limit order's `price` is reached
+    pub stop_limit: Option<f64>,
     pub stop_loss: Option<f64>,
     pub take_profit: Option<f64>,
     pub comment: Option<String>,
     pub magic: u32,
+    /// Expiry of a pending order (epoch seconds); must be in the future.
+    /// See `expiration_utc`.
     pub expiration: Option<i64>,
+    /// Caller-chosen idempotency key, sent to the bridge so a retried
+    /// submission that already reached MT5 isn't placed twice. The bridge
+    /// client generates one when absent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_order_id: Option<String>,
+    /// Largest acceptable slippage from `price` for a market order, in points
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deviation: Option<u32>,
+    /// Send a market order even if the spread exceeds `mt5_max_spread_points`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub skip_spread_check: bool,
+    /// Lifecycle state as reported by the bridge; `Pending` when it doesn't say
+    #[serde(default)]
+    pub state: OrderState,
+}
+
+/// Reason an order failed pre-submission validation
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum OrderValidationError {
+    #[error("volume must be a positive finite number, got {0}")]
+    InvalidVolume(f64),
+    #[error("price must be a non-negative finite number, got {0}")]
+    InvalidPrice(f64),
+    #[error("{0} order requires a price")]
+    MissingPrice(MT5OrderType),
+    #[error("{0} order requires a finite stop_limit price")]
+    MissingStopLimit(MT5OrderType),
+    #[error("{field} must be a finite number, got {value}")]
+    InvalidLevel { field: &'static str, value: f64 },
+    #[error("{field} {value} is on the wrong side of price {price} for {order_type}")]
+    LevelWrongSide {
+        field: &'static str,
+        value: f64,
+        price: f64,
+        order_type: MT5OrderType,
+    },
+    #[error("{0} orders execute immediately and cannot expire")]
+    ExpirationOnMarketOrder(MT5OrderType),
+    #[error("expiration {expiration} is not in the future (now {now})")]
+    ExpirationInPast { expiration: i64, now: i64 },
+    #[error("volume {volume} is below the symbol minimum of {min}")]
+    VolumeBelowMinimum { volume: f64, min: f64 },
+    #[error("volume {volume} is above the symbol maximum of {max}")]
+    VolumeAboveSymbolMaximum { volume: f64, max: f64 },
+    #[error("volume {volume} exceeds the maximum order volume of {max}")]
+    VolumeAboveMaximum { volume: f64, max: f64 },
+}
+
+impl MT5Order {
+    /// Pending order expiry as UTC
+    pub fn expiration_utc(&self) -> Option<DateTime<Utc>> {
+        self.expiration.map(epoch_to_utc)
+    }
+
+    /// Check order parameters before sending them to the bridge
+    ///
+    /// Pending orders need a non-zero price. Market orders may omit it (price
+    /// 0) to fill at market; stop loss and take profit are only checked
+    /// against price when one is set.
+    pub fn validate(&self) -> Result<(), OrderValidationError> {
+        if !self.volume.is_finite() || self.volume <= 0.0 {
+            return Err(OrderValidationError::InvalidVolume(self.volume));
+        }
+        if !self.price.is_finite() || self.price < 0.0 {
+            return Err(OrderValidationError::InvalidPrice(self.price));
+        }
+        if self.order_type.is_pending() && self.price == 0.0 {
+            return Err(OrderValidationError::MissingPrice(self.order_type));
+        }
+        if self.order_type.is_stop_limit() && !self.stop_limit.is_some_and(f64::is_finite) {
+            return Err(OrderValidationError::MissingStopLimit(self.order_type));
+        }
+        if let Some(expiration) = self.expiration {
+            if !self.order_type.is_pending() {
+                return Err(OrderValidationError::ExpirationOnMarketOrder(self.order_type));
+            }
+            let now = Utc::now().timestamp();
+            if epoch_to_utc(expiration).timestamp() <= now {
+                return Err(OrderValidationError::ExpirationInPast { expiration, now });
+            }
+        }
+
+        let is_buy = self.order_type.is_buy();
+        // (field, value, must be above price for a buy)
+        let levels = [
+            ("stop_loss", self.stop_loss, false),
+            ("take_profit", self.take_profit, true),
+        ];
+
+        for (field, value, above_for_buy) in levels {
+            let Some(value) = value else { continue };
+
+            if !value.is_finite() {
+                return Err(OrderValidationError::InvalidLevel { field, value });
+            }
+
+            if self.price > 0.0 {
+                let must_be_above = above_for_buy == is_buy;
+                let correct_side = if must_be_above {
+                    value > self.price
+                } else {
+                    value < self.price
+                };
+                if !correct_side {
+                    return Err(OrderValidationError::LevelWrongSide {
+                        field,
+                        value,
+                        price: self.price,
+                        order_type: self.order_type,
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Outcome of an order accepted by the bridge
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MT5OrderResult {
+    pub ticket: u64,
+    /// MT5 trade server return code (`TRADE_RETCODE_*`), if the bridge sent one
+    pub retcode: Option<u32>,
+    /// Set when the order was only validated (`MT5_DRY_RUN`) and never sent
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Average price the order was filled at; `None` while it is pending
+    #[serde(default)]
+    pub fill_price: Option<f64>,
+    /// Volume filled; `None` while the order is pending
+    #[serde(default)]
+    pub fill_volume: Option<f64>,
+}
+
+impl MT5OrderResult {
+    /// Whether the bridge reported a fill for this order
+    pub fn is_filled(&self) -> bool {
+        self.fill_price.is_some()
+    }
+}
+
+/// Trade server return code for a requote
+pub const TRADE_RETCODE_REQUOTE: u32 = 10004;
+
+/// Trade server return code for a price change while the request was processed
+pub const TRADE_RETCODE_PRICE_CHANGED: u32 = 10020;
+
+/// Human-readable description of an MT5 trade server return code
+///
+/// Covers the `TRADE_RETCODE_*` values; returns `None` for unknown codes.
+pub fn retcode_description(retcode: u32) -> Option<&'static str> {
+    let description = match retcode {
+        10004 => "Requote",
+        10006 => "Request rejected",
+        10007 => "Request canceled by trader",
+        10008 => "Order placed",
+        10009 => "Request completed",
+        10010 => "Only part of the request was completed",
+        10011 => "Request processing error",
+        10012 => "Request canceled by timeout",
+        10013 => "Invalid request",
+        10014 => "Invalid volume in the request",
+        10015 => "Invalid price in the request",
+        10016 => "Invalid stops in the request",
+        10017 => "Trade is disabled",
+        10018 => "Market is closed",
+        10019 => "There is not enough money to complete the request",
+        10020 => "Prices changed",
+        10021 => "There are no quotes to process the request",
+        10022 => "Invalid order expiration date in the request",
+        10023 => "Order state changed",
+        10024 => "Too frequent requests",
+        10025 => "No changes in request",
+        10026 => "Autotrading disabled by server",
+        10027 => "Autotrading disabled by client terminal",
+        10028 => "Request locked for processing",
+        10029 => "Order or position frozen",
+        10030 => "Invalid order filling type",
+        10031 => "No connection with the trade server",
+        10032 => "Operation is allowed only for live accounts",
+        10033 => "The number of pending orders has reached the limit",
+        10034 => "The volume of orders and positions for the symbol has reached the limit",
+        10035 => "Incorrect or prohibited order type",
+        10036 => "Position with the specified identifier has already been closed",
+        _ => return None,
+    };
+    Some(description)
+}
+
+/// What a rejected order's retcode means for the strategy that sent it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RejectionClass {
+    /// Not enough free margin; resize or abort
+    InsufficientFunds,
+    /// The price or stop levels are invalid; recompute them
+    InvalidPrice,
+    /// The market is closed; retry once it opens
+    MarketClosed,
+    /// The price moved; resubmit at the new one
+    Requote,
+    /// Trading is disabled for the symbol, account or terminal; abort
+    TradeDisabled,
+    /// Anything else
+    Other,
+}
+
+/// Classify a rejected order's `TRADE_RETCODE_*` value
+pub fn classify_rejection(retcode: u32) -> RejectionClass {
+    match retcode {
+        10019 => RejectionClass::InsufficientFunds,
+        10015 | 10016 => RejectionClass::InvalidPrice,
+        10018 => RejectionClass::MarketClosed,
+        TRADE_RETCODE_REQUOTE | TRADE_RETCODE_PRICE_CHANGED => RejectionClass::Requote,
+        10017 | 10026 | 10027 | 10032 => RejectionClass::TradeDisabled,
+        _ => RejectionClass::Other,
+    }
 }
 
 /// MT5 Position representation
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
 pub struct MT5Position {
     pub ticket: u64,
     pub symbol: String,
-    pub position_type: String, // "OP_BUY" or "OP_SELL"
+    pub position_type: MT5Side,
     pub volume: f64,
     pub price_open: f64,
     pub price_current: f64,
@@ -33,19 +468,437 @@ pub struct MT5Position {
     pub take_profit: Option<f64>,
     pub comment: Option<String>,
     pub magic: u32,
+    /// Open time (epoch seconds); see `time_open_utc`
     pub time_open: i64,
 }
 
+impl MT5Position {
+    /// Open time as UTC
+    pub fn time_open_utc(&self) -> DateTime<Utc> {
+        epoch_to_utc(self.time_open)
+    }
+
+    /// Profit including swap and commission
+    pub fn net_profit(&self) -> f64 {
+        self.profit + self.swap + self.commission
+    }
+
+    /// Whether this is a buy (long) position
+    pub fn is_long(&self) -> bool {
+        self.position_type == MT5Side::Buy
+    }
+
+    /// Favourable price movement since open, in units of `point`
+    ///
+    /// Positive when the position is in profit, for longs and shorts alike.
+    pub fn unrealized_pips(&self, point: f64) -> f64 {
+        let movement = self.price_current - self.price_open;
+        let movement = if self.is_long() { movement } else { -movement };
+        movement / point
+    }
+
+    /// `profit` converted from the symbol's quote currency into `account_currency`
+    ///
+    /// See `convert_from_quote`.
+    pub fn profit_in(&self, account_currency: &str, rates: &HashMap<String, f64>) -> Option<f64> {
+        convert_from_quote(self.profit, &self.symbol, account_currency, rates)
+    }
+}
+
+/// Quote currency of a currency pair: its fourth to sixth characters, e.g.
+/// `JPY` for `USDJPY`
+pub fn quote_currency(symbol: &str) -> Option<String> {
+    symbol.get(3..6).map(str::to_ascii_uppercase)
+}
+
+/// `amount` converted from `symbol`'s quote currency into `account_currency`
+///
+/// `rates` are keyed by currency pair: `JPYUSD` is multiplied by, or failing
+/// that `USDJPY` divided by. `None` when neither rate is supplied or the
+/// symbol isn't a currency pair.
+pub fn convert_from_quote(
+    amount: f64,
+    symbol: &str,
+    account_currency: &str,
+    rates: &HashMap<String, f64>,
+) -> Option<f64> {
+    let quote = quote_currency(symbol)?;
+    let account = account_currency.to_ascii_uppercase();
+    if quote == account {
+        return Some(amount);
+    }
+    
+    let rate = |pair: String| rates.get(&pair).copied().filter(|rate| *rate > 0.0);
+    rate(format!("{}{}", quote, account))
+        .map(|rate| amount * rate)
+        .or_else(|| rate(format!("{}{}", account, quote)).map(|rate| amount / rate))
+}
+
+/// Aggregate of a group of positions
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ExposureSummary {
+    pub position_count: usize,
+    pub volume: f64,
+    /// Profit including swap and commission
+    pub net_profit: f64,
+}
+
+impl ExposureSummary {
+    fn add(&mut self, position: &MT5Position) {
+        self.position_count += 1;
+        self.volume += position.volume;
+        self.net_profit += position.net_profit();
+    }
+}
+
+/// Criteria for listing positions; unset fields match every position
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PositionFilter {
+    pub symbol: Option<String>,
+    /// Magic number of the strategy that opened the position
+    pub magic: Option<u32>,
+}
+
+impl PositionFilter {
+    /// Whether `position` meets every set criterion
+    pub fn matches(&self, position: &MT5Position) -> bool {
+        self.symbol.as_ref().is_none_or(|symbol| &position.symbol == symbol)
+            && self.magic.is_none_or(|magic| position.magic == magic)
+    }
+}
+
+/// Long and short exposure in one symbol
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct SymbolExposure {
+    pub symbol: String,
+    pub long: ExposureSummary,
+    pub short: ExposureSummary,
+    /// Long volume minus short volume
+    pub net_volume: f64,
+}
+
+/// Portfolio totals across open positions
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct PositionSummary {
+    pub position_count: usize,
+    pub total_profit: f64,
+    pub total_swap: f64,
+    pub total_commission: f64,
+    pub net_profit: f64,
+    pub long: ExposureSummary,
+    pub short: ExposureSummary,
+    /// Per-symbol exposure, sorted by symbol
+    pub symbols: Vec<SymbolExposure>,
+}
+
+/// Aggregate positions into totals, per side and per symbol
+pub fn summarize(positions: &[MT5Position]) -> PositionSummary {
+    let mut summary = PositionSummary::default();
+    let mut symbols: BTreeMap<&str, (ExposureSummary, ExposureSummary)> = BTreeMap::new();
+
+    for position in positions {
+        summary.position_count += 1;
+        summary.total_profit += position.profit;
+        summary.total_swap += position.swap;
+        summary.total_commission += position.commission;
+        summary.net_profit += position.net_profit();
+
+        let (long, short) = symbols.entry(&position.symbol).or_default();
+        if position.is_long() {
+            summary.long.add(position);
+            long.add(position);
+        } else {
+            summary.short.add(position);
+            short.add(position);
+        }
+    }
+
+    summary.symbols = symbols
+        .into_iter()
+        .map(|(symbol, (long, short))| SymbolExposure {
+            symbol: symbol.to_string(),
+            net_volume: long.volume - short.volume,
+            long,
+            short,
+        })
+        .collect();
+    summary
+}
+
+/// Net volumes closer to zero than this count as fully hedged
+const NET_VOLUME_EPSILON: f64 = 1e-9;
+
+/// Net exposure across every position in one symbol
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct NetPosition {
+    pub symbol: String,
+    pub position_count: usize,
+    /// Long volume minus short volume
+    pub net_volume: f64,
+    /// Direction of the net exposure; `None` when fully hedged
+    pub side: Option<MT5Side>,
+    /// Volume-weighted entry price of the net exposure, i.e. the price at
+    /// which the positions break even together; `None` when fully hedged
+    pub average_price: Option<f64>,
+}
+
+impl NetPosition {
+    /// Whether longs and shorts cancel out
+    pub fn is_hedged(&self) -> bool {
+        self.side.is_none()
+    }
+}
+
+/// Net the positions in `symbol`, ignoring those in other symbols
+///
+/// Returns `None` when there are no positions in `symbol`.
+pub fn net_position(symbol: &str, positions: &[MT5Position]) -> Option<NetPosition> {
+    let mut position_count = 0;
+    let mut net_volume = 0.0;
+    let mut signed_notional = 0.0;
+    for position in positions.iter().filter(|position| position.symbol == symbol) {
+        let volume = if position.is_long() { position.volume } else { -position.volume };
+        position_count += 1;
+        net_volume += volume;
+        signed_notional += volume * position.price_open;
+    }
+    if position_count == 0 {
+        return None;
+    }
+
+    if net_volume.abs() < NET_VOLUME_EPSILON {
+        return Some(NetPosition {
+            symbol: symbol.to_string(),
+            position_count,
+            net_volume: 0.0,
+            side: None,
+            average_price: None,
+        });
+    }
+    Some(NetPosition {
+        symbol: symbol.to_string(),
+        position_count,
+        net_volume,
+        side: Some(if net_volume > 0.0 { MT5Side::Buy } else { MT5Side::Sell }),
+        average_price: Some(signed_notional / net_volume),
+    })
+}
+
 /// MT5 Market Data
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct MT5MarketData {
     pub symbol: String,
     pub bid: f64,
     pub ask: f64,
     pub last: f64,
     pub volume: f64,
+    /// Quote time (epoch seconds); see `time_utc`
     pub time: i64,
     pub spread: f64,
     pub digits: u32,
+    /// Served from cache because the bridge is unavailable
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub stale: bool,
+}
+
+impl MT5MarketData {
+    /// Quote time as UTC
+    pub fn time_utc(&self) -> DateTime<Utc> {
+        epoch_to_utc(self.time)
+    }
+
+    /// How old the quote is at `now`
+    ///
+    /// A quote timestamped after `now`, e.g. by a terminal whose clock runs
+    /// ahead, counts as brand new rather than having a negative age.
+    pub fn age(&self, now: DateTime<Utc>) -> std::time::Duration {
+        (now - self.time_utc()).to_std().unwrap_or_default()
+    }
+
+    /// Whether the quote is older than `max_age` now
+    pub fn is_stale(&self, max_age: std::time::Duration) -> bool {
+        self.age(Utc::now()) > max_age
+    }
+
+    /// Ask minus bid in points (units of the last price digit)
+    pub fn spread_points(&self) -> f64 {
+        ((self.ask - self.bid) * 10f64.powi(self.digits as i32)).round()
+    }
+
+    /// Price a market order on `side` would trade at, per `source`
+    pub fn price_for(&self, side: MT5Side, source: PriceSource) -> f64 {
+        match source {
+            PriceSource::Side => match side {
+                MT5Side::Buy => self.ask,
+                MT5Side::Sell => self.bid,
+            },
+            PriceSource::Bid => self.bid,
+            PriceSource::Ask => self.ask,
+            PriceSource::Mid => (self.bid + self.ask) / 2.0,
+        }
+    }
+}
+
+/// Quote used to price market orders sent without a price
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PriceSource {
+    /// The side the order trades against: ask for buys, bid for sells
+    #[default]
+    Side,
+    Bid,
+    Ask,
+    Mid,
+}
+
+impl FromStr for PriceSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "side" => Ok(PriceSource::Side),
+            "bid" => Ok(PriceSource::Bid),
+            "ask" => Ok(PriceSource::Ask),
+            "mid" => Ok(PriceSource::Mid),
+            other => Err(format!("Unknown price source: {}", other)),
+        }
+    }
+}
+
+/// Chart timeframes accepted for candle requests
+pub const TIMEFRAMES: [&str; 9] = ["M1", "M5", "M15", "M30", "H1", "H4", "D1", "W1", "MN1"];
+
+/// Whether `timeframe` is one of `TIMEFRAMES`
+pub fn is_valid_timeframe(timeframe: &str) -> bool {
+    TIMEFRAMES.contains(&timeframe)
+}
+
+/// OHLC bar for one timeframe period
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct MT5Candle {
+    /// Bar open time (epoch seconds)
+    pub time: i64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub tick_volume: u64,
+}
+
+/// Event pushed by the bridge to `POST /bridge/events`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BridgeEvent {
+    /// An order was filled, fully or in part
+    Fill {
+        ticket: u64,
+        symbol: String,
+        volume: f64,
+        price: f64,
+    },
+    /// A position was closed on the terminal
+    PositionClosed {
+        ticket: u64,
+        symbol: String,
+        #[serde(default)]
+        profit: Option<f64>,
+    },
+}
+
+/// MT5 Account information
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MT5AccountInfo {
+    pub balance: f64,
+    pub equity: f64,
+    pub margin: f64,
+    pub free_margin: f64,
+    pub margin_level: f64,
+    pub currency: String,
+    pub leverage: u32,
+}
+
+/// MT5 Symbol trading specification
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MT5SymbolInfo {
+    pub symbol: String,
+    pub digits: u32,
+    pub point: f64,
+    pub tick_size: f64,
+    pub tick_value: f64,
+    pub volume_min: f64,
+    pub volume_max: f64,
+    pub volume_step: f64,
+    pub contract_size: f64,
+    pub trade_allowed: bool,
+}
+
+/// Slack allowed when comparing a volume against the symbol's bounds
+const VOLUME_BOUND_EPSILON: f64 = 1e-9;
+
+impl MT5SymbolInfo {
+    /// Round a raw volume to the nearest `volume_step`
+    ///
+    /// Volumes outside `[volume_min, volume_max]` are rejected rather than
+    /// clamped, at either bound, so the order sent is never a different size
+    /// from the one requested beyond rounding to the step. Float noise within
+    /// `VOLUME_BOUND_EPSILON` of a bound (e.g. 0.009999999999 against a 0.01
+    /// minimum) counts as the bound.
+    pub fn normalize_volume(&self, raw: f64) -> Result<f64, OrderValidationError> {
+        if !raw.is_finite() || raw <= 0.0 {
+            return Err(OrderValidationError::InvalidVolume(raw));
+        }
+
+        let stepped = if self.volume_step > 0.0 {
+            let steps = (raw / self.volume_step).round();
+            // Trim float noise (e.g. 0.30000000000000004) to the step's precision
+            let decimals = (-self.volume_step.log10()).ceil().max(0.0) as i32;
+            let scale = 10f64.powi(decimals);
+            (steps * self.volume_step * scale).round() / scale
+        } else {
+            raw
+        };
+
+        if raw.min(stepped) < self.volume_min - VOLUME_BOUND_EPSILON {
+            return Err(OrderValidationError::VolumeBelowMinimum { volume: raw, min: self.volume_min });
+        }
+        if raw.max(stepped) > self.volume_max + VOLUME_BOUND_EPSILON {
+            return Err(OrderValidationError::VolumeAboveSymbolMaximum { volume: raw, max: self.volume_max });
+        }
+        Ok(stepped)
+    }
+
+    /// Round a price to the symbol's `digits`
+    pub fn round_price(&self, price: f64) -> f64 {
+        let scale = 10f64.powi(self.digits as i32);
+        (price * scale).round() / scale
+    }
+
+    /// Round an order's prices to `digits` and snap its volume to `volume_step`
+    pub fn normalize_order(&self, order: &mut MT5Order) -> Result<(), OrderValidationError> {
+        order.volume = self.normalize_volume(order.volume)?;
+        order.price = self.round_price(order.price);
+        order.stop_limit = order.stop_limit.map(|price| self.round_price(price));
+        order.stop_loss = order.stop_loss.map(|price| self.round_price(price));
+        order.take_profit = order.take_profit.map(|price| self.round_price(price));
+        Ok(())
+    }
+
+    /// Value of a one-`point` price move on `volume` lots, in the currency
+    /// `tick_value` is quoted in (the pip unit of `unrealized_pips`)
+    pub fn pip_value(&self, volume: f64) -> f64 {
+        if self.tick_size <= 0.0 {
+            return 0.0;
+        }
+        self.tick_value * (self.point / self.tick_size) * volume
+    }
 }
 
+/// Margin needed to open `volume` lots of `symbol` at `price` with `leverage`
+///
+/// Uses the plain forex formula `volume * contract_size * price / leverage`,
+/// so the result is in the symbol's quote currency; a leverage of 0 is
+/// treated as 1:1.
+pub fn estimate_margin(volume: f64, price: f64, symbol: &MT5SymbolInfo, leverage: u32) -> f64 {
+    volume * symbol.contract_size * price / f64::from(leverage.max(1))
+}
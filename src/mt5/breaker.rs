@@ -0,0 +1,133 @@
+//! Circuit breaker for bridge requests
+//!
+//! After `threshold` consecutive failures the breaker opens and requests fail
+//! fast instead of each waiting out the full timeout. Once the cooldown has
+//! elapsed it half-opens and lets a single probe request through: success
+//! closes it again, failure re-opens it for another cooldown. A probe that is
+//! abandoned before it reports back (its future dropped) counts as a failure,
+//! so the breaker can never get stuck waiting on it.
+
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use utoipa::ToSchema;
+
+/// State of a circuit breaker
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// Requests flow normally
+    Closed,
+    /// Requests fail fast until the cooldown elapses
+    Open,
+    /// Cooldown elapsed; the next request probes the bridge
+    HalfOpen,
+}
+
+#[derive(Debug, Default)]
+struct BreakerInner {
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    probe_in_flight: bool,
+}
+
+/// Consecutive-failure circuit breaker
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    threshold: u32,
+    cooldown: Duration,
+    inner: Mutex<BreakerInner>,
+}
+
+impl CircuitBreaker {
+    /// Create a breaker; a `threshold` of 0 disables it
+    pub fn new(threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            threshold,
+            cooldown,
+            inner: Mutex::new(BreakerInner::default()),
+        }
+    }
+
+    /// Current state
+    pub fn state(&self) -> CircuitState {
+        let inner = self.inner.lock().unwrap();
+        self.state_of(&inner)
+    }
+
+    fn state_of(&self, inner: &BreakerInner) -> CircuitState {
+        match inner.opened_at {
+            None => CircuitState::Closed,
+            Some(opened_at) if opened_at.elapsed() < self.cooldown => CircuitState::Open,
+            Some(_) => CircuitState::HalfOpen,
+        }
+    }
+
+    /// Admit a request if it may be sent now
+    ///
+    /// In the half-open state only one probe is admitted at a time. The
+    /// returned permit reports the outcome; dropping it without doing so
+    /// counts an unfinished probe as a failure.
+    pub fn try_acquire(&self) -> Option<BreakerPermit<'_>> {
+        let mut inner = self.inner.lock().unwrap();
+        let probe = match self.state_of(&inner) {
+            CircuitState::Closed => false,
+            CircuitState::Open => return None,
+            CircuitState::HalfOpen if inner.probe_in_flight => return None,
+            CircuitState::HalfOpen => {
+                inner.probe_in_flight = true;
+                true
+            }
+        };
+        Some(BreakerPermit { breaker: self, probe, finished: false })
+    }
+
+    /// Record a request that reached a healthy bridge
+    pub fn record_success(&self) {
+        *self.inner.lock().unwrap() = BreakerInner::default();
+    }
+
+    /// Record a request that failed because the bridge is unhealthy
+    pub fn record_failure(&self) {
+        if self.threshold == 0 {
+            return;
+        }
+
+        let mut inner = self.inner.lock().unwrap();
+        inner.consecutive_failures = inner.consecutive_failures.saturating_add(1);
+        if inner.probe_in_flight || inner.consecutive_failures >= self.threshold {
+            inner.opened_at = Some(Instant::now());
+            inner.probe_in_flight = false;
+        }
+    }
+}
+
+/// A request admitted by [`CircuitBreaker::try_acquire`]
+#[derive(Debug)]
+pub struct BreakerPermit<'a> {
+    breaker: &'a CircuitBreaker,
+    probe: bool,
+    finished: bool,
+}
+
+impl BreakerPermit<'_> {
+    /// The request reached a healthy bridge
+    pub fn success(mut self) {
+        self.finished = true;
+        self.breaker.record_success();
+    }
+
+    /// The request failed because the bridge is unhealthy
+    pub fn failure(mut self) {
+        self.finished = true;
+        self.breaker.record_failure();
+    }
+}
+
+impl Drop for BreakerPermit<'_> {
+    fn drop(&mut self) {
+        if self.probe && !self.finished {
+            self.breaker.record_failure();
+        }
+    }
+}
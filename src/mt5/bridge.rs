@@ -3,15 +3,269 @@
 //! This module provides an HTTP client to communicate with an MT5 bridge service.
 //! The bridge service (Python/Node.js) handles actual MT5 API calls via MQL5.
 
-use crate::config::Settings;
-use crate::models::{MT5MarketData, MT5Order, MT5Position};
-use anyhow::{Context, Result};
-use reqwest::Client;
+use crate::config::{parse_header_specs, Settings};
+use crate::correlation::{self, CORRELATION_ID_HEADER};
+use crate::metrics::metrics;
+use crate::models::{
+    MT5AccountInfo, MT5Candle, MT5MarketData, MT5Order, MT5OrderResult, MT5Position,
+    MT5Side, MT5SymbolInfo, OrderValidationError,
+};
+use crate::mt5::breaker::{CircuitBreaker, CircuitState};
+use crate::mt5::error::{MT5Error, Result};
+use crate::mt5::redact::{redact_body, redact_headers};
+use crate::mt5::symbols;
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use futures_util::stream::{BoxStream, StreamExt};
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, USER_AGENT};
+use reqwest::{Client, RequestBuilder, Response};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
-use std::time::Duration;
-use tokio::sync::RwLock;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, RwLock, Semaphore, SemaphorePermit};
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::Message;
 use tracing::{error, info, warn};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Upper bound on the delay between background reconnection attempts
+const MAX_RECONNECT_BACKOFF: Duration = Duration::from_secs(30);
+
+/// How often a queued order re-probes the bridge while waiting for it
+const ORDER_QUEUE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How often startup re-probes the bridge while `mt5_wait_for_bridge` waits for it
+const BRIDGE_WAIT_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Header carrying an order's `client_order_id` so the bridge can drop duplicates
+const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
+/// Request header announcing the version of this client to the bridge
+pub const CLIENT_VERSION_HEADER: &str = "X-MT5-Client-Version";
+
+/// Response header carrying the bridge's version
+pub const BRIDGE_VERSION_HEADER: &str = "X-MT5-Bridge-Version";
+
+/// Interval of TCP keepalive probes on bridge connections
+const TCP_KEEPALIVE: Duration = Duration::from_secs(60);
+
+/// How long fetched symbol specifications are reused for order normalization
+const SYMBOL_INFO_TTL: Duration = Duration::from_secs(60);
+
+/// How long the list of tradable symbols is reused for
+const SYMBOL_LIST_TTL: Duration = Duration::from_secs(300);
+
+/// Number of recent bridge requests averaged for `avg_latency_ms`
+const LATENCY_WINDOW: usize = 50;
+
+/// Upper bound on how long a health probe waits for the bridge
+const HEALTH_PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Characters of an unparseable bridge body quoted in the error
+const BODY_SNIPPET_CHARS: usize = 200;
+
+/// Connection state of the bridge client
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectionState {
+    /// Last contact with the bridge succeeded
+    Connected,
+    /// Bridge is unreachable and a background task is retrying with backoff
+    Reconnecting,
+    /// Bridge has not been reached and no reconnection is in progress
+    Disconnected,
+}
+
+/// A change of `ConnectionState`, broadcast to `subscribe_connection_events` subscribers
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConnectionEvent {
+    pub state: ConnectionState,
+    pub previous: ConnectionState,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Connection state events buffered for slow subscribers before they start missing some
+const CONNECTION_EVENT_CAPACITY: usize = 64;
+
+/// Current connection state, announcing every change to subscribers
+struct ConnectionCell {
+    state: RwLock<ConnectionState>,
+    events: broadcast::Sender<ConnectionEvent>,
+}
+
+impl ConnectionCell {
+    fn new() -> Self {
+        Self {
+            state: RwLock::new(ConnectionState::Disconnected),
+            events: broadcast::channel(CONNECTION_EVENT_CAPACITY).0,
+        }
+    }
+    
+    async fn get(&self) -> ConnectionState {
+        *self.state.read().await
+    }
+    
+    /// Move to the state `next` returns for the current one, if any,
+    /// returning whether the state changed
+    async fn update(&self, next: impl FnOnce(ConnectionState) -> Option<ConnectionState>) -> bool {
+        let mut state = self.state.write().await;
+        let previous = *state;
+        match next(previous) {
+            Some(next) if next != previous => {
+                *state = next;
+                // No subscribers is fine
+                let _ = self.events.send(ConnectionEvent {
+                    state: next,
+                    previous,
+                    timestamp: Utc::now(),
+                });
+                true
+            }
+            _ => false,
+        }
+    }
+    
+    async fn set(&self, next: ConnectionState) {
+        self.update(|_| Some(next)).await;
+    }
+}
+
+/// Snapshot of bridge request health, reported by `/status`
+#[derive(Debug, Clone, Serialize)]
+pub struct ConnectionStats {
+    /// Resolved bridge base URL
+    pub bridge_url: String,
+    /// Milliseconds since the last successful bridge request, if any
+    pub last_success_ms: Option<u64>,
+    /// Mean latency of the last `LATENCY_WINDOW` bridge requests
+    pub avg_latency_ms: Option<f64>,
+    /// Circuit breaker state guarding bridge requests
+    pub circuit_state: CircuitState,
+    /// Version the bridge last reported in `X-MT5-Bridge-Version`
+    pub bridge_version: Option<String>,
+}
+
+/// Optional bridge features, as reported by `GET /capabilities`
+///
+/// Features the bridge leaves out are assumed supported, and so is
+/// everything when the bridge has no `/capabilities` endpoint at all.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(default)]
+pub struct BridgeCapabilities {
+    /// `GET /history/orders`
+    pub history: bool,
+    /// `GET /market/{symbol}/candles`
+    pub candles: bool,
+    /// `GET /account`
+    pub account: bool,
+}
+
+impl Default for BridgeCapabilities {
+    fn default() -> Self {
+        Self {
+            history: true,
+            candles: true,
+            account: true,
+        }
+    }
+}
+
+impl BridgeCapabilities {
+    /// Whether the bridge offers `feature`
+    pub fn supports(&self, feature: BridgeFeature) -> bool {
+        match feature {
+            BridgeFeature::History => self.history,
+            BridgeFeature::Candles => self.candles,
+            BridgeFeature::Account => self.account,
+        }
+    }
+}
+
+/// Bridge feature a call depends on, named in `MT5Error::Unsupported`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BridgeFeature {
+    History,
+    Candles,
+    Account,
+}
+
+impl std::fmt::Display for BridgeFeature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            BridgeFeature::History => "order history",
+            BridgeFeature::Candles => "candles",
+            BridgeFeature::Account => "account info",
+        })
+    }
+}
+
+/// Bridge version as last reported in response headers
+#[derive(Debug, Default)]
+struct BridgeVersion(Mutex<Option<String>>);
+
+impl BridgeVersion {
+    /// Record the version in `headers`, if any
+    ///
+    /// Warns once per newly seen version when its major version differs from
+    /// this client's, since field renames between majors break deserialization.
+    fn observe(&self, headers: &HeaderMap) {
+        let Some(version) = headers
+            .get(BRIDGE_VERSION_HEADER)
+            .and_then(|value| value.to_str().ok())
+        else {
+            return;
+        };
+        
+        let mut current = self.0.lock().unwrap();
+        if current.as_deref() == Some(version) {
+            return;
+        }
+        *current = Some(version.to_string());
+        
+        if major_version(version) == major_version(crate::PLUGIN_VERSION) {
+            info!(bridge_version = version, "MT5 bridge version detected");
+        } else {
+            warn!(
+                bridge_version = version,
+                client_version = crate::PLUGIN_VERSION,
+                "MT5 bridge major version differs from this client, responses may not deserialize"
+            );
+        }
+    }
+    
+    fn get(&self) -> Option<String> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+/// Leading numeric component of a version such as `v1.4.2`
+fn major_version(version: &str) -> &str {
+    let version = version.trim().trim_start_matches(['v', 'V']);
+    version.split('.').next().unwrap_or(version)
+}
+
+/// Rolling record of recent bridge requests
+#[derive(Debug, Default)]
+struct RequestStats {
+    latencies: VecDeque<Duration>,
+    last_success: Option<Instant>,
+}
+
+impl RequestStats {
+    fn record(&mut self, latency: Duration, success: bool) {
+        if self.latencies.len() == LATENCY_WINDOW {
+            self.latencies.pop_front();
+        }
+        self.latencies.push_back(latency);
+        if success {
+            self.last_success = Some(Instant::now());
+        }
+    }
+}
 
 /// Response from MT5 bridge service
 #[derive(Debug, Deserialize)]
@@ -22,10 +276,38 @@ struct BridgeResponse<T> {
 }
 
 /// Order response from bridge
+///
+/// Rejections may carry only a `retcode`, so the ticket defaults to 0.
 #[derive(Debug, Deserialize)]
 struct OrderResponse {
+    #[serde(default)]
     ticket: u64,
     retcode: Option<u32>,
+    /// Fill price; absent (or zero) for orders still pending
+    price: Option<f64>,
+    /// Filled volume
+    volume: Option<f64>,
+}
+
+impl OrderResponse {
+    /// Fill price and volume, if the bridge reported a fill
+    fn fill(&self) -> (Option<f64>, Option<f64>) {
+        match self.price.filter(|price| *price > 0.0) {
+            Some(price) => (Some(price), self.volume),
+            None => (None, None),
+        }
+    }
+}
+
+/// Stop loss / take profit amendment sent to bridge
+///
+/// Levels left as `None` are omitted so the bridge keeps their current value.
+#[derive(Debug, Serialize)]
+struct ModifyOrderPayload {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stop_loss: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    take_profit: Option<f64>,
 }
 
 /// Position data from bridge
@@ -61,6 +343,54 @@ struct MarketDataResponse {
     digits: u32,
 }
 
+/// Single-flight guard for bridge health probes
+///
+/// Only one probe runs at a time. Callers that queued behind a probe take
+/// its outcome instead of probing again, so a burst of requests against a
+/// recovering bridge costs one `/health` call.
+#[derive(Debug)]
+struct ConnectFlight {
+    /// Number of probes that have finished
+    completed: AtomicU64,
+    /// Held while a probe runs; outcome of the last probe
+    last: tokio::sync::Mutex<std::result::Result<(), String>>,
+}
+
+impl ConnectFlight {
+    fn new() -> Self {
+        Self {
+            completed: AtomicU64::new(0),
+            last: tokio::sync::Mutex::new(Ok(())),
+        }
+    }
+
+    /// Probe the bridge, or share the outcome of a probe that finished while waiting
+    ///
+    /// A successful probe marks `state` connected before other callers are released.
+    async fn probe(
+        &self,
+        http_client: &Client,
+        bridge_url: &str,
+        timeout: Duration,
+        state: &ConnectionCell,
+        bridge_version: &BridgeVersion,
+    ) -> Result<()> {
+        let seen = self.completed.load(Ordering::SeqCst);
+        let mut last = self.last.lock().await;
+        if self.completed.load(Ordering::SeqCst) != seen {
+            return last.clone().map_err(MT5Error::BridgeUnavailable);
+        }
+        
+        let result = probe_health(http_client, bridge_url, timeout, bridge_version).await;
+        if result.is_ok() {
+            state.set(ConnectionState::Connected).await;
+        }
+        *last = result.as_ref().map(|_| ()).map_err(|e| e.to_string());
+        self.completed.fetch_add(1, Ordering::SeqCst);
+        result
+    }
+}
+
 /// HTTP Bridge Client for MT5
 ///
 /// Communicates with an external MT5 bridge service (Python/Node.js)
@@ -69,155 +399,874 @@ pub struct MT5BridgeClient {
     settings: Arc<Settings>,
     bridge_url: String,
     http_client: Client,
-    connected: Arc<RwLock<bool>>,
+    state: Arc<ConnectionCell>,
+    connect_flight: Arc<ConnectFlight>,
+    bridge_version: Arc<BridgeVersion>,
+    stats: Mutex<RequestStats>,
+    breaker: CircuitBreaker,
+    /// Slots for bridge requests in flight, sized by `mt5_max_concurrent_requests`
+    request_slots: Semaphore,
+    /// Symbol specifications by symbol, with the time they were fetched
+    symbol_cache: Mutex<HashMap<String, (Instant, MT5SymbolInfo)>>,
+    /// Tradable symbols by group filter ("" for all), with the time they were fetched
+    symbol_list_cache: Mutex<HashMap<String, (Instant, Vec<String>)>>,
+    /// Last quote fetched per symbol, served stale while the circuit is open
+    market_cache: Mutex<HashMap<String, (Instant, MT5MarketData)>>,
+    /// Features the bridge reported, fetched on first use and after `reconnect`
+    capabilities: Mutex<Option<BridgeCapabilities>>,
+    /// Last synthetic ticket handed out in dry-run mode
+    dry_run_ticket: AtomicU64,
 }
 
 impl MT5BridgeClient {
     /// Create new bridge client
-    pub async fn new(settings: Arc<Settings>) -> Result<Self> {
-        let bridge_url = std::env::var("MT5_BRIDGE_URL")
-            .unwrap_or_else(|_| "http://localhost:8006".to_string());
+    ///
+    /// With `mt5_wait_for_bridge` set this blocks until the bridge answers
+    /// and fails if it doesn't in time; otherwise an unreachable bridge is
+    /// left to the background reconnection.
+    pub async fn new(settings: Arc<Settings>) -> anyhow::Result<Self> {
+        let bridge_url = settings
+            .mt5_bridge_url
+            .clone()
+            .unwrap_or_else(|| "http://localhost:8006".to_string());
+        
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            CLIENT_VERSION_HEADER,
+            HeaderValue::from_static(crate::PLUGIN_VERSION),
+        );
+        headers.insert(
+            USER_AGENT,
+            HeaderValue::from_str(&format!("fks_meta/{}", crate::PLUGIN_VERSION))
+                .context("invalid User-Agent")?,
+        );
+        // Listed after the defaults so they can override them, e.g. User-Agent
+        if let Some(specs) = &settings.mt5_bridge_extra_headers {
+            for (name, value) in parse_header_specs(specs)? {
+                headers.insert(name, value);
+            }
+        }
+        if let Some(token) = &settings.mt5_bridge_token {
+            let mut value = HeaderValue::from_str(&format!("Bearer {}", token))
+                .context("MT5_BRIDGE_TOKEN is not a valid header value")?;
+            value.set_sensitive(true);
+            headers.insert(AUTHORIZATION, value);
+        }
         
         let http_client = Client::builder()
-            .timeout(Duration::from_secs(
-                settings.mt5_timeout_ms / 1000
-            ))
+            .timeout(Duration::from_millis(settings.mt5_timeout_ms))
+            .pool_max_idle_per_host(settings.mt5_bridge_pool_max_idle)
+            .pool_idle_timeout(Duration::from_millis(settings.mt5_bridge_pool_idle_timeout_ms))
+            .tcp_keepalive(TCP_KEEPALIVE)
+            .default_headers(headers)
             .build()
             .context("Failed to create HTTP client")?;
         
+        let breaker = CircuitBreaker::new(
+            settings.mt5_circuit_failure_threshold,
+            Duration::from_millis(settings.mt5_circuit_cooldown_ms),
+        );
+        
+        let request_slots = match settings.mt5_max_concurrent_requests {
+            0 => Semaphore::MAX_PERMITS,
+            limit => limit.min(Semaphore::MAX_PERMITS),
+        };
+        
         let client = Self {
             settings,
             bridge_url: bridge_url.clone(),
             http_client,
-            connected: Arc::new(RwLock::new(false)),
+            state: Arc::new(ConnectionCell::new()),
+            connect_flight: Arc::new(ConnectFlight::new()),
+            bridge_version: Arc::new(BridgeVersion::default()),
+            stats: Mutex::new(RequestStats::default()),
+            breaker,
+            request_slots: Semaphore::new(request_slots),
+            symbol_cache: Mutex::new(HashMap::new()),
+            symbol_list_cache: Mutex::new(HashMap::new()),
+            market_cache: Mutex::new(HashMap::new()),
+            capabilities: Mutex::new(None),
+            dry_run_ticket: AtomicU64::new(0),
         };
         
-        // Test connection
-        if let Err(e) = client.connect().await {
-            warn!("Failed to connect to MT5 bridge: {}", e);
-            // Don't fail initialization, will retry on first use
+        if client.settings.mt5_wait_for_bridge {
+            client.wait_for_bridge().await?;
+        } else if let Err(e) = client.connect().await {
+            warn!(error = %e, "Failed to connect to MT5 bridge");
+            // Don't fail initialization, keep retrying in the background
+            client.start_reconnect().await;
         }
         
         Ok(client)
     }
     
+    /// Probe the bridge until it answers, for up to `mt5_bridge_wait_timeout_ms`
+    async fn wait_for_bridge(&self) -> anyhow::Result<()> {
+        let timeout = Duration::from_millis(self.settings.mt5_bridge_wait_timeout_ms);
+        let deadline = Instant::now() + timeout;
+        loop {
+            let Err(e) = self.connect().await else {
+                return Ok(());
+            };
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                anyhow::bail!(
+                    "MT5 bridge at {} not ready after {}ms: {}",
+                    self.bridge_url,
+                    timeout.as_millis(),
+                    e
+                );
+            }
+            
+            info!(
+                error = %e,
+                remaining_ms = remaining.as_millis() as u64,
+                "Waiting for MT5 bridge to become ready"
+            );
+            tokio::time::sleep(remaining.min(BRIDGE_WAIT_POLL_INTERVAL)).await;
+        }
+    }
+    
     /// Connect to bridge service
+    ///
+    /// Concurrent calls share a single probe. A failure leaves a running
+    /// background reconnection in place.
     async fn connect(&self) -> Result<()> {
-        let health_url = format!("{}/health", self.bridge_url);
-        let response = self.http_client
-            .get(&health_url)
-            .send()
+        match self.probe().await {
+            Ok(()) => {
+                info!(bridge_url = %self.bridge_url, "Connected to MT5 bridge service");
+                Ok(())
+            }
+            Err(e) => {
+                self.state
+                    .update(|state| (state != ConnectionState::Reconnecting).then_some(ConnectionState::Disconnected))
+                    .await;
+                Err(e)
+            }
+        }
+    }
+    
+    /// Probe the bridge health endpoint through the single-flight guard
+    async fn probe(&self) -> Result<()> {
+        self.connect_flight
+            .probe(
+                &self.http_client,
+                &self.bridge_url,
+                self.probe_timeout(),
+                &self.state,
+                &self.bridge_version,
+            )
             .await
-            .context("Failed to reach MT5 bridge service")?;
+    }
+    
+    /// Make sure the bridge is reachable before sending an order
+    ///
+    /// When the client isn't connected this probes the bridge right away
+    /// (shared with any concurrent callers) rather than failing while the
+    /// background reconnection waits out its backoff. With
+    /// `mt5_order_queue_wait_ms` set, the order keeps re-probing for up to
+    /// that long before failing, unless the circuit breaker is open.
+    async fn ensure_connected(&self) -> Result<()> {
+        if self.is_connected().await {
+            return Ok(());
+        }
         
-        if response.status().is_success() {
-            *self.connected.write().await = true;
-            info!(bridge_url = %self.bridge_url, "Connected to MT5 bridge service");
-            Ok(())
+        let deadline = Instant::now() + Duration::from_millis(self.settings.mt5_order_queue_wait_ms);
+        loop {
+            let Err(e) = self.connect().await else {
+                return Ok(());
+            };
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() || self.breaker.state() == CircuitState::Open {
+                warn!(error = %e, "MT5 bridge is unreachable");
+                self.start_reconnect().await;
+                return Err(MT5Error::NotConnected);
+            }
+            
+            info!(
+                error = %e,
+                remaining_ms = remaining.as_millis() as u64,
+                "MT5 bridge unreachable, order waiting for reconnection"
+            );
+            tokio::time::sleep(remaining.min(ORDER_QUEUE_POLL_INTERVAL)).await;
+        }
+    }
+    
+    /// Re-establish the bridge connection on demand
+    ///
+    /// Probes the bridge immediately instead of waiting for the background
+    /// task's backoff, and closes the circuit breaker if the bridge answers.
+    /// On failure the background reconnection keeps running.
+    pub async fn reconnect(&self) -> ConnectionState {
+        // The bridge may have been replaced by one with other features
+        self.capabilities.lock().unwrap().take();
+        match self.connect().await {
+            Ok(()) => self.breaker.record_success(),
+            Err(e) => {
+                warn!(error = %e, "MT5 bridge reconnect failed");
+                self.start_reconnect().await;
+            }
+        }
+        self.connection_state().await
+    }
+    
+    /// Start a background reconnection task unless one is already running
+    ///
+    /// The task probes the bridge health endpoint with exponential backoff
+    /// (starting at `mt5_retry_delay_ms`, capped at `MAX_RECONNECT_BACKOFF`)
+    /// and flips the client back to `Connected` once the bridge answers.
+    async fn start_reconnect(&self) {
+        if !self.state.update(|_| Some(ConnectionState::Reconnecting)).await {
+            return;
+        }
+        
+        let http_client = self.http_client.clone();
+        let bridge_url = self.bridge_url.clone();
+        let state = Arc::downgrade(&self.state);
+        let connect_flight = self.connect_flight.clone();
+        let bridge_version = self.bridge_version.clone();
+        let probe_timeout = self.probe_timeout();
+        let mut backoff = Duration::from_millis(self.settings.mt5_retry_delay_ms.max(1));
+        
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(backoff).await;
+                
+                // Stop once the client has been dropped
+                let Some(state) = state.upgrade() else {
+                    return;
+                };
+                // An order or health check may have reconnected in the meantime
+                if state.get().await == ConnectionState::Connected {
+                    return;
+                }
+                
+                metrics().bridge_reconnects.inc();
+                let probe = connect_flight.probe(&http_client, &bridge_url, probe_timeout, &state, &bridge_version);
+                match probe.await {
+                    Ok(()) => {
+                        info!(bridge_url = %bridge_url, "Reconnected to MT5 bridge service");
+                        return;
+                    }
+                    Err(e) => {
+                        backoff = (backoff * 2).min(MAX_RECONNECT_BACKOFF);
+                        warn!(
+                            error = %e,
+                            retry_in_ms = backoff.as_millis() as u64,
+                            "MT5 bridge reconnection failed"
+                        );
+                    }
+                }
+            }
+        });
+    }
+    
+    /// Send a request to the bridge, retrying transient failures
+    ///
+    /// Connection errors, timeouts and 5xx responses are retried until
+    /// `mt5_retry_attempts` is exhausted, sleeping `mt5_retry_delay_ms`
+    /// between attempts. Any other response (including 4xx rejections) is
+    /// returned to the caller untouched. While the circuit breaker is open
+    /// the request fails fast without being sent. Retries are counted in
+    /// `mt5_bridge_retries_total` under `operation`.
+    ///
+    /// The call holds a request slot for all of its attempts, queueing first
+    /// if `mt5_max_concurrent_requests` are already in flight.
+    async fn retry_request<F>(&self, operation: &str, build_request: F) -> Result<Response>
+    where
+        F: Fn() -> RequestBuilder,
+    {
+        let _slot = self.acquire_request_slot(operation).await?;
+        let Some(permit) = self.breaker.try_acquire() else {
+            return Err(MT5Error::BridgeUnavailable("circuit breaker is open".to_string()));
+        };
+        
+        let attempts = self.settings.mt5_retry_attempts.max(1);
+        let delay = Duration::from_millis(self.settings.mt5_retry_delay_ms);
+        let mut attempt = 1;
+        
+        let result = loop {
+            let started = Instant::now();
+            let result = self.send(build_request()).await;
+            let latency = started.elapsed();
+            metrics().bridge_request_duration.observe(latency);
+            if let Ok(response) = &result {
+                let success = !response.status().is_server_error();
+                self.stats.lock().unwrap().record(latency, success);
+            }
+            
+            match result {
+                Ok(response) if response.status().is_server_error() && attempt < attempts => {
+                    warn!(
+                        operation,
+                        attempt,
+                        status = %response.status(),
+                        "MT5 bridge returned server error, retrying"
+                    );
+                }
+                Err(e) if (e.is_connect() || e.is_timeout()) && attempt < attempts => {
+                    warn!(operation, attempt, error = %e, "MT5 bridge request failed, retrying");
+                }
+                Err(e) if e.is_connect() => {
+                    self.start_reconnect().await;
+                    break Err(e);
+                }
+                result => break result,
+            }
+            
+            metrics().bridge_retries.inc(operation);
+            attempt += 1;
+            tokio::time::sleep(delay).await;
+        };
+        
+        let failed = match &result {
+            Ok(response) => response.status().is_server_error(),
+            Err(e) => e.is_connect() || e.is_timeout(),
+        };
+        if failed {
+            permit.failure();
+            if attempt > 1 {
+                error!(operation, attempts = attempt, "MT5 bridge request failed after retries");
+            }
         } else {
-            *self.connected.write().await = false;
-            Err(anyhow::anyhow!(
-                "MT5 bridge service returned status: {}",
-                response.status()
-            ))
+            permit.success();
+        }
+        
+        Ok(result?)
+    }
+    
+    /// Wait up to `mt5_request_queue_timeout_ms` for a request slot
+    async fn acquire_request_slot(&self, operation: &str) -> Result<SemaphorePermit<'_>> {
+        let wait = Duration::from_millis(self.settings.mt5_request_queue_timeout_ms);
+        match tokio::time::timeout(wait, self.request_slots.acquire()).await {
+            Ok(slot) => Ok(slot.expect("request slots are never closed")),
+            Err(_) => {
+                warn!(
+                    operation,
+                    wait_ms = self.settings.mt5_request_queue_timeout_ms,
+                    "No MT5 bridge request slot freed up, failing as overloaded"
+                );
+                Err(MT5Error::Overloaded)
+            }
         }
     }
     
+    /// `retry_request` with a timeout other than the client's default
+    ///
+    /// Each attempt gets the full `timeout`.
+    async fn request_with_timeout<F>(
+        &self,
+        operation: &str,
+        timeout: Duration,
+        build_request: F,
+    ) -> Result<Response>
+    where
+        F: Fn() -> RequestBuilder,
+    {
+        self.retry_request(operation, || build_request().timeout(timeout)).await
+    }
+    
+    /// Send a single request, tracing it when `mt5_log_bridge_bodies` is set
+    ///
+    /// Bodies and headers pass through `redact` first. The response body has
+    /// to be read to be logged, so the response is rebuilt from the bytes.
+    async fn send(&self, request: RequestBuilder) -> reqwest::Result<Response> {
+        let request = match correlation::current() {
+            Some(id) => request.header(CORRELATION_ID_HEADER, id),
+            None => request,
+        };
+        if !self.settings.mt5_log_bridge_bodies {
+            let response = request.send().await?;
+            self.bridge_version.observe(response.headers());
+            return Ok(response);
+        }
+        
+        let request = request.build()?;
+        info!(
+            method = %request.method(),
+            url = %request.url(),
+            headers = %redact_headers(request.headers()),
+            body = %request.body().and_then(|body| body.as_bytes()).map(redact_body).unwrap_or_default(),
+            "MT5 bridge request"
+        );
+        
+        let response = self.http_client.execute(request).await?;
+        let status = response.status();
+        let version = response.version();
+        let headers = response.headers().clone();
+        self.bridge_version.observe(&headers);
+        let body = response.bytes().await?;
+        info!(
+            status = %status,
+            headers = %redact_headers(&headers),
+            body = %redact_body(&body),
+            "MT5 bridge response"
+        );
+        
+        let mut rebuilt = axum::http::Response::new(body);
+        *rebuilt.status_mut() = status;
+        *rebuilt.version_mut() = version;
+        *rebuilt.headers_mut() = headers;
+        Ok(Response::from(rebuilt))
+    }
+    
     /// Check if connected
     pub async fn is_connected(&self) -> bool {
-        *self.connected.read().await
+        self.connection_state().await == ConnectionState::Connected
+    }
+    
+    /// Current connection state, including whether a reconnect is in progress
+    pub async fn connection_state(&self) -> ConnectionState {
+        self.state.get().await
+    }
+    
+    /// Receive every connection state change from now on
+    pub fn subscribe_connection_events(&self) -> broadcast::Receiver<ConnectionEvent> {
+        self.state.events.subscribe()
+    }
+    
+    /// Latency and recency of recent bridge requests
+    pub fn connection_stats(&self) -> ConnectionStats {
+        let stats = self.stats.lock().unwrap();
+        let avg_latency_ms = (!stats.latencies.is_empty()).then(|| {
+            stats.latencies.iter().sum::<Duration>().as_secs_f64() * 1000.0
+                / stats.latencies.len() as f64
+        });
+        
+        ConnectionStats {
+            bridge_url: self.bridge_url.clone(),
+            last_success_ms: stats
+                .last_success
+                .map(|at| at.elapsed().as_millis() as u64),
+            avg_latency_ms,
+            circuit_state: self.breaker.state(),
+            bridge_version: self.bridge_version.get(),
+        }
     }
     
     /// Execute order via bridge
-    pub async fn execute_order(&self, order: &MT5Order) -> Result<u64> {
-        if !self.is_connected().await {
-            // Try to reconnect
-            if let Err(e) = self.connect().await {
-                return Err(anyhow::anyhow!("Not connected to MT5 bridge: {}", e));
-            }
+    pub async fn execute_order(&self, order: &MT5Order) -> Result<MT5OrderResult> {
+        metrics().orders_submitted.inc();
+        metrics().orders_by_strategy.inc(&self.settings.magic_label(order.magic));
+        let result = self.submit_order(order).await;
+        if result.is_err() {
+            metrics().orders_failed.inc();
+        }
+        result
+    }
+    
+    async fn submit_order(&self, order: &MT5Order) -> Result<MT5OrderResult> {
+        if self.settings.mt5_dry_run {
+            return self.dry_run_order(order).await;
         }
         
-        let url = format!("{}/orders", self.bridge_url);
-        
-        // Map MT5 order type to bridge format
-        let action = self.map_order_type_to_action(&order.order_type)?;
+        order.validate()?;
+        self.ensure_connected().await?;
+        self.check_spread(order).await?;
         
-        let payload = serde_json::json!({
-            "symbol": order.symbol,
-            "action": action,
-            "volume": order.volume,
-            "price": order.price,
-            "stop_loss": order.stop_loss,
-            "take_profit": order.take_profit,
-            "comment": order.comment,
-            "magic": order.magic,
-        });
+        let order = self.price_market_order(order).await;
+        let order = &self.normalize_order(&order).await?;
+        let url = format!("{}/orders", self.bridge_url);
+        // Generated once so every retry of this order carries the same key
+        let client_order_id = client_order_id(order);
+        let payload = self.order_payload(order, &client_order_id);
         
+        let strategy = self.settings.magic_label(order.magic);
         info!(
             url = %url,
             symbol = %order.symbol,
+            price = order.price,
+            client_order_id = %client_order_id,
+            strategy = %strategy,
             "Sending order to MT5 bridge"
         );
         
-        let response = self.http_client
-            .post(&url)
-            .json(&payload)
-            .send()
-            .await
-            .context("Failed to send order to bridge")?;
+        let response = self
+            .request_with_timeout("submit_order", self.settings.order_timeout(), || {
+                self.http_client
+                    .post(&url)
+                    .header(IDEMPOTENCY_KEY_HEADER, &client_order_id)
+                    .json(&payload)
+            })
+            .await?;
         
-        let status = response.status();
-        if !status.is_success() {
-            let error_text = response.text().await.unwrap_or_default();
-            return Err(anyhow::anyhow!(
-                "Bridge returned error: {} - {}",
-                status,
-                error_text
-            ));
+        if !response.status().is_success() {
+            return Err(order_status_error(response).await);
         }
         
-        let result: BridgeResponse<OrderResponse> = response
-            .json()
-            .await
-            .context("Failed to parse bridge response")?;
+        let result: BridgeResponse<OrderResponse> = parse_envelope(response).await?;
         
         if result.success {
             if let Some(data) = result.data {
-                info!(ticket = data.ticket, "Order executed successfully");
-                Ok(data.ticket)
+                let (fill_price, fill_volume) = data.fill();
+                info!(
+                    ticket = data.ticket,
+                    retcode = ?data.retcode,
+                    fill_price = ?fill_price,
+                    strategy = %strategy,
+                    "Order executed successfully"
+                );
+                Ok(MT5OrderResult {
+                    ticket: data.ticket,
+                    retcode: data.retcode,
+                    dry_run: false,
+                    fill_price,
+                    fill_volume,
+                })
             } else {
-                Err(anyhow::anyhow!("Bridge returned success but no ticket"))
+                Err(MT5Error::Deserialize("Bridge returned success but no ticket".to_string()))
             }
         } else {
-            Err(anyhow::anyhow!(
-                "Order execution failed: {}",
-                result.error.unwrap_or_else(|| "Unknown error".to_string())
+            Err(MT5Error::rejection(
+                result.data.and_then(|data| data.retcode),
+                result.error.unwrap_or_else(|| "Unknown error".to_string()),
             ))
         }
     }
     
+    /// Reject a market order while the spread exceeds `mt5_max_spread_points`
+    ///
+    /// Pending orders and orders with `skip_spread_check` set are not checked.
+    async fn check_spread(&self, order: &MT5Order) -> Result<()> {
+        let Some(max_spread) = self.settings.mt5_max_spread_points else {
+            return Ok(());
+        };
+        if order.order_type.is_pending() || order.skip_spread_check {
+            return Ok(());
+        }
+        
+        let spread = self.get_market_data(&order.symbol).await?.spread_points();
+        if spread > max_spread {
+            warn!(symbol = %order.symbol, spread, max_spread, "Order rejected, spread too wide");
+            return Err(MT5Error::OrderRejected {
+                retcode: None,
+                message: format!(
+                    "spread of {} points on {} exceeds the {} point limit",
+                    spread, order.symbol, max_spread
+                ),
+            });
+        }
+        Ok(())
+    }
+    
+    /// Price a market order sent without a price from the current quote
+    ///
+    /// The quote is picked by `mt5_price_source`. If none is available the
+    /// order is left unpriced and fills at whatever the broker offers.
+    async fn price_market_order(&self, order: &MT5Order) -> MT5Order {
+        let mut order = order.clone();
+        if order.order_type.is_pending() || order.price > 0.0 {
+            return order;
+        }
+        match self.get_market_data(&order.symbol).await {
+            Ok(quote) => {
+                order.price = quote.price_for(order.order_type.side(), self.settings.mt5_price_source);
+            }
+            Err(e) => warn!(
+                symbol = %order.symbol,
+                error = %e,
+                "Market data unavailable, sending market order without a price"
+            ),
+        }
+        order
+    }
+    
+    /// Round an order's prices and volume to its symbol's specification
+    ///
+    /// Symbol info is cached for `SYMBOL_INFO_TTL`. If it can't be fetched
+    /// the order is sent unchanged and the broker has the final say. Rounding
+    /// to `volume_step` can nudge the volume up, so `mt5_max_order_volume` is
+    /// checked again on the result.
+    async fn normalize_order(&self, order: &MT5Order) -> Result<MT5Order> {
+        let mut order = order.clone();
+        match self.cached_symbol_info(&order.symbol).await {
+            Ok(info) => info.normalize_order(&mut order)?,
+            Err(e) => warn!(
+                symbol = %order.symbol,
+                error = %e,
+                "Symbol info unavailable, sending order without rounding"
+            ),
+        }
+        self.check_volume_cap(&order)?;
+        Ok(order)
+    }
+    
+    /// Reject an order over `mt5_max_order_volume`
+    fn check_volume_cap(&self, order: &MT5Order) -> Result<()> {
+        match self.settings.mt5_max_order_volume {
+            Some(max) if order.volume > max => {
+                Err(OrderValidationError::VolumeAboveMaximum { volume: order.volume, max }.into())
+            }
+            _ => Ok(()),
+        }
+    }
+    
+    /// Symbol specification, served from cache while fresh
+    async fn cached_symbol_info(&self, symbol: &str) -> Result<MT5SymbolInfo> {
+        if let Some((fetched_at, info)) = self.symbol_cache.lock().unwrap().get(symbol) {
+            if fetched_at.elapsed() < SYMBOL_INFO_TTL {
+                return Ok(info.clone());
+            }
+        }
+        
+        let info = self.get_symbol_info(symbol).await?;
+        self.symbol_cache
+            .lock()
+            .unwrap()
+            .insert(symbol.to_string(), (Instant::now(), info.clone()));
+        Ok(info)
+    }
+    
+    /// Validate an order and fill it synthetically without sending it
+    ///
+    /// While the bridge is connected the order goes through the same checks
+    /// as a live one (spread limit, quote pricing, symbol rounding and volume
+    /// bounds, `mt5_max_order_volume`), so a dry run rejects what a live run
+    /// would. Otherwise only validation and the volume cap apply, as nothing
+    /// can be looked up. Tickets count up from 1 per client so dry runs are
+    /// reproducible. Orders with a price are filled at it in full; market
+    /// orders left without one are reported as pending.
+    async fn dry_run_order(&self, order: &MT5Order) -> Result<MT5OrderResult> {
+        order.validate()?;
+        let order = &if self.is_connected().await {
+            self.check_spread(order).await?;
+            let order = self.price_market_order(order).await;
+            self.normalize_order(&order).await?
+        } else {
+            self.check_volume_cap(order)?;
+            order.clone()
+        };
+        
+        let ticket = self.dry_run_ticket.fetch_add(1, Ordering::Relaxed) + 1;
+        info!(ticket, symbol = %order.symbol, "Dry run: order validated, not sent to MT5 bridge");
+        Ok(MT5OrderResult {
+            ticket,
+            retcode: Some(10009), // TRADE_RETCODE_DONE
+            dry_run: true,
+            fill_price: (order.price > 0.0).then_some(order.price),
+            fill_volume: (order.price > 0.0).then_some(order.volume),
+        })
+    }
+    
+    /// Execute several orders in one bridge round-trip
+    ///
+    /// The outer error covers transport failures; each order's own outcome
+    /// (result or rejection reason) is reported individually, in input order.
+    /// Orders that fail validation, and market orders over the spread limit,
+    /// are rejected individually and left out of the batch sent to the bridge.
+    pub async fn execute_orders(&self, orders: &[MT5Order]) -> Result<Vec<Result<MT5OrderResult, String>>> {
+        metrics().orders_submitted.inc_by(orders.len() as u64);
+        for order in orders {
+            metrics().orders_by_strategy.inc(&self.settings.magic_label(order.magic));
+        }
+        let result = self.submit_orders(orders).await;
+        match &result {
+            Ok(results) => metrics()
+                .orders_failed
+                .inc_by(results.iter().filter(|r| r.is_err()).count() as u64),
+            Err(_) => metrics().orders_failed.inc_by(orders.len() as u64),
+        }
+        result
+    }
+    
+    async fn submit_orders(&self, orders: &[MT5Order]) -> Result<Vec<Result<MT5OrderResult, String>>> {
+        if self.settings.mt5_dry_run {
+            let mut results = Vec::with_capacity(orders.len());
+            for order in orders {
+                results.push(
+                    self.dry_run_order(order)
+                        .await
+                        .map_err(|e| e.to_string()),
+                );
+            }
+            return Ok(results);
+        }
+        
+        self.ensure_connected().await?;
+        
+        // `None` marks an order that goes to the bridge, filled in from its reply
+        let mut outcomes: Vec<Option<Result<MT5OrderResult, String>>> = Vec::with_capacity(orders.len());
+        let mut normalized = Vec::with_capacity(orders.len());
+        for order in orders {
+            if let Err(e) = order.validate() {
+                outcomes.push(Some(Err(MT5Error::from(e).to_string())));
+                continue;
+            }
+            if let Err(e) = self.check_spread(order).await {
+                outcomes.push(Some(Err(e.to_string())));
+                continue;
+            }
+            let order = self.price_market_order(order).await;
+            match self.normalize_order(&order).await {
+                Ok(order) => normalized.push(order),
+                Err(e) => {
+                    outcomes.push(Some(Err(e.to_string())));
+                    continue;
+                }
+            }
+            outcomes.push(None);
+        }
+        if normalized.is_empty() {
+            return Ok(outcomes.into_iter().flatten().collect());
+        }
+        let orders = normalized.as_slice();
+        
+        let url = format!("{}/orders/batch", self.bridge_url);
+        let payload = serde_json::json!({
+            "orders": orders
+                .iter()
+                .map(|order| self.order_payload(order, &client_order_id(order)))
+                .collect::<Vec<_>>(),
+        });
+        
+        info!(url = %url, count = orders.len(), "Sending order batch to MT5 bridge");
+        
+        let response = self
+            .request_with_timeout("submit_orders", self.settings.order_timeout(), || {
+                self.http_client.post(&url).json(&payload)
+            })
+            .await?;
+        
+        if !response.status().is_success() {
+            return Err(order_status_error(response).await);
+        }
+        
+        let result: BridgeResponse<Vec<BridgeResponse<OrderResponse>>> = parse_envelope(response).await?;
+        
+        if !result.success {
+            return Err(MT5Error::OrderRejected {
+                retcode: None,
+                message: result.error.unwrap_or_else(|| "Unknown error".to_string()),
+            });
+        }
+        
+        let results = result.data.unwrap_or_default();
+        if results.len() != orders.len() {
+            return Err(MT5Error::Deserialize(format!(
+                "Bridge returned {} results for {} orders",
+                results.len(),
+                orders.len()
+            )));
+        }
+        
+        let mut sent = results.into_iter().map(|item| match (item.success, item.data) {
+            (true, Some(data)) => {
+                let (fill_price, fill_volume) = data.fill();
+                Ok(MT5OrderResult {
+                    ticket: data.ticket,
+                    retcode: data.retcode,
+                    dry_run: false,
+                    fill_price,
+                    fill_volume,
+                })
+            }
+            (true, None) => Err("Bridge returned success but no ticket".to_string()),
+            (false, data) => {
+                let message = item.error.unwrap_or_else(|| "Unknown error".to_string());
+                match data.and_then(|data| data.retcode) {
+                    Some(retcode) => Err(MT5Error::rejection(Some(retcode), message).to_string()),
+                    None => Err(message),
+                }
+            }
+        });
+        Ok(outcomes
+            .into_iter()
+            .map(|outcome| outcome.unwrap_or_else(|| sent.next().expect("one bridge result per sent order")))
+            .collect())
+    }
+    
     /// Get order status
     pub async fn get_order(&self, ticket: u64) -> Result<MT5Order> {
         let url = format!("{}/orders/{}", self.bridge_url, ticket);
         
-        let response = self.http_client
-            .get(&url)
-            .send()
+        let response = self
+            .retry_request("get_order", || self.http_client.get(&url))
             .await?;
         
         if !response.status().is_success() {
-            return Err(anyhow::anyhow!("Order not found: {}", ticket));
+            return Err(status_error(response, format!("Order {}", ticket)).await);
         }
         
-        let result: BridgeResponse<MT5Order> = response.json().await?;
+        let result: BridgeResponse<MT5Order> = parse_envelope(response).await?;
         
         if result.success {
-            result.data.ok_or_else(|| anyhow::anyhow!("No order data returned"))
+            let mut order = result
+                .data
+                .ok_or_else(|| MT5Error::Deserialize("No order data returned".to_string()))?;
+            order.symbol = self.strip_symbol_prefix(&order.symbol);
+            Ok(order)
         } else {
-            Err(anyhow::anyhow!(
+            Err(MT5Error::Bridge(format!(
                 "Failed to get order: {}",
                 result.error.unwrap_or_default()
-            ))
+            )))
+        }
+    }
+    
+    /// Get all open and pending orders
+    pub async fn get_orders(&self) -> Result<Vec<MT5Order>> {
+        let url = format!("{}/orders", self.bridge_url);
+        
+        let response = self
+            .retry_request("get_orders", || self.http_client.get(&url))
+            .await?;
+        
+        let result: BridgeResponse<Vec<MT5Order>> = parse_envelope(response).await?;
+        
+        if result.success {
+            Ok(result
+                .data
+                .unwrap_or_default()
+                .into_iter()
+                .map(|mut order| {
+                    order.symbol = self.strip_symbol_prefix(&order.symbol);
+                    order
+                })
+                .collect())
+        } else {
+            Err(MT5Error::Bridge(format!(
+                "Failed to get orders: {}",
+                result.error.unwrap_or_default()
+            )))
+        }
+    }
+    
+    /// Get historical orders between `from` and `to` (epoch seconds)
+    pub async fn get_order_history(
+        &self,
+        from: i64,
+        to: i64,
+        symbol: Option<&str>,
+    ) -> Result<Vec<MT5Order>> {
+        self.require(BridgeFeature::History).await?;
+        let url = format!("{}/history/orders", self.bridge_url);
+        
+        let mut query = vec![("from", from.to_string()), ("to", to.to_string())];
+        if let Some(symbol) = symbol {
+            query.push(("symbol", self.apply_symbol_prefix(symbol)));
+        }
+        
+        let response = self
+            .retry_request("get_order_history", || self.http_client.get(&url).query(&query))
+            .await?;
+        
+        let result: BridgeResponse<Vec<MT5Order>> = parse_envelope(response).await?;
+        
+        if result.success {
+            Ok(result
+                .data
+                .unwrap_or_default()
+                .into_iter()
+                .map(|mut order| {
+                    order.symbol = self.strip_symbol_prefix(&order.symbol);
+                    order
+                })
+                .collect())
+        } else {
+            Err(MT5Error::Bridge(format!(
+                "Failed to get order history: {}",
+                result.error.unwrap_or_default()
+            )))
         }
     }
     
@@ -225,57 +1274,87 @@ impl MT5BridgeClient {
     pub async fn cancel_order(&self, ticket: u64) -> Result<()> {
         let url = format!("{}/orders/{}", self.bridge_url, ticket);
         
-        let response = self.http_client
-            .delete(&url)
-            .send()
+        let response = self
+            .retry_request("cancel_order", || self.http_client.delete(&url))
+            .await?;
+        
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(status_error(response, format!("Order {}", ticket)).await)
+        }
+    }
+    
+    /// Modify stop loss and/or take profit of an order
+    pub async fn modify_order(
+        &self,
+        ticket: u64,
+        stop_loss: Option<f64>,
+        take_profit: Option<f64>,
+    ) -> Result<()> {
+        let url = format!("{}/orders/{}", self.bridge_url, ticket);
+        let payload = ModifyOrderPayload { stop_loss, take_profit };
+        
+        let response = self
+            .retry_request("modify_order", || self.http_client.patch(&url).json(&payload))
             .await?;
         
         if response.status().is_success() {
             Ok(())
         } else {
-            Err(anyhow::anyhow!("Failed to cancel order: {}", ticket))
+            Err(status_error(response, format!("Order {}", ticket)).await)
         }
     }
     
-    /// Get all positions
+    /// Get all positions, sorted by ticket ascending with one entry per ticket
+    ///
+    /// Bridges may list positions in any order, and a reconnect race can
+    /// list one twice; the later listing is kept as the fresher one.
     pub async fn get_positions(&self) -> Result<Vec<MT5Position>> {
         let url = format!("{}/positions", self.bridge_url);
         
-        let response = self.http_client
-            .get(&url)
-            .send()
+        let response = self
+            .retry_request("get_positions", || self.http_client.get(&url))
             .await?;
         
-        let result: BridgeResponse<Vec<PositionData>> = response.json().await?;
+        let result: BridgeResponse<Vec<PositionData>> = parse_envelope(response).await?;
         
         if result.success {
-            if let Some(positions) = result.data {
-                Ok(positions.into_iter().map(|p| self.position_data_to_model(p)).collect())
-            } else {
-                Ok(vec![])
-            }
+            let positions: BTreeMap<u64, MT5Position> = result
+                .data
+                .unwrap_or_default()
+                .into_iter()
+                .map(|p| {
+                    let position = self.position_data_to_model(p);
+                    (position.ticket, position)
+                })
+                .collect();
+            Ok(positions.into_values().collect())
         } else {
-            Err(anyhow::anyhow!(
+            Err(MT5Error::Bridge(format!(
                 "Failed to get positions: {}",
                 result.error.unwrap_or_default()
-            ))
+            )))
         }
     }
     
     /// Get position for symbol
     pub async fn get_position(&self, symbol: &str) -> Result<Option<MT5Position>> {
-        let url = format!("{}/positions/{}", self.bridge_url, symbol);
+        let url = format!(
+            "{}/positions/{}",
+            self.bridge_url,
+            self.apply_symbol_prefix(symbol)
+        );
         
-        let response = self.http_client
-            .get(&url)
-            .send()
+        let response = self
+            .retry_request("get_position", || self.http_client.get(&url))
             .await?;
         
         if response.status() == 404 {
             return Ok(None);
         }
         
-        let result: BridgeResponse<PositionData> = response.json().await?;
+        let result: BridgeResponse<PositionData> = parse_envelope(response).await?;
         
         if result.success {
             if let Some(data) = result.data {
@@ -284,10 +1363,35 @@ impl MT5BridgeClient {
                 Ok(None)
             }
         } else {
-            Err(anyhow::anyhow!(
+            Err(MT5Error::Bridge(format!(
                 "Failed to get position: {}",
                 result.error.unwrap_or_default()
-            ))
+            )))
+        }
+    }
+    
+    /// Get position by ticket
+    pub async fn get_position_by_ticket(&self, ticket: u64) -> Result<Option<MT5Position>> {
+        let url = format!("{}/positions/ticket/{}", self.bridge_url, ticket);
+        
+        let response = self
+            .retry_request("get_position_by_ticket", || self.http_client.get(&url))
+            .await?;
+        
+        if response.status() == 404 {
+            return Ok(None);
+        }
+        
+        let result: BridgeResponse<PositionData> = parse_envelope(response).await?;
+        
+        if result.success {
+            Ok(result.data.map(|data| self.position_data_to_model(data)))
+        } else {
+            Err(MT5Error::Bridge(format!(
+                "Failed to get position {}: {}",
+                ticket,
+                result.error.unwrap_or_default()
+            )))
         }
     }
     
@@ -295,33 +1399,86 @@ impl MT5BridgeClient {
     pub async fn close_position(&self, ticket: u64) -> Result<()> {
         let url = format!("{}/positions/{}", self.bridge_url, ticket);
         
-        let response = self.http_client
-            .delete(&url)
-            .send()
+        let response = self
+            .retry_request("close_position", || self.http_client.delete(&url))
             .await?;
         
         if response.status().is_success() {
+            metrics().positions_closed.inc();
             Ok(())
         } else {
-            Err(anyhow::anyhow!("Failed to close position: {}", ticket))
+            Err(status_error(response, format!("Position {}", ticket)).await)
         }
     }
     
     /// Get market data
+    ///
+    /// While the circuit breaker is open, a quote cached within
+    /// `mt5_market_stale_ttl_ms` is returned marked `stale` instead of failing.
     pub async fn get_market_data(&self, symbol: &str) -> Result<MT5MarketData> {
-        let url = format!("{}/market/{}", self.bridge_url, symbol);
+        match self.fetch_market_data(symbol).await {
+            Ok(data) => {
+                if let Some(max_age) = self.settings.mt5_max_quote_age_ms.map(Duration::from_millis) {
+                    if data.is_stale(max_age) {
+                        warn!(
+                            symbol = %symbol,
+                            age_ms = data.age(chrono::Utc::now()).as_millis() as u64,
+                            max_age_ms = max_age.as_millis() as u64,
+                            "MT5 bridge returned an old quote"
+                        );
+                    }
+                }
+                self.market_cache
+                    .lock()
+                    .unwrap()
+                    .insert(symbol.to_string(), (Instant::now(), data.clone()));
+                Ok(data)
+            }
+            Err(e) => match self.stale_market_data(symbol) {
+                Some(data) => {
+                    warn!(symbol = %symbol, error = %e, "MT5 bridge unavailable, serving cached market data");
+                    Ok(data)
+                }
+                None => Err(e),
+            },
+        }
+    }
+    
+    /// Cached quote for `symbol` if the circuit is open and it is recent enough
+    fn stale_market_data(&self, symbol: &str) -> Option<MT5MarketData> {
+        let ttl = Duration::from_millis(self.settings.mt5_market_stale_ttl_ms);
+        if ttl.is_zero() || self.breaker.state() != CircuitState::Open {
+            return None;
+        }
+        let cache = self.market_cache.lock().unwrap();
+        let (fetched, data) = cache.get(symbol)?;
+        (fetched.elapsed() < ttl).then(|| MT5MarketData {
+            stale: true,
+            ..data.clone()
+        })
+    }
+    
+    async fn fetch_market_data(&self, symbol: &str) -> Result<MT5MarketData> {
+        let url = format!(
+            "{}/market/{}",
+            self.bridge_url,
+            self.apply_symbol_prefix(symbol)
+        );
         
-        let response = self.http_client
-            .get(&url)
-            .send()
+        let response = self
+            .request_with_timeout("get_market_data", self.settings.market_timeout(), || self.http_client.get(&url))
             .await?;
         
-        let result: BridgeResponse<MarketDataResponse> = response.json().await?;
+        if response.status() == 404 {
+            return Err(MT5Error::InvalidSymbol(symbol.to_string()));
+        }
+        
+        let result: BridgeResponse<MarketDataResponse> = parse_envelope(response).await?;
         
         if result.success {
             if let Some(data) = result.data {
                 Ok(MT5MarketData {
-                    symbol: data.symbol,
+                    symbol: self.strip_symbol_prefix(&data.symbol),
                     bid: data.bid,
                     ask: data.ask,
                     last: data.last,
@@ -329,45 +1486,317 @@ impl MT5BridgeClient {
                     time: data.time,
                     spread: data.spread,
                     digits: data.digits,
+                    stale: false,
                 })
             } else {
-                Err(anyhow::anyhow!("No market data returned"))
+                Err(MT5Error::Deserialize("No market data returned".to_string()))
             }
         } else {
-            Err(anyhow::anyhow!(
+            Err(MT5Error::Bridge(format!(
                 "Failed to get market data: {}",
                 result.error.unwrap_or_default()
-            ))
+            )))
+        }
+    }
+    
+    /// Subscribe to the bridge's tick WebSocket for `symbol`
+    ///
+    /// Connects to `{mt5_bridge_ws_url}/market/{symbol}`, which sends one
+    /// `MT5MarketData` JSON text frame per tick. The stream ends when the
+    /// bridge closes the socket; a connection error is its last item.
+    pub async fn subscribe_ticks(&self, symbol: &str) -> Result<BoxStream<'static, Result<MT5MarketData>>> {
+        let Some(ws_url) = &self.settings.mt5_bridge_ws_url else {
+            return Err(MT5Error::BridgeUnavailable("MT5_BRIDGE_WS_URL is not set".to_string()));
+        };
+        let url = format!(
+            "{}/market/{}",
+            ws_url.trim_end_matches('/'),
+            self.apply_symbol_prefix(symbol)
+        );
+        let mut request = url
+            .into_client_request()
+            .map_err(|e| MT5Error::BridgeUnavailable(e.to_string()))?;
+        if let Some(token) = &self.settings.mt5_bridge_token {
+            let value = HeaderValue::from_str(&format!("Bearer {}", token))
+                .map_err(|e| MT5Error::BridgeUnavailable(e.to_string()))?;
+            request.headers_mut().insert(AUTHORIZATION, value);
+        }
+        
+        let (socket, _) = tokio_tungstenite::connect_async(request)
+            .await
+            .map_err(|e| MT5Error::BridgeUnavailable(e.to_string()))?;
+        info!(symbol = %symbol, "Subscribed to MT5 bridge ticks");
+        
+        let prefix = self.settings.mt5_symbol_prefix.clone();
+        let ticks = socket.filter_map(move |message| {
+            let tick = match message {
+                Ok(Message::Text(text)) => Some(
+                    serde_json::from_str::<MT5MarketData>(&text)
+                        .map(|mut data| {
+                            data.symbol = symbols::strip_symbol_prefix(&data.symbol, &prefix);
+                            data
+                        })
+                        .map_err(|e| MT5Error::Deserialize(e.to_string())),
+                ),
+                Ok(_) => None,
+                Err(e) => Some(Err(MT5Error::BridgeUnavailable(e.to_string()))),
+            };
+            async move { tick }
+        });
+        Ok(ticks.boxed())
+    }
+    
+    /// Get the most recent `count` candles for `symbol` on `timeframe`, oldest first
+    pub async fn get_candles(&self, symbol: &str, timeframe: &str, count: u32) -> Result<Vec<MT5Candle>> {
+        self.require(BridgeFeature::Candles).await?;
+        let url = format!(
+            "{}/market/{}/candles",
+            self.bridge_url,
+            self.apply_symbol_prefix(symbol)
+        );
+        let count = count.to_string();
+        
+        let response = self
+            .request_with_timeout("get_candles", self.settings.market_timeout(), || {
+                self.http_client
+                    .get(&url)
+                    .query(&[("timeframe", timeframe), ("count", count.as_str())])
+            })
+            .await?;
+        
+        if response.status() == 404 {
+            return Err(MT5Error::InvalidSymbol(symbol.to_string()));
+        }
+        
+        let result: BridgeResponse<Vec<MT5Candle>> = parse_envelope(response).await?;
+        
+        if result.success {
+            Ok(result.data.unwrap_or_default())
+        } else {
+            Err(MT5Error::Bridge(format!(
+                "Failed to get candles: {}",
+                result.error.unwrap_or_default()
+            )))
+        }
+    }
+    
+    /// Get symbol trading specification
+    pub async fn get_symbol_info(&self, symbol: &str) -> Result<MT5SymbolInfo> {
+        let url = format!(
+            "{}/symbols/{}",
+            self.bridge_url,
+            self.apply_symbol_prefix(symbol)
+        );
+        
+        let response = self
+            .retry_request("get_symbol_info", || self.http_client.get(&url))
+            .await?;
+        
+        if response.status() == 404 {
+            return Err(MT5Error::InvalidSymbol(symbol.to_string()));
+        }
+        
+        let result: BridgeResponse<MT5SymbolInfo> = parse_envelope(response).await?;
+        
+        if result.success {
+            let mut info = result
+                .data
+                .ok_or_else(|| MT5Error::Deserialize("No symbol info returned".to_string()))?;
+            info.symbol = self.strip_symbol_prefix(&info.symbol);
+            Ok(info)
+        } else {
+            Err(MT5Error::Bridge(format!(
+                "Failed to get symbol info: {}",
+                result.error.unwrap_or_default()
+            )))
+        }
+    }
+    
+    /// Symbols the account can trade, optionally limited to a bridge `group`
+    ///
+    /// Lists are cached per group for `SYMBOL_LIST_TTL`.
+    pub async fn list_symbols(&self, group: Option<&str>) -> Result<Vec<String>> {
+        let key = group.unwrap_or_default().to_string();
+        if let Some((fetched_at, symbols)) = self.symbol_list_cache.lock().unwrap().get(&key) {
+            if fetched_at.elapsed() < SYMBOL_LIST_TTL {
+                return Ok(symbols.clone());
+            }
+        }
+        
+        let url = format!("{}/symbols", self.bridge_url);
+        let query: Vec<(&str, &str)> = group.map(|group| ("group", group)).into_iter().collect();
+        
+        let response = self
+            .retry_request("list_symbols", || self.http_client.get(&url).query(&query))
+            .await?;
+        
+        let result: BridgeResponse<Vec<String>> = parse_envelope(response).await?;
+        
+        if !result.success {
+            return Err(MT5Error::Bridge(format!(
+                "Failed to list symbols: {}",
+                result.error.unwrap_or_default()
+            )));
+        }
+        
+        let symbols: Vec<String> = result
+            .data
+            .unwrap_or_default()
+            .iter()
+            .map(|symbol| self.strip_symbol_prefix(symbol))
+            .collect();
+        self.symbol_list_cache
+            .lock()
+            .unwrap()
+            .insert(key, (Instant::now(), symbols.clone()));
+        Ok(symbols)
+    }
+    
+    /// Get account information
+    pub async fn get_account_info(&self) -> Result<MT5AccountInfo> {
+        self.require(BridgeFeature::Account).await?;
+        let url = format!("{}/account", self.bridge_url);
+        
+        let response = self
+            .retry_request("get_account_info", || self.http_client.get(&url))
+            .await?;
+        
+        let result: BridgeResponse<MT5AccountInfo> = parse_envelope(response).await?;
+        
+        if result.success {
+            result
+                .data
+                .ok_or_else(|| MT5Error::Deserialize("No account data returned".to_string()))
+        } else {
+            Err(MT5Error::Bridge(format!(
+                "Failed to get account info: {}",
+                result.error.unwrap_or_default()
+            )))
+        }
+    }
+    
+    /// Features the bridge supports, fetched once and cached until `reconnect`
+    ///
+    /// A bridge without a `/capabilities` endpoint is taken to support everything.
+    pub async fn capabilities(&self) -> Result<BridgeCapabilities> {
+        if let Some(capabilities) = *self.capabilities.lock().unwrap() {
+            return Ok(capabilities);
+        }
+        
+        let url = format!("{}/capabilities", self.bridge_url);
+        let response = self
+            .retry_request("capabilities", || self.http_client.get(&url))
+            .await?;
+        
+        let capabilities = if response.status() == 404 {
+            BridgeCapabilities::default()
+        } else {
+            let result: BridgeResponse<BridgeCapabilities> = parse_envelope(response).await?;
+            if !result.success {
+                return Err(MT5Error::Bridge(format!(
+                    "Failed to get capabilities: {}",
+                    result.error.unwrap_or_default()
+                )));
+            }
+            result.data.unwrap_or_default()
+        };
+        *self.capabilities.lock().unwrap() = Some(capabilities);
+        Ok(capabilities)
+    }
+    
+    /// Fail with `Unsupported` when the bridge reports it lacks `feature`
+    ///
+    /// When the capabilities can't be fetched the call goes ahead and
+    /// reports its own error.
+    async fn require(&self, feature: BridgeFeature) -> Result<()> {
+        match self.capabilities().await {
+            Ok(capabilities) if !capabilities.supports(feature) => Err(MT5Error::Unsupported(feature)),
+            _ => Ok(()),
         }
     }
     
     /// Health check
+    ///
+    /// Probes the bridge health endpoint and updates the cached connection
+    /// state: success marks the client connected, failure starts a background
+    /// reconnection. Use `is_connected` for a cheap read of the cached state.
     pub async fn health_check(&self) -> bool {
-        self.is_connected().await
+        match self.probe().await {
+            Ok(()) => true,
+            Err(e) => {
+                warn!(error = %e, "MT5 bridge health check failed");
+                self.start_reconnect().await;
+                false
+            }
+        }
+    }
+    
+    /// Timeout for health probes: the request timeout, capped at `HEALTH_PROBE_TIMEOUT`
+    fn probe_timeout(&self) -> Duration {
+        Duration::from_millis(self.settings.mt5_timeout_ms).min(HEALTH_PROBE_TIMEOUT)
+    }
+    
+    /// Build the bridge order payload
+    fn order_payload(&self, order: &MT5Order, client_order_id: &str) -> serde_json::Value {
+        let trade_type = order.order_type.trade_type();
+        let mut payload = serde_json::json!({
+            "client_order_id": client_order_id,
+            "symbol": self.apply_symbol_prefix(&order.symbol),
+            "action": trade_type.action,
+            "type": trade_type.order_type,
+            "volume": order.volume,
+            "price": order.price,
+            "stop_limit": order.stop_limit,
+            "stop_loss": order.stop_loss,
+            "take_profit": order.take_profit,
+            "comment": order.comment,
+            "magic": order.magic,
+        });
+        // Deviation only applies to orders filled at market
+        if let Some(deviation) = order.deviation.filter(|_| !order.order_type.is_pending()) {
+            payload["deviation"] = deviation.into();
+        }
+        // Always send seconds, even if the caller gave milliseconds
+        if let Some(expiration) = order.expiration_utc() {
+            payload["expiration"] = expiration.timestamp().into();
+        }
+        payload
     }
     
-    /// Map MT5 order type to action code
-    fn map_order_type_to_action(&self, order_type: &str) -> Result<u32> {
-        match order_type {
-            "OP_BUY" => Ok(0),      // TRADE_ACTION_DEAL
-            "OP_SELL" => Ok(1),     // TRADE_ACTION_DEAL
-            "OP_BUYLIMIT" => Ok(2), // TRADE_ACTION_PENDING
-            "OP_SELLLIMIT" => Ok(3),
-            "OP_BUYSTOP" => Ok(4),
-            "OP_SELLSTOP" => Ok(5),
-            _ => Err(anyhow::anyhow!("Unknown order type: {}", order_type)),
+    /// Add the broker symbol prefix to a caller-facing symbol, uppercasing
+    /// it first when `mt5_symbol_uppercase` is set
+    fn apply_symbol_prefix(&self, symbol: &str) -> String {
+        let prefix = &self.settings.mt5_symbol_prefix;
+        if self.settings.mt5_symbol_uppercase {
+            let symbol = symbols::strip_symbol_prefix(symbol, prefix).to_ascii_uppercase();
+            symbols::apply_symbol_prefix(&symbol, prefix)
+        } else {
+            symbols::apply_symbol_prefix(symbol, prefix)
         }
     }
     
+    /// Caller-facing form of `symbol`, as the bridge's responses report it
+    ///
+    /// Uppercased when `mt5_symbol_uppercase` is set, so a lowercase symbol
+    /// compares equal to the ones in position and order listings.
+    pub fn normalize_symbol(&self, symbol: &str) -> String {
+        self.strip_symbol_prefix(&self.apply_symbol_prefix(symbol))
+    }
+    
+    /// Remove the broker symbol prefix from a bridge-facing symbol
+    fn strip_symbol_prefix(&self, symbol: &str) -> String {
+        symbols::strip_symbol_prefix(symbol, &self.settings.mt5_symbol_prefix)
+    }
+    
     /// Convert position data to model
     fn position_data_to_model(&self, data: PositionData) -> MT5Position {
         MT5Position {
             ticket: data.ticket,
-            symbol: data.symbol,
+            symbol: self.strip_symbol_prefix(&data.symbol),
             position_type: if data.position_type == 0 {
-                "OP_BUY".to_string()
+                MT5Side::Buy
             } else {
-                "OP_SELL".to_string()
+                MT5Side::Sell
             },
             volume: data.volume,
             price_open: data.price_open,
@@ -384,3 +1813,122 @@ impl MT5BridgeClient {
     }
 }
 
+/// Idempotency key for an order, generating one if the caller didn't
+fn client_order_id(order: &MT5Order) -> String {
+    order
+        .client_order_id
+        .clone()
+        .unwrap_or_else(|| Uuid::new_v4().to_string())
+}
+
+/// Probe the bridge health endpoint
+async fn probe_health(
+    http_client: &Client,
+    bridge_url: &str,
+    timeout: Duration,
+    bridge_version: &BridgeVersion,
+) -> Result<()> {
+    let health_url = format!("{}/health", bridge_url);
+    let mut request = http_client.get(&health_url).timeout(timeout);
+    if let Some(id) = correlation::current() {
+        request = request.header(CORRELATION_ID_HEADER, id);
+    }
+    let response = request.send().await?;
+    bridge_version.observe(response.headers());
+    
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(MT5Error::BridgeUnavailable(format!(
+            "MT5 bridge service returned status: {}",
+            response.status()
+        )))
+    }
+}
+
+/// Read a bridge envelope, tolerating bridges that don't follow it exactly
+///
+/// A 2xx body without `success` is taken as the bare data. Bodies that
+/// aren't JSON at all (an HTML error page from a proxy, say) become a
+/// `Deserialize` error quoting the start of the body.
+async fn parse_envelope<T: DeserializeOwned>(response: Response) -> Result<BridgeResponse<T>> {
+    let status = response.status();
+    let body = response.bytes().await?;
+    let invalid = |reason: String| {
+        MT5Error::Deserialize(format!(
+            "Bridge returned {} with {}: {}",
+            status,
+            reason,
+            body_snippet(&body)
+        ))
+    };
+    
+    let value: serde_json::Value =
+        serde_json::from_slice(&body).map_err(|_| invalid("a non-JSON body".to_string()))?;
+    if value.get("success").is_some() {
+        return serde_json::from_value(value).map_err(|e| invalid(format!("an unexpected envelope ({})", e)));
+    }
+    if !status.is_success() {
+        let error = value.get("error").and_then(|error| error.as_str()).map(str::to_string);
+        return Ok(BridgeResponse {
+            success: false,
+            data: None,
+            error: Some(error.unwrap_or_else(|| body_snippet(&body))),
+        });
+    }
+    
+    let data = serde_json::from_value(value).map_err(|e| invalid(format!("unexpected data ({})", e)))?;
+    Ok(BridgeResponse { success: true, data: Some(data), error: None })
+}
+
+/// Start of a response body for error messages, on one line
+fn body_snippet(body: &[u8]) -> String {
+    let text = String::from_utf8_lossy(body);
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    match text.char_indices().nth(BODY_SNIPPET_CHARS) {
+        Some((end, _)) => format!("{}...", &text[..end]),
+        None => text,
+    }
+}
+
+/// Map an unsuccessful bridge response for `resource` to an error
+///
+/// 404 means the resource is gone, 5xx that the bridge itself is failing;
+/// any other status is reported with the bridge's own explanation.
+async fn status_error(response: Response, resource: String) -> MT5Error {
+    let status = response.status();
+    if status == 404 {
+        return MT5Error::NotFound(resource);
+    }
+    
+    let error_text = response.text().await.unwrap_or_default();
+    if status.is_server_error() {
+        MT5Error::BridgeUnavailable(format!("{} - {}", status, error_text))
+    } else {
+        MT5Error::Bridge(format!("{}: {} - {}", resource, status, error_text))
+    }
+}
+
+/// Map an unsuccessful order submission response to an error
+///
+/// A 4xx means the bridge refused the order itself, so it is reported as a
+/// rejection rather than a bridge failure, keeping the retcode and reason
+/// when the body is a bridge envelope.
+async fn order_status_error(response: Response) -> MT5Error {
+    let status = response.status();
+    let error_text = response.text().await.unwrap_or_default();
+    if status.is_server_error() {
+        return MT5Error::BridgeUnavailable(format!("{} - {}", status, error_text));
+    }
+    
+    match serde_json::from_str::<BridgeResponse<OrderResponse>>(&error_text) {
+        Ok(body) => MT5Error::rejection(
+            body.data.and_then(|data| data.retcode),
+            body.error.unwrap_or_else(|| status.to_string()),
+        ),
+        Err(_) => MT5Error::OrderRejected {
+            retcode: None,
+            message: format!("{} - {}", status, error_text),
+        },
+    }
+}
@@ -7,27 +7,55 @@
 //! - Named pipes (future)
 
 use crate::config::Settings;
-use crate::models::{MT5MarketData, MT5Order, MT5Position};
-use crate::mt5::bridge::MT5BridgeClient;
-use anyhow::Result;
-use std::sync::Arc;
+use crate::models::{
+    net_position, MT5AccountInfo, MT5Candle, MT5MarketData, MT5Order, MT5OrderResult, MT5Position,
+    MT5SymbolInfo, NetPosition, OrderState, PositionFilter,
+};
+use crate::mt5::bridge::{BridgeCapabilities, ConnectionEvent, ConnectionState, ConnectionStats, MT5BridgeClient};
+use crate::mt5::error::{MT5Error, Result};
+use futures_util::future::{BoxFuture, FutureExt, Shared};
+use futures_util::stream::{self, BoxStream, StreamExt};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 /// MT5 Client - Unified interface for MT5 integration
 ///
 /// Currently uses HTTP bridge client. Can be extended to support
 /// direct DLL integration or named pipes.
 pub struct MT5Client {
-    bridge: MT5BridgeClient,
+    bridge: Arc<MT5BridgeClient>,
+    settings: Arc<Settings>,
+    /// Recent `get_order` results, kept for `mt5_order_cache_ttl_ms`
+    order_cache: Mutex<HashMap<u64, (Instant, MT5Order)>>,
+    /// Market data requests in progress, shared by concurrent callers for the same symbol
+    market_in_flight: Mutex<HashMap<String, MarketDataRequest>>,
 }
 
+/// How far back `get_order` searches history for an order no longer live
+const ORDER_HISTORY_LOOKBACK: chrono::Duration = chrono::Duration::days(30);
+
+/// A bridge market data request that several callers can await
+type MarketDataRequest = Shared<BoxFuture<'static, Result<MT5MarketData>>>;
+
 impl MT5Client {
     /// Create new MT5 client
     ///
     /// Uses HTTP bridge by default. Set MT5_BRIDGE_URL environment variable
     /// to specify bridge service URL (default: http://localhost:8006)
-    pub async fn new(settings: Arc<Settings>) -> Result<Self> {
-        let bridge = MT5BridgeClient::new(settings).await?;
-        Ok(Self { bridge })
+    pub async fn new(settings: Arc<Settings>) -> anyhow::Result<Self> {
+        let bridge = Arc::new(MT5BridgeClient::new(settings.clone()).await?);
+        Ok(Self {
+            bridge,
+            settings,
+            order_cache: Mutex::new(HashMap::new()),
+            market_in_flight: Mutex::new(HashMap::new()),
+        })
+    }
+    
+    /// Strategy label for a magic number (see `Settings::magic_label`)
+    pub fn magic_label(&self, magic: u32) -> String {
+        self.settings.magic_label(magic)
     }
     
     /// Check if connected
@@ -35,19 +63,160 @@ impl MT5Client {
         self.bridge.is_connected().await
     }
     
+    /// Get connection state
+    pub async fn connection_state(&self) -> ConnectionState {
+        self.bridge.connection_state().await
+    }
+    
+    /// Features the bridge supports
+    pub async fn capabilities(&self) -> Result<BridgeCapabilities> {
+        self.bridge.capabilities().await
+    }
+    
+    /// Receive every bridge connection state change from now on
+    pub fn subscribe_connection_events(&self) -> tokio::sync::broadcast::Receiver<ConnectionEvent> {
+        self.bridge.subscribe_connection_events()
+    }
+    
+    /// Re-establish the bridge connection now, returning the resulting state
+    pub async fn reconnect(&self) -> ConnectionState {
+        self.bridge.reconnect().await
+    }
+    
+    /// Get bridge latency and last-success statistics
+    pub fn connection_stats(&self) -> ConnectionStats {
+        self.bridge.connection_stats()
+    }
+    
     /// Execute order
-    pub async fn execute_order(&self, order: &MT5Order) -> Result<u64> {
+    pub async fn execute_order(&self, order: &MT5Order) -> Result<MT5OrderResult> {
         self.bridge.execute_order(order).await
     }
     
+    /// Execute several orders in one round-trip
+    pub async fn execute_orders(&self, orders: &[MT5Order]) -> Result<Vec<Result<MT5OrderResult, String>>> {
+        self.bridge.execute_orders(orders).await
+    }
+    
     /// Get order status
+    ///
+    /// An order that is no longer live (filled, cancelled or expired) is
+    /// looked up in the last `ORDER_HISTORY_LOOKBACK` of history; `state`
+    /// says which it is. With `mt5_order_cache_ttl_ms` set, repeated reads of
+    /// a ticket within the TTL are served from memory.
     pub async fn get_order(&self, ticket: u64) -> Result<MT5Order> {
-        self.bridge.get_order(ticket).await
+        let ttl = Duration::from_millis(self.settings.mt5_order_cache_ttl_ms);
+        if ttl.is_zero() {
+            return self.fetch_order(ticket).await;
+        }
+        
+        if let Some((fetched_at, order)) = self.order_cache.lock().unwrap().get(&ticket) {
+            if fetched_at.elapsed() < ttl {
+                return Ok(order.clone());
+            }
+        }
+        
+        let order = self.fetch_order(ticket).await?;
+        let mut cache = self.order_cache.lock().unwrap();
+        // Purge expired tickets so the cache only ever holds one TTL's worth
+        cache.retain(|_, (fetched_at, _)| fetched_at.elapsed() < ttl);
+        cache.insert(ticket, (Instant::now(), order.clone()));
+        Ok(order)
+    }
+    
+    /// Number of orders currently held in the `get_order` cache
+    pub fn cached_order_count(&self) -> usize {
+        self.order_cache.lock().unwrap().len()
+    }
+    
+    /// Fetch a live order, falling back to history when the bridge no longer lists it
+    async fn fetch_order(&self, ticket: u64) -> Result<MT5Order> {
+        match self.bridge.get_order(ticket).await {
+            Err(MT5Error::NotFound(resource)) => {
+                let now = chrono::Utc::now();
+                let history = match self
+                    .bridge
+                    .get_order_history((now - ORDER_HISTORY_LOOKBACK).timestamp(), now.timestamp(), None)
+                    .await
+                {
+                    // Without history the live lookup is all there is
+                    Err(MT5Error::Unsupported(_)) => return Err(MT5Error::NotFound(resource)),
+                    history => history?,
+                };
+                let mut order = history
+                    .into_iter()
+                    .find(|order| order.ticket == ticket)
+                    .ok_or(MT5Error::NotFound(resource))?;
+                // An order that left the live book without a reported state is taken as filled
+                if order.state == OrderState::Pending {
+                    order.state = OrderState::Filled;
+                }
+                Ok(order)
+            }
+            result => result,
+        }
+    }
+    
+    /// Drop a cached order after it may have changed
+    pub fn invalidate_order(&self, ticket: u64) {
+        self.order_cache.lock().unwrap().remove(&ticket);
+    }
+    
+    /// Get all open and pending orders
+    pub async fn get_orders(&self) -> Result<Vec<MT5Order>> {
+        self.bridge.get_orders().await
+    }
+    
+    /// Get historical orders in a time range, optionally for one symbol
+    pub async fn get_order_history(
+        &self,
+        from: i64,
+        to: i64,
+        symbol: Option<&str>,
+    ) -> Result<Vec<MT5Order>> {
+        self.bridge.get_order_history(from, to, symbol).await
     }
     
     /// Cancel order
     pub async fn cancel_order(&self, ticket: u64) -> Result<()> {
-        self.bridge.cancel_order(ticket).await
+        let result = self.bridge.cancel_order(ticket).await;
+        self.invalidate_order(ticket);
+        result
+    }
+    
+    /// Cancel every pending order, optionally only those for `symbol`
+    ///
+    /// `symbol` is normalised as it is for orders sent to the bridge. Each
+    /// cancel is attempted independently; the result for every ticket is
+    /// returned so one failure doesn't stop the rest from being cancelled.
+    pub async fn cancel_all_orders(
+        &self,
+        symbol: Option<&str>,
+    ) -> Result<Vec<(u64, Result<(), String>)>> {
+        let orders = self.bridge.get_orders().await?;
+        let symbol = symbol.map(|symbol| self.bridge.normalize_symbol(symbol));
+        
+        let mut results = Vec::new();
+        for order in orders.into_iter().filter(|o| {
+            o.order_type.is_pending() && symbol.as_ref().is_none_or(|symbol| &o.symbol == symbol)
+        }) {
+            let result = self.cancel_order(order.ticket).await.map_err(|e| e.to_string());
+            results.push((order.ticket, result));
+        }
+        
+        Ok(results)
+    }
+    
+    /// Modify stop loss / take profit of an order
+    pub async fn modify_order(
+        &self,
+        ticket: u64,
+        stop_loss: Option<f64>,
+        take_profit: Option<f64>,
+    ) -> Result<()> {
+        let result = self.bridge.modify_order(ticket, stop_loss, take_profit).await;
+        self.invalidate_order(ticket);
+        result
     }
     
     /// Get all positions
@@ -55,19 +224,157 @@ impl MT5Client {
         self.bridge.get_positions().await
     }
     
+    /// Get positions matching `filter`
+    ///
+    /// The bridge has no position filters, so all positions are fetched and
+    /// filtered here.
+    pub async fn get_positions_matching(&self, filter: &PositionFilter) -> Result<Vec<MT5Position>> {
+        let positions = self.bridge.get_positions().await?;
+        Ok(positions.into_iter().filter(|position| filter.matches(position)).collect())
+    }
+    
     /// Get position for symbol
     pub async fn get_position(&self, symbol: &str) -> Result<Option<MT5Position>> {
         self.bridge.get_position(symbol).await
     }
     
+    /// Net volume and entry price across every position in `symbol`
+    ///
+    /// Returns `None` when there are no positions in `symbol`; fully hedged
+    /// positions give a `NetPosition` with no side or average price.
+    pub async fn get_net_position(&self, symbol: &str) -> Result<Option<NetPosition>> {
+        let positions = self.bridge.get_positions().await?;
+        Ok(net_position(&self.bridge.normalize_symbol(symbol), &positions))
+    }
+    
+    /// Get position by ticket
+    pub async fn get_position_by_ticket(&self, ticket: u64) -> Result<Option<MT5Position>> {
+        self.bridge.get_position_by_ticket(ticket).await
+    }
+    
     /// Close position
     pub async fn close_position(&self, ticket: u64) -> Result<()> {
         self.bridge.close_position(ticket).await
     }
     
+    /// Close every position for `symbol`, returning their tickets
+    ///
+    /// Hedging accounts can hold several positions on one symbol; all of them
+    /// are closed. Fails with `NotFound` when there is no position for
+    /// `symbol`, and with the first failure if any close fails (the others
+    /// are still attempted).
+    pub async fn close_position_by_symbol(&self, symbol: &str) -> Result<Vec<u64>> {
+        let results = self.close_all_positions(Some(symbol)).await?;
+        if results.is_empty() {
+            return Err(MT5Error::NotFound(format!("Position for {}", symbol)));
+        }
+        if let Some((ticket, Err(e))) = results.iter().find(|(_, result)| result.is_err()) {
+            return Err(MT5Error::Bridge(format!(
+                "Failed to close position {} for {}: {}",
+                ticket, symbol, e
+            )));
+        }
+        Ok(results.into_iter().map(|(ticket, _)| ticket).collect())
+    }
+    
+    /// Close every open position, optionally only those for `symbol`
+    ///
+    /// `symbol` is normalised as it is for orders sent to the bridge. Each
+    /// close is attempted independently; the result for every ticket is
+    /// returned so one failure doesn't stop the rest from being flattened.
+    pub async fn close_all_positions(
+        &self,
+        symbol: Option<&str>,
+    ) -> Result<Vec<(u64, Result<(), String>)>> {
+        let positions = self.bridge.get_positions().await?;
+        let symbol = symbol.map(|symbol| self.bridge.normalize_symbol(symbol));
+        
+        let mut results = Vec::new();
+        for position in positions
+            .into_iter()
+            .filter(|p| symbol.as_ref().is_none_or(|symbol| &p.symbol == symbol))
+        {
+            let result = self
+                .bridge
+                .close_position(position.ticket)
+                .await
+                .map_err(|e| e.to_string());
+            results.push((position.ticket, result));
+        }
+        
+        Ok(results)
+    }
+    
     /// Get market data
+    ///
+    /// Concurrent calls for the same symbol share a single bridge request.
     pub async fn get_market_data(&self, symbol: &str) -> Result<MT5MarketData> {
-        self.bridge.get_market_data(symbol).await
+        let request = self
+            .market_in_flight
+            .lock()
+            .unwrap()
+            .entry(symbol.to_string())
+            .or_insert_with(|| {
+                let bridge = self.bridge.clone();
+                let symbol = symbol.to_string();
+                async move { bridge.get_market_data(&symbol).await }.boxed().shared()
+            })
+            .clone();
+        
+        let result = request.clone().await;
+        
+        // The first caller to finish retires the request so later calls fetch afresh
+        let mut in_flight = self.market_in_flight.lock().unwrap();
+        if in_flight.get(symbol).is_some_and(|current| current.ptr_eq(&request)) {
+            in_flight.remove(symbol);
+        }
+        result
+    }
+    
+    /// Get the most recent `count` candles for `symbol` on `timeframe`
+    pub async fn get_candles(&self, symbol: &str, timeframe: &str, count: u32) -> Result<Vec<MT5Candle>> {
+        self.bridge.get_candles(symbol, timeframe, count).await
+    }
+    
+    /// Subscribe to the bridge's tick WebSocket for `symbol`
+    pub async fn subscribe_ticks(&self, symbol: &str) -> Result<BoxStream<'static, Result<MT5MarketData>>> {
+        self.bridge.subscribe_ticks(symbol).await
+    }
+    
+    /// Get market data for several symbols concurrently
+    ///
+    /// At most `mt5_market_data_concurrency` requests are in flight at once,
+    /// and each joins any request already in flight for its symbol.
+    /// Each symbol's outcome is reported individually, in input order, so one
+    /// bad symbol doesn't fail the rest.
+    pub async fn get_market_data_multi(
+        &self,
+        symbols: &[&str],
+    ) -> Vec<(String, Result<MT5MarketData>)> {
+        let symbols: Vec<String> = symbols.iter().map(|symbol| symbol.to_string()).collect();
+        stream::iter(symbols)
+            .map(|symbol| async move {
+                let result = self.get_market_data(&symbol).await;
+                (symbol, result)
+            })
+            .buffered(self.settings.mt5_market_data_concurrency.max(1))
+            .collect()
+            .await
+    }
+    
+    /// Get symbol trading specification
+    pub async fn get_symbol_info(&self, symbol: &str) -> Result<MT5SymbolInfo> {
+        self.bridge.get_symbol_info(symbol).await
+    }
+    
+    /// Symbols the account can trade, optionally limited to a bridge `group`
+    pub async fn list_symbols(&self, group: Option<&str>) -> Result<Vec<String>> {
+        self.bridge.list_symbols(group).await
+    }
+    
+    /// Get account information
+    pub async fn get_account_info(&self) -> Result<MT5AccountInfo> {
+        self.bridge.get_account_info().await
     }
     
     /// Health check
@@ -0,0 +1,125 @@
+//! Typed errors returned by the MT5 bridge client
+//!
+//! Callers (and the HTTP layer) branch on the variant instead of parsing
+//! error strings: a missing order, a broker rejection and an unreachable
+//! bridge each need different handling.
+
+use crate::models::{
+    classify_rejection, retcode_description, OrderValidationError, RejectionClass, TRADE_RETCODE_PRICE_CHANGED,
+    TRADE_RETCODE_REQUOTE,
+};
+use crate::mt5::bridge::BridgeFeature;
+use thiserror::Error;
+
+/// Result type for MT5 bridge operations
+pub type Result<T, E = MT5Error> = std::result::Result<T, E>;
+
+/// Error from an MT5 bridge operation
+#[derive(Debug, Clone, Error)]
+pub enum MT5Error {
+    /// The client is not connected to the bridge (a reconnect may be running)
+    #[error("Not connected to MT5 bridge")]
+    NotConnected,
+
+    /// The bridge could not be reached or answered with a server error
+    #[error("MT5 bridge unavailable: {0}")]
+    BridgeUnavailable(String),
+
+    /// The bridge or broker refused the order
+    #[error("Order rejected{}: {message}", describe_retcode(*retcode))]
+    OrderRejected {
+        retcode: Option<u32>,
+        message: String,
+    },
+
+    /// Price moved beyond the order's deviation before it could be filled;
+    /// resubmitting at the new price may succeed
+    #[error("Order requoted{}: {message}", describe_retcode(Some(*retcode)))]
+    Requote {
+        retcode: u32,
+        message: String,
+    },
+
+    /// The order failed local validation and was never sent
+    #[error("Invalid order: {0}")]
+    InvalidOrder(#[from] OrderValidationError),
+
+    /// The requested order or position does not exist
+    #[error("{0} not found")]
+    NotFound(String),
+
+    /// The bridge does not know the requested symbol
+    #[error("Invalid symbol: {0}")]
+    InvalidSymbol(String),
+
+    /// The bridge did not answer within `mt5_timeout_ms`
+    #[error("MT5 bridge request timed out")]
+    Timeout,
+
+    /// The bridge response could not be parsed
+    #[error("Failed to parse bridge response: {0}")]
+    Deserialize(String),
+
+    /// The bridge reports that it lacks the feature the call needs
+    #[error("MT5 bridge does not support {0}")]
+    Unsupported(BridgeFeature),
+
+    /// Too many bridge requests were in flight for the call to get a slot
+    /// within `mt5_request_queue_timeout_ms`
+    #[error("Too many concurrent MT5 bridge requests")]
+    Overloaded,
+
+    /// The bridge reported a failure that fits no other variant
+    #[error("MT5 bridge error: {0}")]
+    Bridge(String),
+}
+
+impl From<reqwest::Error> for MT5Error {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_timeout() {
+            MT5Error::Timeout
+        } else if e.is_decode() {
+            MT5Error::Deserialize(e.to_string())
+        } else {
+            MT5Error::BridgeUnavailable(e.to_string())
+        }
+    }
+}
+
+impl MT5Error {
+    /// Error for an order the bridge refused with `retcode`
+    ///
+    /// Requotes and price changes are reported as `Requote`, everything
+    /// else as `OrderRejected`.
+    pub fn rejection(retcode: Option<u32>, message: String) -> Self {
+        match retcode {
+            Some(retcode @ (TRADE_RETCODE_REQUOTE | TRADE_RETCODE_PRICE_CHANGED)) => {
+                MT5Error::Requote { retcode, message }
+            }
+            retcode => MT5Error::OrderRejected { retcode, message },
+        }
+    }
+
+    /// Class of a rejection that carries a retcode
+    ///
+    /// `None` for other errors and for rejections made before the order
+    /// reached the broker, such as the spread check.
+    pub fn rejection_class(&self) -> Option<RejectionClass> {
+        match self {
+            MT5Error::OrderRejected { retcode, .. } => retcode.map(classify_rejection),
+            MT5Error::Requote { retcode, .. } => Some(classify_rejection(*retcode)),
+            _ => None,
+        }
+    }
+}
+
+/// Format a rejection's retcode and its description for display
+fn describe_retcode(retcode: Option<u32>) -> String {
+    match retcode {
+        Some(code) => match retcode_description(code) {
+            Some(description) => format!(" (retcode {}: {})", code, description),
+            None => format!(" (retcode {})", code),
+        },
+        None => String::new(),
+    }
+}
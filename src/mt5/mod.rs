@@ -1,10 +1,21 @@
 //! MetaTrader 5 integration module
 
+pub mod breaker;
 pub mod bridge;
 pub mod client;
+pub mod error;
 pub mod plugin;
+pub mod reconcile;
+pub mod symbols;
+pub mod redact;
+pub mod trailing;
+pub mod webhook;
 
-pub use bridge::MT5BridgeClient;
+pub use breaker::CircuitState;
+pub use bridge::{BridgeCapabilities, BridgeFeature, ConnectionEvent, ConnectionState, ConnectionStats, MT5BridgeClient, BRIDGE_VERSION_HEADER, CLIENT_VERSION_HEADER};
 pub use client::MT5Client;
+pub use error::MT5Error;
 pub use plugin::MT5Plugin;
+pub use reconcile::{PositionChange, Reconciler};
+pub use trailing::{TrailingStop, TrailingStops};
 
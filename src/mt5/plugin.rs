@@ -4,28 +4,123 @@
 //! When used as a library, it can be integrated into fks_execution.
 //! When used standalone, it provides HTTP API endpoints.
 
-use crate::mt5::MT5Client;
+use crate::models::{MT5OrderType, MT5Side, OrderState, RejectionClass};
+use crate::mt5::webhook::FillWebhook;
+use crate::mt5::{MT5Client, MT5Error};
 use crate::config::Settings;
+use crate::api::health::HealthResponse;
+use crate::shutdown::Shutdown;
 use async_trait::async_trait;
+use serde::Serialize;
 use std::error::Error;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::RwLock;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 // Types matching fks_execution plugin interface
 // These should match the ExecutionPlugin trait from fks_execution
 
 /// Execution result matching fks_execution interface
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ExecutionResult {
     pub success: bool,
     pub order_id: Option<String>,
     pub filled_quantity: f64,
     pub average_price: f64,
     pub error: Option<String>,
+    /// Kind of failure, for callers that branch on it; `error` has the details
+    pub error_code: Option<ExecutionErrorCode>,
+    /// What the broker's retcode means for a rejected order
+    pub rejection_class: Option<RejectionClass>,
+    /// Set when `mt5_dry_run` filled the order synthetically; nothing was sent
+    pub dry_run: bool,
     pub timestamp: i64,
 }
 
+impl ExecutionResult {
+    /// Result for an order that failed to execute
+    fn failed(error: String, error_code: ExecutionErrorCode, rejection_class: Option<RejectionClass>) -> Self {
+        Self {
+            success: false,
+            order_id: None,
+            filled_quantity: 0.0,
+            average_price: 0.0,
+            error: Some(error),
+            error_code: Some(error_code),
+            rejection_class,
+            dry_run: false,
+            timestamp: chrono::Utc::now().timestamp_millis(),
+        }
+    }
+}
+
+/// Why an order failed to execute
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecutionErrorCode {
+    /// The bridge or broker refused the order
+    Rejected,
+    /// Price moved beyond the allowed deviation; the order may be resubmitted
+    Requote,
+    /// The bridge could not be reached
+    NotConnected,
+    /// The bridge did not answer in time; the order may still have been placed
+    Timeout,
+    /// The order failed validation before it was sent
+    InvalidOrder,
+    Unknown,
+}
+
+impl From<&MT5Error> for ExecutionErrorCode {
+    fn from(error: &MT5Error) -> Self {
+        match error {
+            MT5Error::OrderRejected { .. } => ExecutionErrorCode::Rejected,
+            MT5Error::Requote { .. } => ExecutionErrorCode::Requote,
+            MT5Error::NotConnected | MT5Error::BridgeUnavailable(_) | MT5Error::Overloaded => {
+                ExecutionErrorCode::NotConnected
+            }
+            MT5Error::Timeout => ExecutionErrorCode::Timeout,
+            MT5Error::InvalidOrder(_) | MT5Error::InvalidSymbol(_) => ExecutionErrorCode::InvalidOrder,
+            MT5Error::NotFound(_)
+            | MT5Error::Unsupported(_)
+            | MT5Error::Deserialize(_)
+            | MT5Error::Bridge(_) => {
+                ExecutionErrorCode::Unknown
+            }
+        }
+    }
+}
+
+/// Order comment used when `mt5_order_comment_template` is not set
+pub const DEFAULT_COMMENT_TEMPLATE: &str = "FKS order (confidence: {confidence})";
+
+/// Longest comment MT5 keeps on an order
+pub const MAX_COMMENT_LEN: usize = 31;
+
+/// Appended to the comment of orders placed in dry-run mode
+pub const DRY_RUN_COMMENT_SUFFIX: &str = " [dry-run]";
+
+/// Render an order comment from `template`
+///
+/// `{confidence}`, `{symbol}` and `{side}` are replaced with the order's
+/// values, and `DRY_RUN_COMMENT_SUFFIX` is appended for a dry run. Comments
+/// longer than `MAX_COMMENT_LEN` characters, suffix included, are truncated.
+pub fn order_comment(template: &str, order: &Order, dry_run: bool) -> String {
+    let mut comment = template
+        .replace("{confidence}", &order.confidence.to_string())
+        .replace("{symbol}", &order.symbol)
+        .replace("{side}", &format!("{:?}", order.side));
+    if dry_run {
+        comment.push_str(DRY_RUN_COMMENT_SUFFIX);
+    }
+    if comment.chars().count() > MAX_COMMENT_LEN {
+        warn!(comment = %comment, max_len = MAX_COMMENT_LEN, "Order comment too long, truncating");
+        return comment.chars().take(MAX_COMMENT_LEN).collect();
+    }
+    comment
+}
+
 /// Order side matching fks_execution interface
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum OrderSide {
@@ -52,6 +147,8 @@ pub struct Order {
     pub order_type: OrderType,
     pub quantity: f64,
     pub price: Option<f64>,
+    /// Trigger price for stop-limit orders (`price` is the limit placed once hit)
+    pub stop_price: Option<f64>,
     pub stop_loss: Option<f64>,
     pub take_profit: Option<f64>,
     pub confidence: f64,
@@ -75,6 +172,8 @@ pub trait ExecutionPlugin: Send + Sync {
     async fn init(&mut self, config: serde_json::Value) -> Result<(), Box<dyn Error + Send + Sync>>;
     async fn execute_order(&self, order: Order) -> Result<ExecutionResult, Box<dyn Error + Send + Sync>>;
     async fn fetch_data(&self, symbol: &str) -> Result<MarketData, Box<dyn Error + Send + Sync>>;
+    async fn cancel_order(&self, order_id: &str) -> Result<(), Box<dyn Error + Send + Sync>>;
+    async fn close_position(&self, symbol: &str) -> Result<(), Box<dyn Error + Send + Sync>>;
     fn name(&self) -> &str;
     async fn health_check(&self) -> Result<bool, Box<dyn Error + Send + Sync>>;
 }
@@ -86,6 +185,9 @@ pub struct MT5Plugin {
     name: String,
     client: Arc<RwLock<Option<Arc<MT5Client>>>>,
     settings: Arc<RwLock<Option<Arc<Settings>>>>,
+    fill_webhook: Arc<RwLock<Option<FillWebhook>>>,
+    /// Tracks fill notifications still being delivered
+    shutdown: Shutdown,
 }
 
 impl MT5Plugin {
@@ -94,8 +196,101 @@ impl MT5Plugin {
             name: name.to_string(),
             client: Arc::new(RwLock::new(None)),
             settings: Arc::new(RwLock::new(None)),
+            fill_webhook: Arc::new(RwLock::new(None)),
+            shutdown: Shutdown::new(),
         }
     }
+    
+    /// Wait up to `mt5_shutdown_grace_ms` for fill notifications still being
+    /// delivered, so they aren't lost when the host exits
+    ///
+    /// Returns whether every delivery finished in time.
+    pub async fn shutdown(&self) -> bool {
+        let grace = self
+            .settings
+            .read()
+            .await
+            .as_ref()
+            .map_or(0, |settings| settings.mt5_shutdown_grace_ms);
+        self.shutdown.drain(Duration::from_millis(grace)).await
+    }
+    
+    /// Service health reporting plugin mode and this plugin's name
+    pub fn health(&self) -> HealthResponse {
+        HealthResponse::plugin(&self.name)
+    }
+    
+    /// Attach a TakeProfit / StopLoss order as a level on the open position
+    ///
+    /// The order's side must be the one that closes the position (a Sell
+    /// take profit for a long, a Buy stop loss for a short, ...). The level is
+    /// taken from `price`, falling back to the matching `take_profit` /
+    /// `stop_loss` field.
+    async fn attach_exit_level(
+        &self,
+        client: &MT5Client,
+        order: &Order,
+    ) -> Result<ExecutionResult, Box<dyn Error + Send + Sync>> {
+        let level = match order.order_type {
+            OrderType::TakeProfit => order.price.or(order.take_profit),
+            _ => order.price.or(order.stop_loss),
+        }
+        .ok_or_else(|| {
+            format!(
+                "{:?} {:?} order requires a price level",
+                order.side, order.order_type
+            )
+        })?;
+        
+        let position = client
+            .get_position(&order.symbol)
+            .await?
+            .ok_or_else(|| {
+                format!(
+                    "Cannot attach {:?} {:?}: no open position for {}",
+                    order.side, order.order_type, order.symbol
+                )
+            })?;
+        
+        let order_side = match order.side {
+            OrderSide::Buy => MT5Side::Buy,
+            OrderSide::Sell => MT5Side::Sell,
+        };
+        if order_side != position.position_type.opposite() {
+            return Err(format!(
+                "Cannot attach {:?} {:?} to {} position {} for {}",
+                order.side, order.order_type, position.position_type, position.ticket, order.symbol
+            )
+            .into());
+        }
+        
+        let (stop_loss, take_profit) = match order.order_type {
+            OrderType::TakeProfit => (None, Some(level)),
+            _ => (Some(level), None),
+        };
+        
+        info!(
+            plugin = %self.name,
+            symbol = %order.symbol,
+            ticket = position.ticket,
+            order_type = ?order.order_type,
+            level,
+            "Attaching exit level to MT5 position"
+        );
+        client.modify_order(position.ticket, stop_loss, take_profit).await?;
+        
+        Ok(ExecutionResult {
+            success: true,
+            order_id: Some(position.ticket.to_string()),
+            filled_quantity: 0.0,
+            average_price: 0.0,
+            error: None,
+            error_code: None,
+            rejection_class: None,
+            dry_run: false,
+            timestamp: chrono::Utc::now().timestamp_millis(),
+        })
+    }
 }
 
 #[async_trait]
@@ -104,18 +299,24 @@ impl ExecutionPlugin for MT5Plugin {
         info!(plugin = %self.name, "Initializing MT5 plugin");
         
         // Parse configuration
-        let settings = Arc::new(Settings::from_env()
-            .map_err(|e| format!("Failed to load settings: {}", e))?);
+        let mut settings = Settings::from_env()
+            .map_err(|e| format!("Failed to load settings: {}", e))?;
         
         // Override with config JSON if provided
-        if let Some(terminal_path) = config.get("terminal_path").and_then(|v| v.as_str()) {
-            // Update settings
-        }
+        settings.apply_overrides(&config);
+        settings
+            .validate()
+            .map_err(|e| format!("Invalid configuration: {:#}", e))?;
+        let settings = Arc::new(settings);
         
         // Initialize MT5 client
         let client = Arc::new(MT5Client::new(settings.clone()).await
             .map_err(|e| format!("Failed to initialize MT5 client: {}", e))?);
         
+        *self.fill_webhook.write().await = settings
+            .mt5_fill_webhook_url
+            .as_deref()
+            .map(|url| FillWebhook::new(url, self.shutdown.clone()));
         *self.settings.write().await = Some(settings);
         *self.client.write().await = Some(client);
         
@@ -130,6 +331,9 @@ impl ExecutionPlugin for MT5Plugin {
         let client = self.client.read().await;
         let client = client.as_ref()
             .ok_or("Plugin not initialized")?;
+        let settings = self.settings.read().await;
+        let settings = settings.as_ref()
+            .ok_or("Plugin not initialized")?;
         
         // Convert FKS Order to MT5 Order format
         // Clone values needed for logging before moving order
@@ -137,28 +341,76 @@ impl ExecutionPlugin for MT5Plugin {
         let side = order.side;
         let quantity = order.quantity;
         
-        let mt5_order_type = match (order.side, order.order_type) {
-            (OrderSide::Buy, OrderType::Market) => "OP_BUY".to_string(),
-            (OrderSide::Sell, OrderType::Market) => "OP_SELL".to_string(),
-            (OrderSide::Buy, OrderType::Limit) => "OP_BUYLIMIT".to_string(),
-            (OrderSide::Sell, OrderType::Limit) => "OP_SELLLIMIT".to_string(),
-            (OrderSide::Buy, OrderType::Stop) => "OP_BUYSTOP".to_string(),
-            (OrderSide::Sell, OrderType::Stop) => "OP_SELLSTOP".to_string(),
-            _ => return Err("Unsupported order type".into()),
+        // TakeProfit / StopLoss orders amend the open position rather than
+        // creating a standalone MT5 order
+        if matches!(order.order_type, OrderType::TakeProfit | OrderType::StopLoss) {
+            return self.attach_exit_level(client, &order).await;
+        }
+        
+        let (mt5_order_type, price, stop_limit) = match (order.side, order.order_type) {
+            (OrderSide::Buy, OrderType::Market) => (MT5OrderType::Buy, order.price, None),
+            (OrderSide::Sell, OrderType::Market) => (MT5OrderType::Sell, order.price, None),
+            (OrderSide::Buy, OrderType::Limit) => (MT5OrderType::BuyLimit, order.price, None),
+            (OrderSide::Sell, OrderType::Limit) => (MT5OrderType::SellLimit, order.price, None),
+            (OrderSide::Buy, OrderType::Stop) => (MT5OrderType::BuyStop, order.price, None),
+            (OrderSide::Sell, OrderType::Stop) => (MT5OrderType::SellStop, order.price, None),
+            (side, OrderType::StopLimit) => {
+                let (Some(stop_price), Some(limit_price)) = (order.stop_price, order.price) else {
+                    return Err(format!(
+                        "{:?} {:?} order requires both stop_price and price",
+                        side, order.order_type
+                    )
+                    .into());
+                };
+                let mt5_order_type = match side {
+                    OrderSide::Buy => MT5OrderType::BuyStopLimit,
+                    OrderSide::Sell => MT5OrderType::SellStopLimit,
+                };
+                (mt5_order_type, Some(stop_price), Some(limit_price))
+            }
+            (side, order_type) => {
+                return Err(format!("Unsupported order: {:?} {:?}", side, order_type).into());
+            }
         };
         
+        let volume = match settings.order_volume(order.quantity) {
+            Ok(volume) => volume,
+            Err(e) => {
+                warn!(plugin = %self.name, symbol = %symbol, error = %e, "Order failed validation");
+                return Ok(ExecutionResult::failed(e, ExecutionErrorCode::InvalidOrder, None));
+            }
+        };
+        let comment = order_comment(
+            settings
+                .mt5_order_comment_template
+                .as_deref()
+                .unwrap_or(DEFAULT_COMMENT_TEMPLATE),
+            &order,
+            settings.mt5_dry_run,
+        );
+        
         let mt5_order = crate::models::MT5Order {
             ticket: 0, // Will be assigned by MT5
             symbol: order.symbol,
             order_type: mt5_order_type,
-            volume: order.quantity,
-            price: order.price.unwrap_or(0.0),
+            volume,
+            price: price.unwrap_or(0.0),
+            stop_limit,
             stop_loss: order.stop_loss,
             take_profit: order.take_profit,
-            comment: Some(format!("FKS order (confidence: {})", order.confidence)),
-            magic: 123456, // FKS magic number
+            comment: Some(comment),
+            magic: settings.mt5_magic_number,
             expiration: None,
+            client_order_id: None,
+            deviation: settings.mt5_default_deviation,
+            skip_spread_check: false,
+            state: OrderState::Pending,
         };
+        if let Err(e) = mt5_order.validate() {
+            let e = MT5Error::from(e);
+            warn!(plugin = %self.name, symbol = %symbol, error = %e, "Order failed validation");
+            return Ok(ExecutionResult::failed(e.to_string(), ExecutionErrorCode::from(&e), e.rejection_class()));
+        }
         
         info!(
             plugin = %self.name,
@@ -169,26 +421,30 @@ impl ExecutionPlugin for MT5Plugin {
         );
         
         match client.execute_order(&mt5_order).await {
-            Ok(ticket) => {
-                Ok(ExecutionResult {
+            Ok(result) => {
+                // A pending order reports nothing filled yet
+                let execution = ExecutionResult {
                     success: true,
-                    order_id: Some(ticket.to_string()),
-                    filled_quantity: order.quantity,
-                    average_price: order.price.unwrap_or(0.0),
+                    order_id: Some(result.ticket.to_string()),
+                    filled_quantity: result.fill_price.and(result.fill_volume).unwrap_or(0.0),
+                    average_price: result.fill_price.unwrap_or(0.0),
                     error: None,
+                    error_code: None,
+                    rejection_class: None,
+                    dry_run: settings.mt5_dry_run,
                     timestamp: chrono::Utc::now().timestamp_millis(),
-                })
+                };
+                // Dry-run fills are synthetic and must not reach downstream books
+                if execution.filled_quantity > 0.0 && !execution.dry_run {
+                    if let Some(webhook) = self.fill_webhook.read().await.as_ref() {
+                        webhook.notify(&execution);
+                    }
+                }
+                Ok(execution)
             }
             Err(e) => {
                 error!(plugin = %self.name, error = %e, "Order execution failed");
-                Ok(ExecutionResult {
-                    success: false,
-                    order_id: None,
-                    filled_quantity: 0.0,
-                    average_price: 0.0,
-                    error: Some(e.to_string()),
-                    timestamp: chrono::Utc::now().timestamp_millis(),
-                })
+                Ok(ExecutionResult::failed(e.to_string(), ExecutionErrorCode::from(&e), e.rejection_class()))
             }
         }
     }
@@ -210,10 +466,35 @@ impl ExecutionPlugin for MT5Plugin {
             extra: serde_json::json!({
                 "spread": mt5_data.spread,
                 "digits": mt5_data.digits,
+                "stale": mt5_data.stale,
             }),
         })
     }
     
+    async fn cancel_order(&self, order_id: &str) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let client = self.client.read().await;
+        let client = client.as_ref()
+            .ok_or("Plugin not initialized")?;
+        
+        let ticket: u64 = order_id
+            .parse()
+            .map_err(|_| format!("Invalid MT5 order id: {:?}", order_id))?;
+        
+        info!(plugin = %self.name, ticket, "Cancelling order via MT5");
+        client.cancel_order(ticket).await?;
+        Ok(())
+    }
+    
+    async fn close_position(&self, symbol: &str) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let client = self.client.read().await;
+        let client = client.as_ref()
+            .ok_or("Plugin not initialized")?;
+        
+        let tickets = client.close_position_by_symbol(symbol).await?;
+        info!(plugin = %self.name, symbol = %symbol, ?tickets, "Closed position via MT5");
+        Ok(())
+    }
+    
     fn name(&self) -> &str {
         &self.name
     }
@@ -0,0 +1,146 @@
+//! Periodic position reconciliation
+//!
+//! A background task polls the bridge for open positions and compares them
+//! with the previous snapshot. Opened, closed and resized positions are
+//! logged as structured events so downstream systems can resync after a
+//! crash or a fill they didn't see.
+
+use crate::models::MT5Position;
+use crate::mt5::client::MT5Client;
+use crate::shutdown::Shutdown;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Difference between two position snapshots
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum PositionChange {
+    /// A position not in the previous snapshot
+    Opened(MT5Position),
+    /// A position that is no longer open
+    Closed(MT5Position),
+    /// A position whose volume changed, e.g. after a partial close
+    VolumeChanged {
+        ticket: u64,
+        symbol: String,
+        previous: f64,
+        current: f64,
+    },
+}
+
+/// Changes between `previous` and `current`, ordered by ticket
+pub fn diff_positions(previous: &[MT5Position], current: &[MT5Position]) -> Vec<PositionChange> {
+    let previous: BTreeMap<u64, &MT5Position> = previous.iter().map(|p| (p.ticket, p)).collect();
+    let current: BTreeMap<u64, &MT5Position> = current.iter().map(|p| (p.ticket, p)).collect();
+    let mut tickets: Vec<u64> = previous.keys().chain(current.keys()).copied().collect();
+    tickets.sort_unstable();
+    tickets.dedup();
+
+    tickets
+        .into_iter()
+        .filter_map(|ticket| match (previous.get(&ticket), current.get(&ticket)) {
+            (None, Some(position)) => Some(PositionChange::Opened((*position).clone())),
+            (Some(position), None) => Some(PositionChange::Closed((*position).clone())),
+            (Some(before), Some(after)) if before.volume != after.volume => {
+                Some(PositionChange::VolumeChanged {
+                    ticket,
+                    symbol: after.symbol.clone(),
+                    previous: before.volume,
+                    current: after.volume,
+                })
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Last reconciled position snapshot
+#[derive(Debug, Default)]
+pub struct Reconciler {
+    snapshot: Mutex<Option<Vec<MT5Position>>>,
+}
+
+impl Reconciler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Positions seen by the last reconciliation, if one has run
+    pub fn snapshot(&self) -> Option<Vec<MT5Position>> {
+        self.snapshot.lock().unwrap().clone()
+    }
+
+    /// Replace the snapshot with `current`, returning what changed
+    ///
+    /// The first snapshot is the baseline and reports no changes.
+    pub fn update(&self, current: Vec<MT5Position>) -> Vec<PositionChange> {
+        let mut snapshot = self.snapshot.lock().unwrap();
+        let changes = match snapshot.as_deref() {
+            Some(previous) => diff_positions(previous, &current),
+            None => Vec::new(),
+        };
+        *snapshot = Some(current);
+        changes
+    }
+
+    /// Reconcile every `interval` until shutdown
+    pub fn start(self: &Arc<Self>, client: Arc<MT5Client>, shutdown: &Shutdown, interval: Duration) {
+        let reconciler = Arc::clone(self);
+        let token = shutdown.token();
+
+        shutdown.spawn(async move {
+            info!(interval_ms = interval.as_millis() as u64, "Position reconciliation started");
+            loop {
+                match client.get_positions().await {
+                    Ok(positions) => {
+                        for change in reconciler.update(positions) {
+                            log_change(&change, &client);
+                        }
+                    }
+                    Err(e) => warn!(error = %e, "Position reconciliation could not fetch positions"),
+                }
+
+                tokio::select! {
+                    _ = token.cancelled() => break,
+                    _ = tokio::time::sleep(interval) => {}
+                }
+            }
+        });
+    }
+}
+
+fn log_change(change: &PositionChange, client: &MT5Client) {
+    match change {
+        PositionChange::Opened(position) => info!(
+            event = "position_opened",
+            ticket = position.ticket,
+            symbol = %position.symbol,
+            strategy = %client.magic_label(position.magic),
+            side = %position.position_type,
+            volume = position.volume,
+            price_open = position.price_open,
+            "Position opened"
+        ),
+        PositionChange::Closed(position) => info!(
+            event = "position_closed",
+            ticket = position.ticket,
+            symbol = %position.symbol,
+            strategy = %client.magic_label(position.magic),
+            side = %position.position_type,
+            volume = position.volume,
+            profit = position.profit,
+            "Position closed"
+        ),
+        PositionChange::VolumeChanged { ticket, symbol, previous, current } => info!(
+            event = "position_volume_changed",
+            ticket,
+            symbol = %symbol,
+            previous,
+            current,
+            "Position volume changed"
+        ),
+    }
+}
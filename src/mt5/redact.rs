@@ -0,0 +1,69 @@
+//! Redaction of credentials in logged bridge traffic
+//!
+//! Used when `MT5_LOG_BRIDGE_BODIES` is enabled so payloads can be traced
+//! without leaking passwords, tokens or account identifiers.
+
+use reqwest::header::HeaderMap;
+
+/// Replacement for redacted values
+pub const REDACTED: &str = "[REDACTED]";
+
+/// JSON keys that identify an account
+const ACCOUNT_KEYS: [&str; 3] = ["account", "account_number", "login"];
+
+/// Whether a JSON key or header name holds a secret or account identifier
+fn is_sensitive(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    name.contains("password")
+        || name.contains("token")
+        || name.contains("secret")
+        || name.contains("authorization")
+        || ACCOUNT_KEYS.contains(&name.as_str())
+}
+
+/// Mask sensitive values in a JSON document, at any depth
+pub fn redact_json(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if is_sensitive(key) {
+                    *value = REDACTED.into();
+                } else {
+                    redact_json(value);
+                }
+            }
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(redact_json),
+        _ => {}
+    }
+}
+
+/// Render a request or response body for logging with secrets masked
+///
+/// Bodies that aren't JSON can't be inspected, so only their size is shown.
+pub fn redact_body(body: &[u8]) -> String {
+    match serde_json::from_slice::<serde_json::Value>(body) {
+        Ok(mut value) => {
+            redact_json(&mut value);
+            value.to_string()
+        }
+        Err(_) if body.is_empty() => String::new(),
+        Err(_) => format!("<{} bytes, not JSON>", body.len()),
+    }
+}
+
+/// Render headers for logging with token-bearing values masked
+pub fn redact_headers(headers: &HeaderMap) -> String {
+    let rendered: Vec<String> = headers
+        .iter()
+        .map(|(name, value)| {
+            let value = if is_sensitive(name.as_str()) {
+                REDACTED
+            } else {
+                value.to_str().unwrap_or("<binary>")
+            };
+            format!("{}: {}", name, value)
+        })
+        .collect();
+    rendered.join(", ")
+}
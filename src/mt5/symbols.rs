@@ -0,0 +1,25 @@
+//! Broker symbol prefixes
+//!
+//! Some brokers name symbols with an account-type prefix (`m.EURUSD`).
+//! Callers always use the bare name; the prefix is added on the way to the
+//! bridge and removed from everything it returns. Matching is
+//! case-sensitive, as MT5 symbol names are.
+
+/// Broker-facing name for `symbol`
+///
+/// A symbol that already carries the prefix is returned unchanged, so
+/// applying the prefix twice is harmless.
+pub fn apply_symbol_prefix(symbol: &str, prefix: &str) -> String {
+    if symbol.starts_with(prefix) {
+        symbol.to_string()
+    } else {
+        format!("{}{}", prefix, symbol)
+    }
+}
+
+/// Caller-facing name for a broker `symbol`
+///
+/// Symbols without the prefix are returned unchanged.
+pub fn strip_symbol_prefix(symbol: &str, prefix: &str) -> String {
+    symbol.strip_prefix(prefix).unwrap_or(symbol).to_string()
+}
@@ -0,0 +1,169 @@
+//! Server-side trailing stops
+//!
+//! Each trailing stop is a background task that polls the position and its
+//! market price, and ratchets the stop loss toward price as it moves in the
+//! position's favour. Stops never move back. The task ends when the position
+//! closes, the trailing stop is cancelled or the service shuts down.
+
+use crate::models::{MT5MarketData, MT5Position, MT5Side, MT5SymbolInfo};
+use crate::mt5::client::MT5Client;
+use crate::shutdown::Shutdown;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::AbortHandle;
+use tracing::{info, warn};
+use utoipa::ToSchema;
+
+/// Trailing stop parameters, in symbol points
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct TrailingStop {
+    /// Distance kept between price and the stop loss
+    pub distance_points: f64,
+    /// Minimum improvement before the stop is moved, to avoid modifying on every tick
+    pub step_points: f64,
+}
+
+impl TrailingStop {
+    /// Stop loss to move to for the current price, if it should move
+    ///
+    /// Longs trail the bid, shorts the ask. The level is rounded to the
+    /// symbol's `digits`, as brokers reject unrounded stops. Returns `None`
+    /// unless it improves on the current stop by at least `step_points`, and
+    /// never for a level equal to the current stop (MT5 rejects a modify that
+    /// changes nothing), even with `step_points` of 0.
+    pub fn next_stop(&self, position: &MT5Position, market: &MT5MarketData, symbol: &MT5SymbolInfo) -> Option<f64> {
+        let distance = self.distance_points * symbol.point;
+        let step = self.step_points * symbol.point;
+        match position.position_type {
+            MT5Side::Buy => {
+                let candidate = symbol.round_price(market.bid - distance);
+                match position.stop_loss {
+                    Some(current) if candidate <= current || candidate < current + step => None,
+                    _ => Some(candidate),
+                }
+            }
+            MT5Side::Sell => {
+                let candidate = symbol.round_price(market.ask + distance);
+                match position.stop_loss {
+                    Some(current) if candidate >= current || candidate > current - step => None,
+                    _ => Some(candidate),
+                }
+            }
+        }
+    }
+}
+
+/// Registry of running trailing stop tasks, keyed by position ticket
+#[derive(Debug)]
+pub struct TrailingStops {
+    shutdown: Shutdown,
+    next_id: AtomicU64,
+    tasks: Mutex<HashMap<u64, (u64, AbortHandle)>>,
+}
+
+impl TrailingStops {
+    /// Create a registry whose tasks stop when `shutdown` is triggered
+    pub fn new(shutdown: Shutdown) -> Self {
+        Self {
+            shutdown,
+            next_id: AtomicU64::new(0),
+            tasks: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Start trailing `position`'s stop, replacing any trailing stop it already has
+    ///
+    /// `symbol` is the position's symbol specification; the position and
+    /// price are polled every `interval`.
+    pub fn start(
+        self: &Arc<Self>,
+        client: Arc<MT5Client>,
+        position: &MT5Position,
+        trailing: TrailingStop,
+        symbol_info: MT5SymbolInfo,
+        interval: Duration,
+    ) {
+        let ticket = position.ticket;
+        let symbol = position.symbol.clone();
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let registry = Arc::clone(self);
+        let token = self.shutdown.token();
+
+        let task = self.shutdown.spawn(async move {
+            info!(ticket, symbol = %symbol, ?trailing, "Trailing stop started");
+            loop {
+                tokio::select! {
+                    _ = token.cancelled() => {
+                        info!(ticket, "Shutting down, trailing stop finished");
+                        break;
+                    }
+                    _ = tokio::time::sleep(interval) => {}
+                }
+
+                let position = match client.get_position_by_ticket(ticket).await {
+                    Ok(position) => position,
+                    Err(e) => {
+                        warn!(ticket, error = %e, "Trailing stop could not fetch the position");
+                        continue;
+                    }
+                };
+                let Some(position) = position else {
+                    info!(ticket, "Position closed, trailing stop finished");
+                    break;
+                };
+
+                let market = match client.get_market_data(&symbol).await {
+                    Ok(market) => market,
+                    Err(e) => {
+                        warn!(ticket, error = %e, "Trailing stop could not fetch market data");
+                        continue;
+                    }
+                };
+
+                if let Some(stop_loss) = trailing.next_stop(&position, &market, &symbol_info) {
+                    match client.modify_order(ticket, Some(stop_loss), None).await {
+                        Ok(()) => info!(ticket, stop_loss, "Trailing stop moved"),
+                        Err(e) => warn!(ticket, error = %e, "Failed to move trailing stop"),
+                    }
+                }
+            }
+            registry.remove(ticket, id);
+        });
+
+        let previous = self
+            .tasks
+            .lock()
+            .unwrap()
+            .insert(ticket, (id, task.abort_handle()));
+        if let Some((_, previous)) = previous {
+            previous.abort();
+        }
+    }
+
+    /// Stop trailing a position; returns whether it had a trailing stop
+    pub fn cancel(&self, ticket: u64) -> bool {
+        match self.tasks.lock().unwrap().remove(&ticket) {
+            Some((_, task)) => {
+                task.abort();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Whether a position currently has a trailing stop
+    pub fn is_active(&self, ticket: u64) -> bool {
+        self.tasks.lock().unwrap().contains_key(&ticket)
+    }
+
+    /// Drop a finished task's entry, unless it has since been replaced
+    fn remove(&self, ticket: u64, id: u64) {
+        let mut tasks = self.tasks.lock().unwrap();
+        if tasks.get(&ticket).is_some_and(|(current, _)| *current == id) {
+            tasks.remove(&ticket);
+        }
+    }
+}
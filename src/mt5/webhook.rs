@@ -0,0 +1,86 @@
+//! Fill notifications
+//!
+//! When `mt5_fill_webhook_url` is set, each filled plugin order is POSTed to
+//! it as `ExecutionResult` JSON; dry-run fills are never sent. Delivery runs
+//! in a background task with a bounded number of attempts, so a slow or
+//! failing receiver never delays the order response. The task is tracked by
+//! `Shutdown`, so draining it finishes deliveries still in flight. Logs name
+//! only the receiver's origin, as the URL's path is often its secret.
+
+use crate::config::loggable_url;
+use crate::mt5::plugin::ExecutionResult;
+use crate::shutdown::Shutdown;
+use reqwest::Client;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Attempts made to deliver one notification
+const MAX_ATTEMPTS: u32 = 3;
+
+/// Timeout of each delivery attempt
+const ATTEMPT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Delay before the first retry, doubled for each one after it
+const RETRY_DELAY: Duration = Duration::from_millis(200);
+
+/// Posts execution results to a configured URL
+#[derive(Debug, Clone)]
+pub struct FillWebhook {
+    http_client: Client,
+    url: String,
+    /// `url` cut to its origin, for logs
+    origin: String,
+    shutdown: Shutdown,
+}
+
+impl FillWebhook {
+    /// Webhook posting to `url`, with deliveries tracked by `shutdown`
+    pub fn new(url: &str, shutdown: Shutdown) -> Self {
+        Self {
+            http_client: Client::new(),
+            url: url.to_string(),
+            origin: loggable_url(url, true),
+            shutdown,
+        }
+    }
+
+    /// Deliver `result` in the background, retrying failed attempts
+    ///
+    /// Delivery carries on through shutdown rather than dropping the fill;
+    /// `Shutdown::drain`'s grace period bounds how long it is waited for.
+    pub fn notify(&self, result: &ExecutionResult) {
+        let webhook = self.clone();
+        let result = result.clone();
+        self.shutdown.spawn(async move { webhook.deliver(&result).await });
+    }
+
+    /// Deliver `result`, returning whether the receiver accepted it
+    pub async fn deliver(&self, result: &ExecutionResult) -> bool {
+        let mut delay = RETRY_DELAY;
+        for attempt in 1..=MAX_ATTEMPTS {
+            let response = self
+                .http_client
+                .post(&self.url)
+                .timeout(ATTEMPT_TIMEOUT)
+                .json(result)
+                .send()
+                .await;
+            match response {
+                Ok(response) if response.status().is_success() => {
+                    info!(url = %self.origin, order_id = ?result.order_id, "Fill webhook delivered");
+                    return true;
+                }
+                Ok(response) => {
+                    warn!(url = %self.origin, attempt, status = %response.status(), "Fill webhook rejected")
+                }
+                Err(e) => warn!(url = %self.origin, attempt, error = %e, "Fill webhook failed"),
+            }
+            if attempt < MAX_ATTEMPTS {
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+            }
+        }
+        warn!(url = %self.origin, order_id = ?result.order_id, "Giving up on fill webhook");
+        false
+    }
+}
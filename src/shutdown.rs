@@ -0,0 +1,95 @@
+//! Shutdown coordination for background tasks
+//!
+//! Background work (trailing stops, reconciliation, market data streams) is
+//! spawned through `Shutdown` so it observes a shared cancellation token, and
+//! `main` can wait a bounded time for it to wind down after the server stops.
+
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+#[derive(Debug, Default)]
+struct Tracker {
+    running: AtomicUsize,
+    finished: Notify,
+}
+
+/// Decrements the running count when a tracked task ends, even if aborted
+struct TaskGuard(Arc<Tracker>);
+
+impl Drop for TaskGuard {
+    fn drop(&mut self) {
+        self.0.running.fetch_sub(1, Ordering::SeqCst);
+        self.0.finished.notify_waiters();
+    }
+}
+
+/// Cancellation token plus a count of the tasks that should observe it
+#[derive(Debug, Clone, Default)]
+pub struct Shutdown {
+    token: CancellationToken,
+    tracker: Arc<Tracker>,
+}
+
+impl Shutdown {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Token cancelled when shutdown begins
+    pub fn token(&self) -> CancellationToken {
+        self.token.clone()
+    }
+
+    /// Whether shutdown has begun
+    pub fn is_triggered(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    /// Begin shutdown, cancelling the token
+    pub fn trigger(&self) {
+        self.token.cancel();
+    }
+
+    /// Spawn a task that `drain` waits for
+    ///
+    /// The task should stop promptly once `token()` is cancelled.
+    pub fn spawn<F>(&self, task: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        self.tracker.running.fetch_add(1, Ordering::SeqCst);
+        let guard = TaskGuard(self.tracker.clone());
+        tokio::spawn(async move {
+            let _guard = guard;
+            task.await
+        })
+    }
+
+    /// Number of tracked tasks still running
+    pub fn running(&self) -> usize {
+        self.tracker.running.load(Ordering::SeqCst)
+    }
+
+    /// Trigger shutdown and wait up to `grace` for tracked tasks to finish
+    ///
+    /// Returns whether every task finished in time.
+    pub async fn drain(&self, grace: Duration) -> bool {
+        self.trigger();
+        let finished = async {
+            loop {
+                let notified = self.tracker.finished.notified();
+                if self.running() == 0 {
+                    return;
+                }
+                notified.await;
+            }
+        };
+        tokio::time::timeout(grace, finished).await.is_ok()
+    }
+}
@@ -0,0 +1,231 @@
+//! Programmable mock of the MT5 HTTP bridge
+//!
+//! Each test configures only the routes it needs through `MockBridge`, then
+//! spawns it on an ephemeral port. Canned data is wrapped in the bridge's
+//! `{ "success", "data", "error" }` envelope and every request is recorded:
+//!
+//! ```ignore
+//! let bridge = MockBridge::new()
+//!     .order(json!({ "ticket": 11 }))
+//!     .market(quote("EURUSD", 1.0850, 1.0851))
+//!     .spawn()
+//!     .await;
+//! let client = MT5BridgeClient::new(Arc::new(settings_for(&bridge.url))).await?;
+//! ```
+
+#![allow(dead_code)]
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::{Path, Request, State},
+    http::{HeaderMap, StatusCode},
+    middleware::{self, Next},
+    response::Response,
+    routing::{get, post, MethodRouter},
+    Json, Router,
+};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// A request received by the mock bridge
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub path: String,
+    pub headers: HeaderMap,
+    /// JSON body, if the request had one
+    pub body: Option<Value>,
+}
+
+/// Requests received so far, in arrival order
+#[derive(Debug, Clone, Default)]
+pub struct Requests(Arc<Mutex<Vec<RecordedRequest>>>);
+
+impl Requests {
+    pub fn all(&self) -> Vec<RecordedRequest> {
+        self.0.lock().unwrap().clone()
+    }
+
+    /// Requests to `path` with `method`
+    pub fn to(&self, method: &str, path: &str) -> Vec<RecordedRequest> {
+        self.all()
+            .into_iter()
+            .filter(|request| request.method == method && request.path == path)
+            .collect()
+    }
+
+    /// JSON bodies of the `POST /orders` requests
+    pub fn orders(&self) -> Vec<Value> {
+        self.to("POST", "/orders")
+            .into_iter()
+            .filter_map(|request| request.body)
+            .collect()
+    }
+}
+
+/// Successful bridge response carrying `data`
+pub fn envelope(data: Value) -> Value {
+    json!({ "success": true, "data": data })
+}
+
+/// Failed bridge response carrying `error`
+pub fn error_envelope(error: &str) -> Value {
+    json!({ "success": false, "error": error })
+}
+
+/// Market data payload for `symbol` with 5-digit pricing
+pub fn quote(symbol: &str, bid: f64, ask: f64) -> Value {
+    json!({
+        "symbol": symbol,
+        "bid": bid,
+        "ask": ask,
+        "last": bid,
+        "volume": 100.0,
+        "time": 1699113600,
+        "spread": ((ask - bid) * 1e5).round(),
+        "digits": 5,
+    })
+}
+
+/// Open position payload; `side` is 0 for buy, 1 for sell
+pub fn position(ticket: u64, symbol: &str, side: u8, volume: f64) -> Value {
+    json!({
+        "ticket": ticket,
+        "symbol": symbol,
+        "type": side,
+        "volume": volume,
+        "price_open": 1.0850,
+        "price_current": 1.0860,
+        "profit": 10.0,
+        "swap": 0.0,
+        "commission": -0.5,
+        "stop_loss": null,
+        "take_profit": null,
+        "comment": null,
+        "magic": 123456,
+        "time_open": 1699113600,
+    })
+}
+
+/// Builder for a mock bridge
+///
+/// `/health` answers 200 unless overridden; routes that aren't configured
+/// answer 404. Each path may only be configured once.
+pub struct MockBridge {
+    router: Router,
+    health: StatusCode,
+}
+
+impl Default for MockBridge {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockBridge {
+    pub fn new() -> Self {
+        Self {
+            router: Router::new(),
+            health: StatusCode::OK,
+        }
+    }
+
+    /// Status returned by `GET /health`
+    pub fn health(mut self, status: StatusCode) -> Self {
+        self.health = status;
+        self
+    }
+
+    /// Accept every `POST /orders` with `data` as the order result
+    pub fn order(self, data: Value) -> Self {
+        self.order_response(StatusCode::OK, envelope(data))
+    }
+
+    /// Answer every `POST /orders` with `status` and the raw `body`
+    pub fn order_response(self, status: StatusCode, body: Value) -> Self {
+        self.route("/orders", post(move || async move { (status, Json(body)) }))
+    }
+
+    /// Serve `positions` from `GET /positions` and, by symbol, `GET /positions/{symbol}`
+    pub fn positions(self, positions: Vec<Value>) -> Self {
+        let by_symbol: HashMap<String, Value> = positions
+            .iter()
+            .map(|position| (position["symbol"].as_str().unwrap_or_default().to_string(), position.clone()))
+            .collect();
+        let list = envelope(Value::Array(positions));
+        self.route("/positions", get(move || async move { Json(list) }))
+            .route(
+                "/positions/{symbol}",
+                get(move |Path(symbol): Path<String>| async move {
+                    match by_symbol.get(&symbol) {
+                        Some(position) => Ok(Json(envelope(position.clone()))),
+                        None => Err(StatusCode::NOT_FOUND),
+                    }
+                }),
+            )
+    }
+
+    /// Serve `GET /market/{symbol}` from `quotes`; other symbols are 404
+    pub fn market(self, quotes: Vec<Value>) -> Self {
+        let quotes: HashMap<String, Value> = quotes
+            .into_iter()
+            .map(|quote| (quote["symbol"].as_str().unwrap_or_default().to_string(), quote))
+            .collect();
+        self.route(
+            "/market/{symbol}",
+            get(move |Path(symbol): Path<String>| async move {
+                match quotes.get(&symbol) {
+                    Some(quote) => Ok(Json(envelope(quote.clone()))),
+                    None => Err(StatusCode::NOT_FOUND),
+                }
+            }),
+        )
+    }
+
+    /// Add any other route
+    pub fn route(mut self, path: &str, handler: MethodRouter) -> Self {
+        self.router = self.router.route(path, handler);
+        self
+    }
+
+    /// Serve the bridge on an ephemeral local port
+    pub async fn spawn(self) -> RunningBridge {
+        let requests = Requests::default();
+        let health = self.health;
+        let app = self
+            .router
+            .route("/health", get(move || async move { health }))
+            .layer(middleware::from_fn_with_state(requests.clone(), record));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        RunningBridge {
+            url: format!("http://{}", addr),
+            requests,
+        }
+    }
+}
+
+/// A spawned mock bridge
+pub struct RunningBridge {
+    /// Base URL to use as `mt5_bridge_url`
+    pub url: String,
+    pub requests: Requests,
+}
+
+async fn record(State(requests): State<Requests>, request: Request, next: Next) -> Response {
+    let (parts, body) = request.into_parts();
+    let bytes = to_bytes(body, usize::MAX).await.unwrap_or_default();
+    requests.0.lock().unwrap().push(RecordedRequest {
+        method: parts.method.to_string(),
+        path: parts.uri.path().to_string(),
+        headers: parts.headers.clone(),
+        body: serde_json::from_slice(&bytes).ok(),
+    });
+    next.run(Request::from_parts(parts, Body::from(bytes))).await
+}
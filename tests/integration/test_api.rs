@@ -0,0 +1,1942 @@
+//! Integration tests for the HTTP API against a mock bridge
+
+#[path = "../common/mock_bridge.rs"]
+mod mock_bridge;
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::{
+        ws::{Message as AxumMessage, WebSocketUpgrade},
+        Path, State,
+    },
+    http::{Request, StatusCode},
+    routing::{delete, get, post},
+    Json, Router,
+};
+use fks_meta::api::bridge_events::{sign, SIGNATURE_HEADER};
+use fks_meta::api::cors::cors_layer;
+use fks_meta::config::parse_magic_labels;
+use fks_meta::correlation::CORRELATION_ID_HEADER;
+use fks_meta::{AppState, BridgeEvent, MT5Client, Settings};
+use futures_util::StreamExt;
+use tokio_tungstenite::tungstenite::Message as WsMessage;
+use mock_bridge::{envelope, quote, MockBridge};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tower::ServiceExt;
+
+/// Start a mock bridge on an ephemeral port and return its base URL
+async fn spawn_bridge(app: Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    format!("http://{}", addr)
+}
+
+fn test_settings(bridge_url: &str) -> Settings {
+    let mut settings = Settings::from_env().unwrap();
+    settings.mt5_bridge_url = Some(bridge_url.to_string());
+    settings.mt5_retry_delay_ms = 10;
+    settings
+}
+
+async fn app_with_settings(settings: Settings) -> Router {
+    let settings = Arc::new(settings);
+    let mt5_client = Arc::new(MT5Client::new(settings.clone()).await.unwrap());
+    fks_meta::api::router(AppState::new(mt5_client, settings))
+}
+
+async fn test_app(bridge_url: &str) -> Router {
+    app_with_settings(test_settings(bridge_url)).await
+}
+
+fn post_json(uri: &str, body: serde_json::Value) -> Request<Body> {
+    Request::post(uri)
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+async fn body_string(response: axum::response::Response) -> String {
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    String::from_utf8(bytes.to_vec()).unwrap()
+}
+
+/// Read a single unlabelled sample from a Prometheus text exposition
+fn sample(exposition: &str, name: &str) -> u64 {
+    exposition
+        .lines()
+        .find_map(|line| line.strip_prefix(name)?.strip_prefix(' '))
+        .and_then(|value| value.parse().ok())
+        .unwrap_or_else(|| panic!("metric {} not found", name))
+}
+
+async fn scrape(app: &Router) -> String {
+    let response = app
+        .clone()
+        .oneshot(Request::get("/metrics").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    body_string(response).await
+}
+
+#[tokio::test]
+async fn test_metrics_count_submitted_orders() {
+    let bridge = Router::new()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .route(
+            "/orders",
+            post(|| async {
+                Json(serde_json::json!({ "success": true, "data": { "ticket": 7 } }))
+            }),
+        );
+    let app = test_app(&spawn_bridge(bridge).await).await;
+
+    let before = sample(&scrape(&app).await, "mt5_orders_submitted_total");
+
+    let order = serde_json::json!({
+        "symbol": "EURUSD",
+        "order_type": "OP_BUY",
+        "volume": 0.1,
+        "price": 1.0850,
+    });
+    let response = app.clone().oneshot(post_json("/orders", order)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let exposition = scrape(&app).await;
+    assert!(sample(&exposition, "mt5_orders_submitted_total") > before);
+    assert!(exposition.contains("# TYPE mt5_bridge_request_duration_seconds histogram"));
+}
+
+#[tokio::test]
+async fn test_metrics_label_orders_by_strategy() {
+    let bridge = Router::new()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .route(
+            "/orders",
+            post(|| async {
+                Json(serde_json::json!({ "success": true, "data": { "ticket": 7 } }))
+            }),
+        );
+    let mut settings = test_settings(&spawn_bridge(bridge).await);
+    settings.mt5_magic_labels = parse_magic_labels("424242=trend-test").unwrap();
+    let app = app_with_settings(settings).await;
+
+    for magic in [424242, 515151] {
+        let order = serde_json::json!({
+            "symbol": "EURUSD",
+            "order_type": "OP_BUY",
+            "volume": 0.1,
+            "magic": magic,
+        });
+        let response = app.clone().oneshot(post_json("/orders", order)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    let exposition = scrape(&app).await;
+    assert!(exposition.contains("# TYPE mt5_orders_by_strategy_total counter"));
+    assert_eq!(sample(&exposition, "mt5_orders_by_strategy_total{strategy=\"trend-test\"}"), 1);
+    assert_eq!(sample(&exposition, "mt5_orders_by_strategy_total{strategy=\"515151\"}"), 1);
+}
+
+/// Batch of `count` market orders
+fn order_batch(count: usize) -> serde_json::Value {
+    let order = serde_json::json!({
+        "symbol": "EURUSD",
+        "order_type": "OP_BUY",
+        "volume": 0.1,
+        "comment": "batch order",
+        "client_order_id": uuid::Uuid::new_v4().to_string(),
+    });
+    serde_json::Value::Array(vec![order; count])
+}
+
+/// Mock bridge filling every order of a batch, numbering tickets from 1
+async fn batch_bridge() -> mock_bridge::RunningBridge {
+    MockBridge::new()
+        .route(
+            "/orders/batch",
+            post(|Json(batch): Json<serde_json::Value>| async move {
+                let count = batch["orders"].as_array().map_or(0, Vec::len);
+                let results: Vec<_> = (1..=count)
+                    .map(|ticket| serde_json::json!({ "success": true, "data": { "ticket": ticket } }))
+                    .collect();
+                Json(mock_bridge::envelope(results.into()))
+            }),
+        )
+        .spawn()
+        .await
+}
+
+#[tokio::test]
+async fn test_oversized_body_is_rejected() {
+    let bridge = MockBridge::new().route("/orders/batch", post(|| async { StatusCode::OK })).spawn().await;
+    let mut settings = test_settings(&bridge.url);
+    settings.mt5_max_body_bytes = 4096;
+    let app = app_with_settings(settings).await;
+
+    let batch = order_batch(100);
+    assert!(batch.to_string().len() > 4096);
+    let response = app.clone().oneshot(post_json("/orders/batch", batch.clone())).await.unwrap();
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+    // A declared length over the limit is refused before the body is read
+    let declared = Request::post("/orders/batch")
+        .header("content-type", "application/json")
+        .header("content-length", batch.to_string().len())
+        .body(Body::from(batch.to_string()))
+        .unwrap();
+    let response = app.oneshot(declared).await.unwrap();
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    assert!(bridge.requests.to("POST", "/orders/batch").is_empty());
+}
+
+#[tokio::test]
+async fn test_default_body_limit_allows_reasonable_batches() {
+    let results: Vec<_> = (1..=100)
+        .map(|ticket| serde_json::json!({ "success": true, "data": { "ticket": ticket } }))
+        .collect();
+    let bridge = MockBridge::new()
+        .route("/orders/batch", post(move || async move { Json(mock_bridge::envelope(results.into())) }))
+        .spawn()
+        .await;
+    let app = test_app(&bridge.url).await;
+
+    let response = app.oneshot(post_json("/orders/batch", order_batch(100))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let results: Vec<serde_json::Value> = serde_json::from_str(&body_string(response).await).unwrap();
+    assert_eq!(results.len(), 100);
+}
+
+#[tokio::test]
+async fn test_batch_reports_each_order_status() {
+    let results = serde_json::json!([
+        { "success": true, "data": { "ticket": 1, "price": 1.0851, "volume": 0.1 } },
+        { "success": true, "data": { "ticket": 2 } },
+        { "success": false, "error": "No money" },
+    ]);
+    let bridge = MockBridge::new()
+        .route("/orders/batch", post(move || async move { Json(mock_bridge::envelope(results)) }))
+        .spawn()
+        .await;
+
+    let response = test_app(&bridge.url).await.oneshot(post_json("/orders/batch", order_batch(3))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: Vec<serde_json::Value> = serde_json::from_str(&body_string(response).await).unwrap();
+    let statuses: Vec<&str> = body.iter().map(|result| result["status"].as_str().unwrap()).collect();
+    assert_eq!(statuses, ["filled", "pending", "rejected"]);
+
+    let mut settings = test_settings(&bridge.url);
+    settings.mt5_dry_run = true;
+    let app = app_with_settings(settings).await;
+    let response = app.oneshot(post_json("/orders/batch", order_batch(1))).await.unwrap();
+    let body: Vec<serde_json::Value> = serde_json::from_str(&body_string(response).await).unwrap();
+    assert_eq!(body[0]["status"], "dry_run");
+}
+
+#[tokio::test]
+async fn test_batch_rejects_invalid_orders_individually() {
+    let bridge = batch_bridge().await;
+    let app = test_app(&bridge.url).await;
+
+    let mut batch = order_batch(3);
+    batch[1]["order_type"] = "OP_BOGUS".into();
+    batch[1]["symbol"] = "GBPUSD".into();
+    let response = app.clone().oneshot(post_json("/orders/batch", batch)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: Vec<serde_json::Value> = serde_json::from_str(&body_string(response).await).unwrap();
+    let statuses: Vec<&str> = body.iter().map(|result| result["status"].as_str().unwrap()).collect();
+    assert_eq!(statuses, ["pending", "rejected", "pending"]);
+    assert_eq!(body[1]["symbol"], "GBPUSD");
+    assert!(body[1]["error"].as_str().unwrap().contains("OP_BOGUS"));
+    assert_eq!(body[2]["ticket"], 2);
+    let sent = bridge.requests.to("POST", "/orders/batch");
+    assert_eq!(sent[0].body.as_ref().unwrap()["orders"].as_array().unwrap().len(), 2);
+
+    // A batch with nothing valid never reaches the bridge
+    let mut batch = order_batch(1);
+    batch[0]["volume"] = (-1.0).into();
+    let response = app.oneshot(post_json("/orders/batch", batch)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: Vec<serde_json::Value> = serde_json::from_str(&body_string(response).await).unwrap();
+    assert_eq!(body[0]["status"], "rejected");
+    assert_eq!(bridge.requests.to("POST", "/orders/batch").len(), 1);
+}
+
+#[tokio::test]
+async fn test_order_history_rejects_inverted_range() {
+    let bridge = Router::new().route("/health", get(|| async { StatusCode::OK }));
+    let app = test_app(&spawn_bridge(bridge).await).await;
+
+    let response = app
+        .oneshot(
+            Request::get("/history/orders?from=200&to=100")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_candles_reject_unknown_timeframe() {
+    let bridge = Router::new().route("/health", get(|| async { StatusCode::OK }));
+    let app = test_app(&spawn_bridge(bridge).await).await;
+
+    let response = app
+        .oneshot(
+            Request::get("/market/EURUSD/candles?timeframe=M7")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert!(body_string(response).await.contains("M7"));
+}
+
+#[tokio::test]
+async fn test_orders_carry_configured_magic_number() {
+    let magics = Arc::new(Mutex::new(Vec::new()));
+    let bridge = Router::new()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .route(
+            "/orders",
+            post(
+                |State(magics): State<Arc<Mutex<Vec<u64>>>>, Json(body): Json<serde_json::Value>| async move {
+                    magics.lock().unwrap().push(body["magic"].as_u64().unwrap());
+                    Json(serde_json::json!({ "success": true, "data": { "ticket": 1 } }))
+                },
+            ),
+        )
+        .with_state(magics.clone());
+    let mut settings = test_settings(&spawn_bridge(bridge).await);
+    settings.mt5_magic_number = 777;
+    let app = app_with_settings(settings).await;
+
+    let order = serde_json::json!({
+        "symbol": "EURUSD",
+        "order_type": "OP_BUY",
+        "volume": 0.1,
+        "price": 0.0,
+    });
+    let response = app.clone().oneshot(post_json("/orders", order.clone())).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let mut with_override = order;
+    with_override["magic"] = 888.into();
+    let response = app.oneshot(post_json("/orders", with_override)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    assert_eq!(*magics.lock().unwrap(), vec![777, 888]);
+}
+
+fn get_with_key(uri: &str, api_key: Option<&str>) -> Request<Body> {
+    let mut request = Request::get(uri);
+    if let Some(api_key) = api_key {
+        request = request.header("x-api-key", api_key);
+    }
+    request.body(Body::empty()).unwrap()
+}
+
+async fn app_with_api_key(api_key: Option<&str>) -> Router {
+    let bridge = Router::new().route("/health", get(|| async { StatusCode::OK }));
+    let mut settings = test_settings(&spawn_bridge(bridge).await);
+    settings.mt5_api_key = api_key.map(str::to_string);
+    app_with_settings(settings).await
+}
+
+#[tokio::test]
+async fn test_api_key_authorized() {
+    let app = app_with_api_key(Some("k3y")).await;
+
+    let response = app.oneshot(get_with_key("/status", Some("k3y"))).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_api_key_unauthorized() {
+    let app = app_with_api_key(Some("k3y")).await;
+
+    let missing = app.clone().oneshot(get_with_key("/status", None)).await.unwrap();
+    let wrong = app.clone().oneshot(get_with_key("/status", Some("nope"))).await.unwrap();
+    let health = app.clone().oneshot(get_with_key("/health", None)).await.unwrap();
+    let metrics = app.oneshot(get_with_key("/metrics", None)).await.unwrap();
+
+    assert_eq!(missing.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(wrong.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(health.status(), StatusCode::OK);
+    assert_eq!(metrics.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_api_key_not_configured() {
+    let app = app_with_api_key(None).await;
+
+    let response = app.oneshot(get_with_key("/status", None)).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_stale_market_data_carries_warning_header() {
+    let healthy = Arc::new(AtomicBool::new(true));
+    let bridge = Router::new()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .route(
+            "/market/{symbol}",
+            get(|State(healthy): State<Arc<AtomicBool>>| async move {
+                if !healthy.load(Ordering::SeqCst) {
+                    return Err(StatusCode::SERVICE_UNAVAILABLE);
+                }
+                Ok(Json(serde_json::json!({
+                    "success": true,
+                    "data": {
+                        "symbol": "EURUSD",
+                        "bid": 1.0850,
+                        "ask": 1.0851,
+                        "last": 1.0850,
+                        "volume": 100.0,
+                        "time": 1699113600,
+                        "spread": 1,
+                        "digits": 5,
+                    },
+                })))
+            }),
+        )
+        .with_state(healthy.clone());
+    let mut settings = test_settings(&spawn_bridge(bridge).await);
+    settings.mt5_retry_attempts = 1;
+    settings.mt5_circuit_failure_threshold = 1;
+    let app = app_with_settings(settings).await;
+
+    let fresh = app.clone().oneshot(get_with_key("/market/EURUSD", None)).await.unwrap();
+    assert_eq!(fresh.status(), StatusCode::OK);
+    assert!(fresh.headers().get("warning").is_none());
+    assert!(!body_string(fresh).await.contains("stale"));
+
+    healthy.store(false, Ordering::SeqCst);
+    let stale = app.oneshot(get_with_key("/market/EURUSD", None)).await.unwrap();
+    assert_eq!(stale.status(), StatusCode::OK);
+    assert_eq!(stale.headers()["warning"], "110 - \"Response is Stale\"");
+    let body: serde_json::Value = serde_json::from_str(&body_string(stale).await).unwrap();
+    assert_eq!(body["stale"], true);
+}
+
+#[tokio::test]
+async fn test_market_stream_stops_polling_on_disconnect() {
+    let polls = Arc::new(Mutex::new(0u32));
+    let bridge = Router::new()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .route(
+            "/market/{symbol}",
+            get(|State(polls): State<Arc<Mutex<u32>>>| async move {
+                *polls.lock().unwrap() += 1;
+                Json(serde_json::json!({
+                    "success": true,
+                    "data": {
+                        "symbol": "EURUSD",
+                        "bid": 1.0850,
+                        "ask": 1.0851,
+                        "last": 1.0850,
+                        "volume": 100.0,
+                        "time": 1699113600,
+                        "spread": 1,
+                        "digits": 5,
+                    },
+                }))
+            }),
+        )
+        .with_state(polls.clone());
+    let mut settings = test_settings(&spawn_bridge(bridge).await);
+    settings.mt5_stream_interval_ms = 10;
+    let app = app_with_settings(settings).await;
+
+    let response = app
+        .oneshot(Request::get("/market/EURUSD/stream").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "text/event-stream");
+
+    let mut body = response.into_body().into_data_stream();
+    let first = body.next().await.unwrap().unwrap();
+    let first = String::from_utf8(first.to_vec()).unwrap();
+    assert!(first.starts_with("data: "));
+    assert!(first.contains("\"symbol\":\"EURUSD\""));
+    drop(body);
+
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    let after_disconnect = *polls.lock().unwrap();
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    assert_eq!(*polls.lock().unwrap(), after_disconnect);
+}
+
+/// Serve `app` on an ephemeral port and return its `ws://` base URL
+async fn spawn_ws_app(app: Router) -> String {
+    spawn_bridge(app).await.replacen("http://", "ws://", 1)
+}
+
+/// Next text frame from a WebSocket, parsed as JSON
+async fn next_json<S>(socket: &mut S) -> serde_json::Value
+where
+    S: futures_util::Stream<Item = Result<WsMessage, tokio_tungstenite::tungstenite::Error>> + Unpin,
+{
+    loop {
+        match socket.next().await.unwrap().unwrap() {
+            WsMessage::Text(text) => return serde_json::from_str(&text).unwrap(),
+            _ => continue,
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_market_stream_over_websocket() {
+    let bridge = MockBridge::new().market(vec![quote("EURUSD", 1.0850, 1.0851)]).spawn().await;
+    let mut settings = test_settings(&bridge.url);
+    settings.mt5_stream_interval_ms = 10;
+    let url = spawn_ws_app(app_with_settings(settings).await).await;
+
+    let (mut socket, _) = tokio_tungstenite::connect_async(format!("{}/market/EURUSD/stream", url))
+        .await
+        .unwrap();
+    let frame = next_json(&mut socket).await;
+    assert_eq!(frame["symbol"], "EURUSD");
+    assert_eq!(frame["bid"], 1.0850);
+    socket.close(None).await.unwrap();
+    drop(socket);
+
+    // Closing the socket stops the polling behind it
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    let after_disconnect = bridge.requests.to("GET", "/market/EURUSD").len();
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    assert_eq!(bridge.requests.to("GET", "/market/EURUSD").len(), after_disconnect);
+}
+
+#[tokio::test]
+async fn test_market_stream_relays_bridge_websocket() {
+    let bridge = MockBridge::new()
+        .market(vec![quote("EURUSD", 1.0850, 1.0851)])
+        .route(
+            "/ws/market/{symbol}",
+            get(|ws: WebSocketUpgrade, Path(symbol): Path<String>| async move {
+                ws.on_upgrade(move |mut socket| async move {
+                    let tick = quote(&symbol, 1.2345, 1.2346).to_string();
+                    socket.send(AxumMessage::Text(tick.into())).await.unwrap();
+                    // Hold the socket open until the service disconnects
+                    while socket.recv().await.is_some() {}
+                })
+            }),
+        )
+        .spawn()
+        .await;
+    let mut settings = test_settings(&bridge.url);
+    settings.mt5_bridge_ws_url = Some(format!("{}/ws", bridge.url.replacen("http://", "ws://", 1)));
+    let url = spawn_ws_app(app_with_settings(settings).await).await;
+
+    let (mut socket, _) = tokio_tungstenite::connect_async(format!("{}/market/EURUSD/stream", url))
+        .await
+        .unwrap();
+    let frame = next_json(&mut socket).await;
+    assert_eq!(frame["symbol"], "EURUSD");
+    assert_eq!(frame["bid"], 1.2345);
+    assert!(bridge.requests.to("GET", "/market/EURUSD").is_empty());
+}
+
+fn rejecting_bridge() -> Router {
+    Router::new()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .route(
+            "/orders",
+            post(|| async {
+                Json(serde_json::json!({ "success": false, "error": "Not enough money" }))
+            }),
+        )
+        .route("/market/{symbol}", get(|| async { StatusCode::NOT_FOUND }))
+        .route("/positions", get(|| async { Json(mock_bridge::envelope(serde_json::json!([]))) }))
+        .route(
+            "/positions/{ticket}",
+            get(|| async { StatusCode::NOT_FOUND }).delete(|| async { StatusCode::NOT_FOUND }),
+        )
+        .route(
+            "/account",
+            get(|| async {
+                tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+                StatusCode::OK
+            }),
+        )
+}
+
+#[tokio::test]
+async fn test_order_rejection_maps_to_unprocessable_entity() {
+    let app = test_app(&spawn_bridge(rejecting_bridge()).await).await;
+
+    let order = serde_json::json!({
+        "symbol": "EURUSD",
+        "order_type": "OP_BUY",
+        "volume": 0.1,
+        "price": 0.0,
+    });
+    let response = app.oneshot(post_json("/orders", order)).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert!(body_string(response).await.contains("Not enough money"));
+}
+
+#[tokio::test]
+async fn test_missing_resources_map_to_client_errors() {
+    let app = test_app(&spawn_bridge(rejecting_bridge()).await).await;
+
+    let symbol = app
+        .clone()
+        .oneshot(Request::get("/market/NOPE").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let position = app
+        .clone()
+        .oneshot(Request::delete("/positions/NOPE").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let by_ticket = app
+        .clone()
+        .oneshot(Request::get("/positions/ticket/42").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let close_by_ticket = app
+        .oneshot(Request::delete("/positions/ticket/42").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+
+    assert_eq!(symbol.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(position.status(), StatusCode::NOT_FOUND);
+    assert_eq!(by_ticket.status(), StatusCode::NOT_FOUND);
+    assert_eq!(close_by_ticket.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_bridge_timeout_maps_to_gateway_timeout() {
+    let mut settings = test_settings(&spawn_bridge(rejecting_bridge()).await);
+    settings.mt5_timeout_ms = 100;
+    settings.mt5_retry_attempts = 1;
+    let app = app_with_settings(settings).await;
+
+    let response = app
+        .oneshot(Request::get("/account").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+}
+
+#[tokio::test]
+async fn test_unreachable_bridge_maps_to_service_unavailable() {
+    // Bind and drop a listener to get a port nothing is listening on
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    drop(listener);
+    let mut settings = test_settings(&url);
+    settings.mt5_retry_attempts = 1;
+    let app = app_with_settings(settings).await;
+
+    let account = app
+        .clone()
+        .oneshot(Request::get("/account").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let order = serde_json::json!({
+        "symbol": "EURUSD",
+        "order_type": "OP_BUY",
+        "volume": 0.1,
+        "price": 0.0,
+    });
+    let order = app.oneshot(post_json("/orders", order)).await.unwrap();
+
+    assert_eq!(account.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(order.status(), StatusCode::SERVICE_UNAVAILABLE);
+}
+
+#[tokio::test]
+async fn test_order_response_carries_retcode() {
+    let bridge = Router::new()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .route(
+            "/orders",
+            post(|Json(body): Json<serde_json::Value>| async move {
+                if body["volume"].as_f64().unwrap() > 1.0 {
+                    Json(serde_json::json!({
+                        "success": false,
+                        "error": "No money",
+                        "data": { "retcode": 10019 },
+                    }))
+                } else {
+                    Json(serde_json::json!({
+                        "success": true,
+                        "data": { "ticket": 5, "retcode": 10009 },
+                    }))
+                }
+            }),
+        );
+    let app = test_app(&spawn_bridge(bridge).await).await;
+
+    let order = serde_json::json!({
+        "symbol": "EURUSD",
+        "order_type": "OP_BUY",
+        "volume": 0.1,
+        "price": 0.0,
+    });
+    let response = app.clone().oneshot(post_json("/orders", order.clone())).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value = serde_json::from_str(&body_string(response).await).unwrap();
+    assert_eq!(body["retcode"], 10009);
+    assert_eq!(body["retcode_description"], "Request completed");
+
+    let mut too_large = order;
+    too_large["volume"] = 5.0.into();
+    let response = app.oneshot(post_json("/orders", too_large)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let error = body_string(response).await;
+    assert!(error.contains("10019"), "{}", error);
+    assert!(error.contains("not enough money"), "{}", error);
+    assert!(error.contains("No money"), "{}", error);
+}
+
+#[tokio::test]
+async fn test_order_response_reports_fill() {
+    let bridge = Router::new()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .route(
+            "/orders",
+            post(|Json(body): Json<serde_json::Value>| async move {
+                let data = if body["action"] == 1 {
+                    serde_json::json!({ "ticket": 5, "price": 1.08512, "volume": 0.1 })
+                } else {
+                    serde_json::json!({ "ticket": 6 })
+                };
+                Json(serde_json::json!({ "success": true, "data": data }))
+            }),
+        );
+    let app = test_app(&spawn_bridge(bridge).await).await;
+
+    let market = serde_json::json!({
+        "symbol": "EURUSD",
+        "order_type": "OP_BUY",
+        "volume": 0.1,
+        "price": 1.0850,
+    });
+    let response = app.clone().oneshot(post_json("/orders", market)).await.unwrap();
+    let body: serde_json::Value = serde_json::from_str(&body_string(response).await).unwrap();
+    assert_eq!(body["status"], "filled");
+    assert_eq!(body["average_price"], 1.08512);
+    assert_eq!(body["filled_volume"], 0.1);
+
+    let limit = serde_json::json!({
+        "symbol": "EURUSD",
+        "order_type": "OP_BUYLIMIT",
+        "volume": 0.1,
+        "price": 1.0800,
+    });
+    let response = app.oneshot(post_json("/orders", limit)).await.unwrap();
+    let body: serde_json::Value = serde_json::from_str(&body_string(response).await).unwrap();
+    assert_eq!(body["status"], "pending");
+    assert!(body["average_price"].is_null());
+}
+
+#[tokio::test]
+async fn test_order_price_requirement() {
+    let bridge = Router::new()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .route(
+            "/orders",
+            post(|| async { Json(serde_json::json!({ "success": true, "data": { "ticket": 1 } })) }),
+        );
+    let app = test_app(&spawn_bridge(bridge).await).await;
+
+    let limit = serde_json::json!({ "symbol": "EURUSD", "order_type": "OP_BUYLIMIT", "volume": 0.1 });
+    let response = app.clone().oneshot(post_json("/orders", limit)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let error = body_string(response).await;
+    assert!(error.contains("requires a price"), "{}", error);
+
+    // Market orders fill at market without one
+    for price in [serde_json::Value::Null, serde_json::json!(0.0)] {
+        let market = serde_json::json!({ "symbol": "EURUSD", "order_type": "OP_BUY", "volume": 0.1, "price": price });
+        let response = app.clone().oneshot(post_json("/orders", market)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+    let market = serde_json::json!({ "symbol": "EURUSD", "order_type": "OP_SELL", "volume": 0.1 });
+    let response = app.oneshot(post_json("/orders", market)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_order_deviation_forwarded_to_bridge() {
+    let payloads = Arc::new(Mutex::new(Vec::new()));
+    let bridge = Router::new()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .route(
+            "/orders",
+            post(
+                |State(payloads): State<Arc<Mutex<Vec<serde_json::Value>>>>,
+                 Json(body): Json<serde_json::Value>| async move {
+                    payloads.lock().unwrap().push(body);
+                    Json(serde_json::json!({ "success": true, "data": { "ticket": 1 } }))
+                },
+            ),
+        )
+        .with_state(payloads.clone());
+    let mut settings = test_settings(&spawn_bridge(bridge).await);
+    settings.mt5_default_deviation = Some(10);
+    let app = app_with_settings(settings).await;
+
+    let orders = [
+        serde_json::json!({ "symbol": "EURUSD", "order_type": "OP_BUY", "volume": 0.1, "price": 0.0, "deviation": 25 }),
+        serde_json::json!({ "symbol": "EURUSD", "order_type": "OP_BUY", "volume": 0.1, "price": 0.0 }),
+        serde_json::json!({ "symbol": "EURUSD", "order_type": "OP_BUYLIMIT", "volume": 0.1, "price": 1.0800 }),
+    ];
+    for order in orders {
+        let response = app.clone().oneshot(post_json("/orders", order)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    let payloads = payloads.lock().unwrap();
+    assert_eq!(payloads[0]["deviation"], 25);
+    assert_eq!(payloads[1]["deviation"], 10);
+    // Pending orders have no slippage to bound
+    assert!(payloads[2].get("deviation").is_none());
+}
+
+#[tokio::test]
+async fn test_requote_maps_to_conflict() {
+    let bridge = Router::new()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .route(
+            "/orders",
+            post(|| async {
+                Json(serde_json::json!({
+                    "success": false,
+                    "error": "Requote",
+                    "data": { "ticket": 0, "retcode": 10004 },
+                }))
+            }),
+        );
+    let app = test_app(&spawn_bridge(bridge).await).await;
+
+    let order = serde_json::json!({ "symbol": "EURUSD", "order_type": "OP_BUY", "volume": 0.1, "price": 0.0 });
+    let response = app.oneshot(post_json("/orders", order)).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    assert!(body_string(response).await.contains("requoted"));
+}
+
+#[tokio::test]
+async fn test_order_rate_limit() {
+    let bridge = Router::new()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .route(
+            "/orders",
+            post(|| async { Json(serde_json::json!({ "success": true, "data": { "ticket": 1 } })) }),
+        );
+    let mut settings = test_settings(&spawn_bridge(bridge).await);
+    settings.mt5_max_orders_per_sec = 3;
+    let app = app_with_settings(settings).await;
+
+    let order = serde_json::json!({
+        "symbol": "EURUSD",
+        "order_type": "OP_BUY",
+        "volume": 0.1,
+        "price": 0.0,
+    });
+    let mut statuses = Vec::new();
+    for _ in 0..6 {
+        let response = app.clone().oneshot(post_json("/orders", order.clone())).await.unwrap();
+        if response.status() == StatusCode::TOO_MANY_REQUESTS {
+            assert_eq!(response.headers()["retry-after"], "1");
+        }
+        statuses.push(response.status());
+    }
+
+    assert_eq!(statuses.iter().filter(|status| **status == StatusCode::OK).count(), 3);
+    assert!(statuses.contains(&StatusCode::TOO_MANY_REQUESTS));
+
+    // Batches draw from the same limit; reads aren't limited
+    let response = app
+        .clone()
+        .oneshot(post_json("/orders/batch", serde_json::json!([order])))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    let response = app.oneshot(get_with_key("/status", None)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_order_batch_charges_one_token_per_order() {
+    let bridge = batch_bridge().await;
+    let mut settings = test_settings(&bridge.url);
+    settings.mt5_max_orders_per_sec = 3;
+    let app = app_with_settings(settings).await;
+
+    // More orders than the bucket holds could never be admitted, so
+    // retrying is pointless: 413 without Retry-After, and no tokens spent
+    let response = app.clone().oneshot(post_json("/orders/batch", order_batch(4))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    assert!(!response.headers().contains_key("retry-after"));
+    assert!(bridge.requests.to("POST", "/orders/batch").is_empty());
+
+    // Orders that fail validation aren't charged
+    let mut batch = order_batch(3);
+    batch[0]["order_type"] = "OP_BOGUS".into();
+    let response = app.clone().oneshot(post_json("/orders/batch", batch)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // One token is left: not enough for another pair, enough for a single order
+    let response = app.clone().oneshot(post_json("/orders/batch", order_batch(2))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    let response = app.oneshot(post_json("/orders/batch", order_batch(1))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(bridge.requests.to("POST", "/orders/batch").len(), 2);
+}
+
+#[tokio::test]
+async fn test_status_reports_bridge_stats() {
+    let bridge = Router::new().route("/health", get(|| async { StatusCode::OK }));
+    let url = spawn_bridge(bridge).await;
+    let app = test_app(&url).await;
+
+    let response = app.oneshot(get_with_key("/status", None)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value = serde_json::from_str(&body_string(response).await).unwrap();
+
+    assert_eq!(body["connected"], true);
+    assert_eq!(body["bridge_url"], url);
+    assert!(body["last_success_ms"].is_null());
+    assert!(body["avg_latency_ms"].is_null());
+    // The bridge has no /capabilities endpoint, so everything counts as supported
+    assert_eq!(
+        body["capabilities"],
+        serde_json::json!({ "history": true, "candles": true, "account": true })
+    );
+}
+
+#[tokio::test]
+async fn test_status_probes_bridge_live() {
+    let healthy = Arc::new(AtomicBool::new(true));
+    let bridge = Router::new().route(
+        "/health",
+        get({
+            let healthy = healthy.clone();
+            move || async move {
+                if healthy.load(Ordering::SeqCst) {
+                    StatusCode::OK
+                } else {
+                    StatusCode::SERVICE_UNAVAILABLE
+                }
+            }
+        }),
+    );
+    let settings = Arc::new(test_settings(&spawn_bridge(bridge).await));
+    let mt5_client = Arc::new(MT5Client::new(settings.clone()).await.unwrap());
+    let app = fks_meta::api::router(AppState::new(mt5_client.clone(), settings));
+    assert!(mt5_client.is_connected().await);
+
+    // The cached flag is still set, but the bridge has gone unhealthy
+    healthy.store(false, Ordering::SeqCst);
+    assert!(mt5_client.is_connected().await);
+
+    let response = app.clone().oneshot(get_with_key("/status", None)).await.unwrap();
+    let body: serde_json::Value = serde_json::from_str(&body_string(response).await).unwrap();
+    assert_eq!(body["connected"], false);
+    assert_eq!(body["mt5_status"], "disconnected");
+    assert!(!mt5_client.is_connected().await);
+
+    // The service health endpoint doesn't consult the bridge
+    let response = app.oneshot(get_with_key("/health", None)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_health_reports_standalone_mode() {
+    let bridge = Router::new().route("/health", get(|| async { StatusCode::OK }));
+    let app = test_app(&spawn_bridge(bridge).await).await;
+
+    let response = app.oneshot(get_with_key("/health", None)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value = serde_json::from_str(&body_string(response).await).unwrap();
+    assert_eq!(body["mode"], "standalone");
+    assert_eq!(body["plugin_name"], serde_json::Value::Null);
+}
+
+#[tokio::test]
+async fn test_admin_reconnect_after_bridge_restart() {
+    let healthy = Arc::new(AtomicBool::new(false));
+    let bridge = Router::new().route(
+        "/health",
+        get({
+            let healthy = healthy.clone();
+            move || async move {
+                if healthy.load(Ordering::SeqCst) {
+                    StatusCode::OK
+                } else {
+                    StatusCode::SERVICE_UNAVAILABLE
+                }
+            }
+        }),
+    );
+    let mut settings = test_settings(&spawn_bridge(bridge).await);
+    // Keep the background reconnection from getting there first
+    settings.mt5_retry_delay_ms = 60_000;
+    let settings = Arc::new(settings);
+    let mt5_client = Arc::new(MT5Client::new(settings.clone()).await.unwrap());
+    let app = fks_meta::api::router(AppState::new(mt5_client.clone(), settings));
+    assert!(!mt5_client.is_connected().await);
+
+    // The bridge comes back up
+    healthy.store(true, Ordering::SeqCst);
+    let response = app
+        .oneshot(Request::post("/admin/reconnect").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value = serde_json::from_str(&body_string(response).await).unwrap();
+
+    assert_eq!(body["connected"], true);
+    assert_eq!(body["state"], "connected");
+    assert!(mt5_client.is_connected().await);
+}
+
+#[tokio::test]
+async fn test_admin_reconnect_requires_api_key() {
+    let app = app_with_api_key(Some("k3y")).await;
+    let response = app
+        .oneshot(Request::post("/admin/reconnect").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_order_expiration_reaches_bridge() {
+    let payloads = Arc::new(Mutex::new(Vec::new()));
+    let bridge = Router::new()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .route(
+            "/orders",
+            post(
+                |State(payloads): State<Arc<Mutex<Vec<serde_json::Value>>>>,
+                 Json(body): Json<serde_json::Value>| async move {
+                    payloads.lock().unwrap().push(body);
+                    Json(serde_json::json!({ "success": true, "data": { "ticket": 1 } }))
+                },
+            ),
+        )
+        .with_state(payloads.clone());
+    let app = test_app(&spawn_bridge(bridge).await).await;
+
+    let expiration = chrono::Utc::now().timestamp() + 3600;
+    let order = serde_json::json!({
+        "symbol": "EURUSD",
+        "order_type": "OP_BUYLIMIT",
+        "volume": 0.1,
+        "price": 1.0800,
+        "expiration": expiration,
+    });
+    let response = app.clone().oneshot(post_json("/orders", order.clone())).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let mut expired = order;
+    expired["expiration"] = (expiration - 7200).into();
+    let response = app.oneshot(post_json("/orders", expired)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let payloads = payloads.lock().unwrap();
+    assert_eq!(payloads.len(), 1);
+    assert_eq!(payloads[0]["expiration"], expiration);
+}
+
+#[tokio::test]
+async fn test_list_orders_filters_by_symbol() {
+    let order = |ticket: u64, symbol: &str| {
+        serde_json::json!({
+            "ticket": ticket,
+            "symbol": symbol,
+            "order_type": "OP_BUYLIMIT",
+            "volume": 0.1,
+            "price": 1.0800,
+            "stop_limit": null,
+            "stop_loss": null,
+            "take_profit": null,
+            "comment": null,
+            "magic": 123456,
+            "expiration": null,
+        })
+    };
+    let orders = vec![order(1, "EURUSD"), order(2, "GBPUSD"), order(3, "EURUSD")];
+    let bridge = Router::new()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .route(
+            "/orders",
+            get(move || async move { Json(serde_json::json!({ "success": true, "data": orders })) }),
+        );
+    let app = test_app(&spawn_bridge(bridge).await).await;
+
+    let all = app.clone().oneshot(get_with_key("/orders", None)).await.unwrap();
+    let all: Vec<serde_json::Value> = serde_json::from_str(&body_string(all).await).unwrap();
+    let filtered = app.oneshot(get_with_key("/orders?symbol=EURUSD", None)).await.unwrap();
+    assert_eq!(filtered.status(), StatusCode::OK);
+    let filtered: Vec<serde_json::Value> =
+        serde_json::from_str(&body_string(filtered).await).unwrap();
+
+    assert_eq!(all.len(), 3);
+    let tickets: Vec<_> = filtered.iter().map(|order| order["ticket"].as_u64().unwrap()).collect();
+    assert_eq!(tickets, vec![1, 3]);
+}
+
+#[tokio::test]
+async fn test_get_order_distinguishes_missing_from_bridge_failure() {
+    let bridge = Router::new()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .route(
+            "/orders/{ticket}",
+            get(|Path(ticket): Path<u64>| async move {
+                if ticket == 404 {
+                    StatusCode::NOT_FOUND
+                } else {
+                    StatusCode::INTERNAL_SERVER_ERROR
+                }
+            }),
+        )
+        .route(
+            "/history/orders",
+            get(|| async { Json(serde_json::json!({ "success": true, "data": [] })) }),
+        );
+    let app = test_app(&spawn_bridge(bridge).await).await;
+
+    let missing = app.clone().oneshot(get_with_key("/orders/404", None)).await.unwrap();
+    let failing = app.oneshot(get_with_key("/orders/500", None)).await.unwrap();
+
+    assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+    assert_eq!(failing.status(), StatusCode::SERVICE_UNAVAILABLE);
+}
+
+#[tokio::test]
+async fn test_order_status_reports_minimal_state() {
+    let order = |ticket: u64, state: &str| {
+        serde_json::json!({
+            "ticket": ticket,
+            "symbol": "EURUSD",
+            "order_type": "OP_BUYLIMIT",
+            "volume": 0.4,
+            "price": 1.08,
+            "magic": 123456,
+            "state": state,
+        })
+    };
+    let (live, filled) = (order(5, "pending"), order(6, "filled"));
+    let bridge = MockBridge::new()
+        .route(
+            "/orders/{ticket}",
+            get(move |Path(ticket): Path<u64>| async move {
+                match ticket {
+                    5 => Ok(Json(mock_bridge::envelope(live))),
+                    _ => Err(StatusCode::NOT_FOUND),
+                }
+            }),
+        )
+        .route(
+            "/history/orders",
+            get(move || async move { Json(mock_bridge::envelope(serde_json::json!([filled]))) }),
+        )
+        .spawn()
+        .await;
+    let app = test_app(&bridge.url).await;
+
+    let response = app.clone().oneshot(get_with_key("/orders/6/status", None)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let status: serde_json::Value = serde_json::from_str(&body_string(response).await).unwrap();
+    assert_eq!(status, serde_json::json!({ "ticket": 6, "state": "filled", "filled_volume": 0.4 }));
+
+    let response = app.clone().oneshot(get_with_key("/orders/5/status", None)).await.unwrap();
+    let status: serde_json::Value = serde_json::from_str(&body_string(response).await).unwrap();
+    assert_eq!(status, serde_json::json!({ "ticket": 5, "state": "pending", "filled_volume": 0.0 }));
+
+    let response = app.oneshot(get_with_key("/orders/7/status", None)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_market_data_for_several_symbols() {
+    let bridge = Router::new()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .route(
+            "/market/{symbol}",
+            get(|Path(symbol): Path<String>| async move {
+                if symbol != "EURUSD" {
+                    return Err(StatusCode::NOT_FOUND);
+                }
+                Ok(Json(serde_json::json!({
+                    "success": true,
+                    "data": {
+                        "symbol": symbol,
+                        "bid": 1.0850,
+                        "ask": 1.0851,
+                        "last": 1.0850,
+                        "volume": 100.0,
+                        "time": 1699113600,
+                        "spread": 1,
+                        "digits": 5,
+                    },
+                })))
+            }),
+        );
+    let app = test_app(&spawn_bridge(bridge).await).await;
+
+    let response = app
+        .oneshot(get_with_key("/market?symbols=EURUSD,%20NOPE", None))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let results: Vec<serde_json::Value> =
+        serde_json::from_str(&body_string(response).await).unwrap();
+
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0]["symbol"], "EURUSD");
+    assert_eq!(results[0]["data"]["bid"], 1.0850);
+    assert!(results[0]["error"].is_null());
+    assert_eq!(results[1]["symbol"], "NOPE");
+    assert!(results[1]["data"].is_null());
+    assert!(results[1]["error"].as_str().unwrap().contains("NOPE"));
+}
+
+#[tokio::test]
+async fn test_openapi_spec_describes_routes() {
+    let bridge = Router::new().route("/health", get(|| async { StatusCode::OK }));
+    let mut settings = test_settings(&spawn_bridge(bridge).await);
+    settings.mt5_api_key = Some("k3y".to_string());
+    let app = app_with_settings(settings).await;
+
+    let response = app.clone().oneshot(get_with_key("/openapi.json", None)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let spec: serde_json::Value = serde_json::from_str(&body_string(response).await).unwrap();
+
+    for path in ["/health", "/status", "/orders", "/positions", "/market/{symbol}"] {
+        assert!(spec["paths"][path].is_object(), "missing {}", path);
+    }
+    assert_eq!(
+        spec["paths"]["/orders"]["post"]["requestBody"]["content"]["application/json"]["schema"]["$ref"],
+        "#/components/schemas/CreateOrderRequest"
+    );
+    let create_order = &spec["components"]["schemas"]["CreateOrderRequest"];
+    assert_eq!(create_order["properties"]["order_type"]["enum"][0], "OP_BUY");
+    assert!(create_order["properties"]["client_order_id"].is_object());
+
+    // Swagger UI is served from the binary, not a CDN
+    let response = app.clone().oneshot(get_with_key("/docs", None)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+    assert_eq!(response.headers()["location"], "docs/");
+    let response = app.clone().oneshot(get_with_key("/docs/", None)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let page = body_string(response).await;
+    assert!(page.contains("swagger-ui"));
+    assert!(!page.contains("unpkg.com"));
+    let response = app.clone().oneshot(get_with_key("/docs/swagger-initializer.js", None)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(body_string(response).await.contains("../openapi.json"));
+    let response = app.oneshot(get_with_key("/docs/swagger-ui.css", None)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "text/css");
+}
+
+/// Shared state of a mock bridge holding one long EURUSD position
+#[derive(Default)]
+struct TrailingBridge {
+    bid: Mutex<f64>,
+    stop_loss: Mutex<Option<f64>>,
+    stop_moves: Mutex<Vec<f64>>,
+    closed: AtomicBool,
+}
+
+fn trailing_bridge(bridge: Arc<TrailingBridge>) -> Router {
+    Router::new()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .route(
+            "/positions/ticket/{ticket}",
+            get(|State(bridge): State<Arc<TrailingBridge>>| async move {
+                if bridge.closed.load(Ordering::SeqCst) {
+                    return Err(StatusCode::NOT_FOUND);
+                }
+                Ok(Json(serde_json::json!({
+                    "success": true,
+                    "data": {
+                        "ticket": 7,
+                        "symbol": "EURUSD",
+                        "type": 0,
+                        "volume": 0.1,
+                        "price_open": 1.1000,
+                        "price_current": *bridge.bid.lock().unwrap(),
+                        "profit": 0.0,
+                        "swap": 0.0,
+                        "commission": 0.0,
+                        "stop_loss": *bridge.stop_loss.lock().unwrap(),
+                        "take_profit": null,
+                        "comment": null,
+                        "magic": 123456,
+                        "time_open": 1699113600,
+                    },
+                })))
+            }),
+        )
+        .route(
+            "/market/{symbol}",
+            get(|State(bridge): State<Arc<TrailingBridge>>| async move {
+                let bid = *bridge.bid.lock().unwrap();
+                Json(serde_json::json!({
+                    "success": true,
+                    "data": {
+                        "symbol": "EURUSD",
+                        "bid": bid,
+                        "ask": bid + 0.0001,
+                        "last": bid,
+                        "volume": 100.0,
+                        "time": 1699113600,
+                        "spread": 1,
+                        "digits": 5,
+                    },
+                }))
+            }),
+        )
+        .route(
+            "/symbols/{symbol}",
+            get(|| async {
+                Json(serde_json::json!({
+                    "success": true,
+                    "data": {
+                        "symbol": "EURUSD",
+                        "digits": 5,
+                        "point": 0.0001,
+                        "tick_size": 0.00001,
+                        "tick_value": 1.0,
+                        "volume_min": 0.01,
+                        "volume_max": 100.0,
+                        "volume_step": 0.01,
+                        "contract_size": 100000.0,
+                        "trade_allowed": true,
+                    },
+                }))
+            }),
+        )
+        .route(
+            "/orders/{ticket}",
+            axum::routing::patch(
+                |State(bridge): State<Arc<TrailingBridge>>, Json(body): Json<serde_json::Value>| async move {
+                    let stop_loss = body["stop_loss"].as_f64().unwrap();
+                    *bridge.stop_loss.lock().unwrap() = Some(stop_loss);
+                    bridge.stop_moves.lock().unwrap().push(stop_loss);
+                    StatusCode::OK
+                },
+            ),
+        )
+        .with_state(bridge)
+}
+
+#[tokio::test]
+async fn test_trailing_stop_only_moves_in_favour() {
+    let bridge = Arc::new(TrailingBridge::default());
+    *bridge.bid.lock().unwrap() = 1.1000;
+    let mut settings = test_settings(&spawn_bridge(trailing_bridge(bridge.clone())).await);
+    settings.mt5_stream_interval_ms = 10;
+    let settings = Arc::new(settings);
+    let mt5_client = Arc::new(MT5Client::new(settings.clone()).await.unwrap());
+    let state = AppState::new(mt5_client, settings);
+    let app = fks_meta::api::router(state.clone());
+
+    let trailing = serde_json::json!({ "distance_points": 20.0, "step_points": 5.0 });
+    let response = app
+        .clone()
+        .oneshot(post_json("/positions/7/trailing-stop", trailing.clone()))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    assert!(state.trailing_stops.is_active(7));
+
+    // Price rises, falls back, then rises past the previous high
+    for bid in [1.1000, 1.1010, 1.1002, 1.1004, 1.1020] {
+        *bridge.bid.lock().unwrap() = bid;
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+
+    // Stops are rounded to the symbol's digits, free of float noise
+    let moves = bridge.stop_moves.lock().unwrap().clone();
+    assert_eq!(moves, [1.0980, 1.0990, 1.1000]);
+
+    let response = app
+        .clone()
+        .oneshot(Request::delete("/positions/7/trailing-stop").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    assert!(!state.trailing_stops.is_active(7));
+
+    // A trailing stop ends by itself once the position closes
+    let response = app
+        .clone()
+        .oneshot(post_json("/positions/7/trailing-stop", trailing.clone()))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    bridge.closed.store(true, Ordering::SeqCst);
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    assert!(!state.trailing_stops.is_active(7));
+
+    let response = app
+        .oneshot(post_json("/positions/7/trailing-stop", trailing))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_ready_when_bridge_connected() {
+    let bridge = Router::new().route("/health", get(|| async { StatusCode::OK }));
+    let mut settings = test_settings(&spawn_bridge(bridge).await);
+    settings.mt5_api_key = Some("secret".to_string());
+    let app = app_with_settings(settings).await;
+
+    let response = app.oneshot(get_with_key("/ready", None)).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value = serde_json::from_str(&body_string(response).await).unwrap();
+    assert_eq!(body["ready"], true);
+    assert_eq!(body["state"], "connected");
+}
+
+#[tokio::test]
+async fn test_not_ready_when_bridge_down() {
+    let bridge = Router::new().route("/health", get(|| async { StatusCode::SERVICE_UNAVAILABLE }));
+    let app = test_app(&spawn_bridge(bridge).await).await;
+
+    let ready = app.clone().oneshot(get_with_key("/ready", None)).await.unwrap();
+    let live = app.oneshot(get_with_key("/health", None)).await.unwrap();
+
+    assert_eq!(ready.status(), StatusCode::SERVICE_UNAVAILABLE);
+    let body: serde_json::Value = serde_json::from_str(&body_string(ready).await).unwrap();
+    assert_eq!(body["ready"], false);
+    // Liveness doesn't depend on the bridge
+    assert_eq!(live.status(), StatusCode::OK);
+}
+
+fn preflight(origin: &str) -> Request<Body> {
+    Request::options("/orders")
+        .header("origin", origin)
+        .header("access-control-request-method", "POST")
+        .header("access-control-request-headers", "content-type,x-api-key")
+        .body(Body::empty())
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_cors_preflight() {
+    let bridge = Router::new().route("/health", get(|| async { StatusCode::OK }));
+    let mut settings = test_settings(&spawn_bridge(bridge).await);
+    settings.mt5_api_key = Some("secret".to_string());
+    settings.mt5_cors_allowed_origins = Some("https://dashboard.example.com, http://localhost:3000".to_string());
+    let cors = cors_layer(&settings).unwrap();
+    let app = app_with_settings(settings).await.layer(cors);
+
+    let allowed = app.clone().oneshot(preflight("http://localhost:3000")).await.unwrap();
+    assert_eq!(allowed.status(), StatusCode::OK);
+    assert_eq!(allowed.headers()["access-control-allow-origin"], "http://localhost:3000");
+    let methods = allowed.headers()["access-control-allow-methods"].to_str().unwrap();
+    assert!(methods.contains("POST"), "{}", methods);
+
+    let disallowed = app.oneshot(preflight("https://evil.example.com")).await.unwrap();
+    assert!(disallowed.headers().get("access-control-allow-origin").is_none());
+}
+
+#[tokio::test]
+async fn test_cors_disabled_without_origins() {
+    let mut settings = Settings::from_env().unwrap();
+    settings.mt5_cors_allowed_origins = None;
+    assert!(cors_layer(&settings).is_none());
+
+    settings.mt5_cors_allowed_origins = Some("*".to_string());
+    let bridge = Router::new().route("/health", get(|| async { StatusCode::OK }));
+    settings.mt5_bridge_url = Some(spawn_bridge(bridge).await);
+    let cors = cors_layer(&settings).unwrap();
+    let app = app_with_settings(settings).await.layer(cors);
+
+    let response = app.oneshot(preflight("https://anywhere.example.com")).await.unwrap();
+    assert_eq!(response.headers()["access-control-allow-origin"], "*");
+}
+
+#[tokio::test]
+async fn test_correlation_id_is_echoed_and_forwarded_to_bridge() {
+    let bridge = MockBridge::new().market(vec![quote("EURUSD", 1.0850, 1.0851)]).spawn().await;
+    let app = test_app(&bridge.url).await;
+
+    let request = Request::get("/market/EURUSD")
+        .header(CORRELATION_ID_HEADER, "exec-42")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[CORRELATION_ID_HEADER], "exec-42");
+
+    let forwarded = bridge.requests.to("GET", "/market/EURUSD");
+    assert_eq!(forwarded.len(), 1);
+    assert_eq!(forwarded[0].headers[CORRELATION_ID_HEADER], "exec-42");
+
+    // Without one, a fresh id is generated and still forwarded
+    let response = app
+        .oneshot(Request::get("/market/EURUSD").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let generated = response.headers()[CORRELATION_ID_HEADER].to_str().unwrap().to_string();
+    assert!(uuid::Uuid::parse_str(&generated).is_ok(), "{}", generated);
+    let forwarded = bridge.requests.to("GET", "/market/EURUSD");
+    assert_eq!(forwarded[1].headers[CORRELATION_ID_HEADER], generated.as_str());
+}
+
+#[tokio::test]
+async fn test_simulate_order_estimates_without_submitting() {
+    let submitted = Arc::new(AtomicBool::new(false));
+    let bridge = Router::new()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .route(
+            "/symbols/{symbol}",
+            get(|| async {
+                Json(serde_json::json!({ "success": true, "data": {
+                    "symbol": "EURUSD", "digits": 5, "point": 0.00001, "tick_size": 0.00001,
+                    "tick_value": 1.0, "volume_min": 0.01, "volume_max": 100.0, "volume_step": 0.01,
+                    "contract_size": 100000.0, "trade_allowed": true,
+                }}))
+            }),
+        )
+        .route(
+            "/account",
+            get(|| async {
+                Json(serde_json::json!({ "success": true, "data": {
+                    "balance": 1000.0, "equity": 1000.0, "margin": 0.0, "free_margin": 1000.0,
+                    "margin_level": 0.0, "currency": "USD", "leverage": 100,
+                }}))
+            }),
+        )
+        .route(
+            "/market/{symbol}",
+            get(|| async {
+                Json(serde_json::json!({ "success": true, "data": {
+                    "symbol": "EURUSD", "bid": 1.0999, "ask": 1.1, "last": 1.0999, "volume": 0.0,
+                    "time": 1699113600, "spread": 10, "digits": 5,
+                }}))
+            }),
+        )
+        .route(
+            "/orders",
+            post({
+                let submitted = submitted.clone();
+                move || async move {
+                    submitted.store(true, Ordering::SeqCst);
+                    StatusCode::OK
+                }
+            }),
+        );
+    let app = test_app(&spawn_bridge(bridge).await).await;
+
+    let order = serde_json::json!({ "symbol": "EURUSD", "order_type": "OP_BUY", "volume": 0.123 });
+    let response = app.oneshot(post_json("/orders/simulate", order)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let estimate: serde_json::Value = serde_json::from_str(&body_string(response).await).unwrap();
+    assert_eq!(estimate["volume"], 0.12);
+    assert_eq!(estimate["price"], 1.1);
+    assert!((estimate["margin_required"].as_f64().unwrap() - 132.0).abs() < 1e-6);
+    assert!((estimate["pip_value"].as_f64().unwrap() - 0.12).abs() < 1e-9);
+    assert_eq!(estimate["sufficient_margin"], true);
+    assert_eq!(estimate["currency"], "USD");
+    assert!(!submitted.load(Ordering::SeqCst));
+}
+
+/// Simulate 0.1 lots of EURJPY for a USD account, with the bridge quoting `quotes`
+async fn simulate_eurjpy(quotes: Vec<serde_json::Value>) -> serde_json::Value {
+    let symbol = envelope(serde_json::json!({
+        "symbol": "EURJPY", "digits": 3, "point": 0.001, "tick_size": 0.001, "tick_value": 0.67,
+        "volume_min": 0.01, "volume_max": 100.0, "volume_step": 0.01, "contract_size": 100000.0,
+        "trade_allowed": true,
+    }));
+    let account = envelope(serde_json::json!({
+        "balance": 1000.0, "equity": 1000.0, "margin": 0.0, "free_margin": 1000.0,
+        "margin_level": 0.0, "currency": "USD", "leverage": 100,
+    }));
+    let bridge = MockBridge::new()
+        .market(quotes)
+        .route("/symbols/{symbol}", get(move || async move { Json(symbol) }))
+        .route("/account", get(move || async move { Json(account) }))
+        .spawn()
+        .await;
+    let app = test_app(&bridge.url).await;
+
+    let order = serde_json::json!({ "symbol": "EURJPY", "order_type": "OP_BUY", "volume": 0.1 });
+    let response = app.oneshot(post_json("/orders/simulate", order)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    serde_json::from_str(&body_string(response).await).unwrap()
+}
+
+#[tokio::test]
+async fn test_simulate_order_converts_margin_to_account_currency() {
+    let estimate = simulate_eurjpy(vec![
+        quote("EURJPY", 160.0, 160.0),
+        quote("USDJPY", 150.0, 150.0),
+    ])
+    .await;
+
+    // 0.1 lots * 100,000 * 160 / 100 = 16,000 JPY, or about 106.67 USD
+    assert!((estimate["margin_required"].as_f64().unwrap() - 16_000.0).abs() < 1e-6);
+    assert!((estimate["margin_required_account"].as_f64().unwrap() - 106.666_666).abs() < 1e-3);
+    assert_eq!(estimate["sufficient_margin"], true);
+    assert_eq!(estimate["currency"], "USD");
+}
+
+#[tokio::test]
+async fn test_simulate_order_without_conversion_rate_leaves_margin_check_unknown() {
+    let estimate = simulate_eurjpy(vec![quote("EURJPY", 160.0, 160.0)]).await;
+
+    assert!((estimate["margin_required"].as_f64().unwrap() - 16_000.0).abs() < 1e-6);
+    assert_eq!(estimate["margin_required_account"], serde_json::Value::Null);
+    assert_eq!(estimate["sufficient_margin"], serde_json::Value::Null);
+}
+
+/// App accepting bridge events signed with `secret`, and the state it dispatches into
+async fn bridge_events_app(secret: &str) -> (Router, AppState) {
+    let bridge = Router::new().route("/health", get(|| async { StatusCode::OK }));
+    let mut settings = test_settings(&spawn_bridge(bridge).await);
+    settings.mt5_api_key = Some("api-key".to_string());
+    settings.mt5_bridge_hmac_secret = Some(secret.to_string());
+    let settings = Arc::new(settings);
+    let mt5_client = Arc::new(MT5Client::new(settings.clone()).await.unwrap());
+    let state = AppState::new(mt5_client, settings);
+    (fks_meta::api::router(state.clone()), state)
+}
+
+fn bridge_event(body: &str, signature: Option<&str>) -> Request<Body> {
+    let mut request = Request::post("/bridge/events").header("content-type", "application/json");
+    if let Some(signature) = signature {
+        request = request.header(SIGNATURE_HEADER, signature);
+    }
+    request.body(Body::from(body.to_string())).unwrap()
+}
+
+const FILL_EVENT: &str = r#"{"type":"fill","ticket":42,"symbol":"EURUSD","volume":0.1,"price":1.085}"#;
+
+#[tokio::test]
+async fn test_bridge_event_with_valid_signature_is_dispatched() {
+    let (app, state) = bridge_events_app("shared").await;
+    let mut events = state.bridge_events.subscribe();
+
+    let signature = sign("shared", FILL_EVENT.as_bytes());
+    let response = app.oneshot(bridge_event(FILL_EVENT, Some(&signature))).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    assert_eq!(
+        events.try_recv().unwrap(),
+        BridgeEvent::Fill { ticket: 42, symbol: "EURUSD".to_string(), volume: 0.1, price: 1.085 }
+    );
+}
+
+#[tokio::test]
+async fn test_bridge_event_with_tampered_body_is_rejected() {
+    let (app, state) = bridge_events_app("shared").await;
+    let mut events = state.bridge_events.subscribe();
+
+    let signature = sign("shared", FILL_EVENT.as_bytes());
+    let tampered = FILL_EVENT.replace("0.1", "10.0");
+    let response = app.clone().oneshot(bridge_event(&tampered, Some(&signature))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let wrong_secret = sign("other", FILL_EVENT.as_bytes());
+    let response = app.oneshot(bridge_event(FILL_EVENT, Some(&wrong_secret))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert!(events.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn test_bridge_event_without_signature_is_rejected() {
+    let (app, state) = bridge_events_app("shared").await;
+    let mut events = state.bridge_events.subscribe();
+
+    let response = app.clone().oneshot(bridge_event(FILL_EVENT, None)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = app.oneshot(bridge_event(FILL_EVENT, Some("not-hex"))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert!(events.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn test_bridge_events_disabled_without_secret() {
+    let bridge = Router::new().route("/health", get(|| async { StatusCode::OK }));
+    let app = test_app(&spawn_bridge(bridge).await).await;
+
+    let signature = sign("shared", FILL_EVENT.as_bytes());
+    let response = app.oneshot(bridge_event(FILL_EVENT, Some(&signature))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_route_prefix() {
+    let bridge = Router::new().route("/health", get(|| async { StatusCode::OK }));
+    let url = spawn_bridge(bridge).await;
+    let mut settings = test_settings(&url);
+    settings.mt5_route_prefix = Some("/mt5/".to_string());
+    settings.mt5_health_at_root = false;
+    let app = app_with_settings(settings.clone()).await;
+
+    for (uri, status) in [
+        ("/mt5/health", StatusCode::OK),
+        ("/mt5/status", StatusCode::OK),
+        ("/health", StatusCode::NOT_FOUND),
+        ("/status", StatusCode::NOT_FOUND),
+    ] {
+        let response = app.clone().oneshot(get_with_key(uri, None)).await.unwrap();
+        assert_eq!(response.status(), status, "{}", uri);
+    }
+
+    let response = app.clone().oneshot(get_with_key("/mt5/openapi.json", None)).await.unwrap();
+    let spec: serde_json::Value = serde_json::from_str(&body_string(response).await).unwrap();
+    assert_eq!(spec["servers"][0]["url"], "/mt5");
+    let response = app.oneshot(get_with_key("/mt5/docs/", None)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Health can stay at the root for orchestrators
+    settings.mt5_health_at_root = true;
+    let app = app_with_settings(settings).await;
+    for uri in ["/health", "/mt5/health", "/ready", "/mt5/ready"] {
+        let response = app.clone().oneshot(get_with_key(uri, None)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK, "{}", uri);
+    }
+    let response = app.oneshot(get_with_key("/status", None)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+/// Bridge position payload for `GET /positions`
+fn bridge_position(ticket: u64, symbol: &str, magic: u32) -> serde_json::Value {
+    serde_json::json!({
+        "ticket": ticket,
+        "symbol": symbol,
+        "type": 0,
+        "volume": 0.1,
+        "price_open": 1.1000,
+        "price_current": 1.1010,
+        "profit": 10.0,
+        "swap": 0.0,
+        "commission": 0.0,
+        "stop_loss": null,
+        "take_profit": null,
+        "comment": null,
+        "magic": magic,
+        "time_open": 1699113600,
+    })
+}
+
+async fn positions_page(app: &Router, uri: &str) -> serde_json::Value {
+    let response = app.clone().oneshot(get_with_key(uri, None)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK, "{}", uri);
+    serde_json::from_str(&body_string(response).await).unwrap()
+}
+
+async fn position_tickets(app: &Router, uri: &str) -> Vec<u64> {
+    let page = positions_page(app, uri).await;
+    page["positions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|position| position["ticket"].as_u64().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_list_positions_filters_by_magic_and_symbol() {
+    let bridge = Router::new()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .route(
+            "/positions",
+            get(|| async {
+                Json(serde_json::json!({
+                    "success": true,
+                    "data": [
+                        bridge_position(1, "EURUSD", 111),
+                        bridge_position(2, "GBPUSD", 111),
+                        bridge_position(3, "EURUSD", 222),
+                    ],
+                }))
+            }),
+        );
+    let app = test_app(&spawn_bridge(bridge).await).await;
+
+    assert_eq!(position_tickets(&app, "/positions").await, vec![1, 2, 3]);
+    assert_eq!(position_tickets(&app, "/positions?magic=111").await, vec![1, 2]);
+    assert_eq!(position_tickets(&app, "/positions?magic=111&symbol=EURUSD").await, vec![1]);
+    assert_eq!(position_tickets(&app, "/positions?magic=222&symbol=GBPUSD").await, Vec::<u64>::new());
+}
+
+/// App whose bridge lists positions with tickets `1..=count`
+async fn app_with_positions(count: u64) -> Router {
+    let positions: Vec<_> = (1..=count).map(|ticket| bridge_position(ticket, "EURUSD", 111)).collect();
+    let bridge = MockBridge::new().route(
+        "/positions",
+        get(move || async move { Json(mock_bridge::envelope(positions.into())) }),
+    );
+    test_app(&bridge.spawn().await.url).await
+}
+
+#[tokio::test]
+async fn test_list_positions_pages() {
+    let app = app_with_positions(5).await;
+
+    let first = positions_page(&app, "/positions?limit=2").await;
+    assert_eq!(first["total"], 5);
+    assert_eq!(first["offset"], 0);
+    assert_eq!(first["limit"], 2);
+    assert_eq!(position_tickets(&app, "/positions?limit=2").await, vec![1, 2]);
+    assert_eq!(position_tickets(&app, "/positions?limit=2&offset=2").await, vec![3, 4]);
+    assert_eq!(position_tickets(&app, "/positions?limit=2&offset=4").await, vec![5]);
+
+    // Without paging every position is returned
+    let all = positions_page(&app, "/positions").await;
+    assert_eq!(all["limit"], 1000);
+    assert_eq!(position_tickets(&app, "/positions").await, vec![1, 2, 3, 4, 5]);
+}
+
+#[tokio::test]
+async fn test_net_position_route() {
+    let app = app_with_positions(3).await;
+
+    let response = app.clone().oneshot(get_with_key("/positions/EURUSD/net", None)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let net: serde_json::Value = serde_json::from_str(&body_string(response).await).unwrap();
+    assert_eq!(net["position_count"], 3);
+    assert!((net["net_volume"].as_f64().unwrap() - 0.3).abs() < 1e-9);
+    assert_eq!(net["side"], "OP_BUY");
+    assert!((net["average_price"].as_f64().unwrap() - 1.1).abs() < 1e-9);
+
+    let response = app.oneshot(get_with_key("/positions/GBPUSD/net", None)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_close_position_by_symbol_and_by_ticket() {
+    let bridge = MockBridge::new()
+        .positions(vec![bridge_position(7, "EURUSD", 111)])
+        .route("/positions/{symbol}", delete(|| async { Json(mock_bridge::envelope(serde_json::json!(null))) }))
+        .spawn()
+        .await;
+    let app = test_app(&bridge.url).await;
+
+    let by_symbol = app
+        .clone()
+        .oneshot(Request::delete("/positions/EURUSD").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let by_ticket = app
+        .clone()
+        .oneshot(Request::delete("/positions/ticket/9").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let missing = app
+        .oneshot(Request::delete("/positions/GBPUSD").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+
+    assert_eq!(by_symbol.status(), StatusCode::NO_CONTENT);
+    assert_eq!(by_ticket.status(), StatusCode::NO_CONTENT);
+    assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+    // The symbol's ticket was resolved before closing
+    let closed: Vec<String> = bridge
+        .requests
+        .all()
+        .into_iter()
+        .filter(|request| request.method == "DELETE")
+        .map(|request| request.path)
+        .collect();
+    assert_eq!(closed, ["/positions/7", "/positions/9"]);
+}
+
+#[tokio::test]
+async fn test_list_positions_offset_out_of_range() {
+    let app = app_with_positions(3).await;
+
+    let page = positions_page(&app, "/positions?offset=10&limit=5").await;
+    assert_eq!(page["total"], 3);
+    assert_eq!(page["positions"], serde_json::json!([]));
+
+    let response = app.oneshot(get_with_key("/positions?limit=0", None)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_order_volume_cap_and_default() {
+    let volumes = Arc::new(Mutex::new(Vec::new()));
+    let bridge = Router::new()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .route(
+            "/orders",
+            post(|State(volumes): State<Arc<Mutex<Vec<f64>>>>, Json(body): Json<serde_json::Value>| async move {
+                volumes.lock().unwrap().push(body["volume"].as_f64().unwrap());
+                Json(serde_json::json!({ "success": true, "data": { "ticket": 5 } }))
+            }),
+        )
+        .with_state(volumes.clone());
+    let mut settings = test_settings(&spawn_bridge(bridge).await);
+    settings.mt5_max_order_volume = Some(1.0);
+    settings.mt5_default_volume = Some(0.2);
+    let app = app_with_settings(settings).await;
+
+    let oversized = serde_json::json!({
+        "symbol": "EURUSD",
+        "order_type": "OP_BUY",
+        "volume": 5.0,
+        "price": 0.0,
+    });
+    let response = app.clone().oneshot(post_json("/orders", oversized)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert!(body_string(response).await.contains("maximum order volume"));
+
+    let unsized_order = serde_json::json!({
+        "symbol": "EURUSD",
+        "order_type": "OP_BUY",
+        "price": 0.0,
+    });
+    let response = app.oneshot(post_json("/orders", unsized_order)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(*volumes.lock().unwrap(), vec![0.2]);
+}
+
+fn get_gzip(uri: &str) -> Request<Body> {
+    Request::get(uri)
+        .header("accept-encoding", "gzip")
+        .body(Body::empty())
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_large_responses_are_gzipped() {
+    let positions: Vec<serde_json::Value> = (1..=20)
+        .map(|ticket| mock_bridge::position(ticket, "EURUSD", 0, 0.1))
+        .collect();
+    let bridge = MockBridge::new().positions(positions).spawn().await;
+    let app = test_app(&bridge.url).await;
+
+    let response = app.clone().oneshot(get_gzip("/positions")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-encoding"], "gzip");
+
+    // Small bodies aren't worth compressing
+    let response = app.oneshot(get_gzip("/health")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers().get("content-encoding").is_none());
+}
@@ -0,0 +1,1806 @@
+//! Integration tests for the MT5 bridge client against a mock bridge
+
+#[path = "../common/mock_bridge.rs"]
+mod mock_bridge;
+
+use axum::{extract::{Path, Query, State}, http::{HeaderMap, StatusCode}, routing::{get, patch, post}, Json, Router};
+use fks_meta::models::{MT5Order, MT5OrderType, MT5Side, OrderState, OrderValidationError, PriceSource};
+use fks_meta::logging::{subscriber, LogFormat};
+use fks_meta::mt5::{
+    BridgeCapabilities, BridgeFeature, CircuitState, ConnectionState, MT5BridgeClient, MT5Error,
+    BRIDGE_VERSION_HEADER, CLIENT_VERSION_HEADER,
+};
+use fks_meta::Settings;
+use mock_bridge::{position, quote, MockBridge};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing_subscriber::EnvFilter;
+
+/// Start a mock bridge on an ephemeral port and return its base URL
+async fn spawn_bridge(app: Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    format!("http://{}", addr)
+}
+
+/// What a mock bridge route recorded, shared with the test
+type Recorded<T> = Arc<Mutex<Vec<T>>>;
+
+fn test_settings(bridge_url: &str) -> Settings {
+    let mut settings = Settings::from_env().unwrap();
+    settings.mt5_bridge_url = Some(bridge_url.to_string());
+    settings.mt5_retry_attempts = 3;
+    settings.mt5_retry_delay_ms = 10;
+    settings
+}
+
+fn test_order() -> MT5Order {
+    MT5Order {
+        ticket: 0,
+        symbol: "EURUSD".to_string(),
+        order_type: MT5OrderType::Buy,
+        volume: 0.1,
+        price: 1.0850,
+        stop_limit: None,
+        stop_loss: None,
+        take_profit: None,
+        comment: None,
+        magic: 123456,
+        expiration: None,
+        client_order_id: None,
+        deviation: None,
+        skip_spread_check: false,
+        state: OrderState::Pending,
+    }
+}
+
+#[tokio::test]
+async fn test_execute_order_retries_transient_errors() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let app = Router::new()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .route(
+            "/orders",
+            post(|State(calls): State<Arc<AtomicUsize>>| async move {
+                if calls.fetch_add(1, Ordering::SeqCst) < 2 {
+                    Err(StatusCode::SERVICE_UNAVAILABLE)
+                } else {
+                    Ok(Json(serde_json::json!({
+                        "success": true,
+                        "data": { "ticket": 42, "retcode": 10009 },
+                    })))
+                }
+            }),
+        )
+        .with_state(calls.clone());
+    let url = spawn_bridge(app).await;
+
+    let client = MT5BridgeClient::new(Arc::new(test_settings(&url))).await.unwrap();
+    let result = client.execute_order(&test_order()).await.unwrap();
+
+    assert_eq!(result.ticket, 42);
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_execute_order_does_not_retry_client_errors() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let app = Router::new()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .route(
+            "/orders",
+            post(|State(calls): State<Arc<AtomicUsize>>| async move {
+                calls.fetch_add(1, Ordering::SeqCst);
+                StatusCode::BAD_REQUEST
+            }),
+        )
+        .with_state(calls.clone());
+    let url = spawn_bridge(app).await;
+
+    let client = MT5BridgeClient::new(Arc::new(test_settings(&url))).await.unwrap();
+
+    assert!(client.execute_order(&test_order()).await.is_err());
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_sub_second_timeout_is_respected() {
+    let app = Router::new()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .route(
+            "/market/{symbol}",
+            get(|Path(_symbol): Path<String>| async {
+                tokio::time::sleep(Duration::from_secs(3)).await;
+                StatusCode::OK
+            }),
+        );
+    let url = spawn_bridge(app).await;
+
+    let mut settings = test_settings(&url);
+    settings.mt5_timeout_ms = 250;
+    settings.mt5_retry_attempts = 1;
+    let client = MT5BridgeClient::new(Arc::new(settings)).await.unwrap();
+
+    let started = Instant::now();
+    let result = client.get_market_data("EURUSD").await;
+    let elapsed = started.elapsed();
+
+    assert!(result.is_err());
+    assert!(elapsed >= Duration::from_millis(200), "aborted too early: {:?}", elapsed);
+    assert!(elapsed < Duration::from_secs(1), "timeout not applied: {:?}", elapsed);
+}
+
+#[tokio::test]
+async fn test_per_call_timeouts() {
+    let slow = || async {
+        tokio::time::sleep(Duration::from_millis(400)).await;
+        Json(serde_json::json!({ "success": true, "data": { "ticket": 9 } }))
+    };
+    let app = Router::new()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .route("/market/{symbol}", get(slow))
+        .route("/orders", post(slow));
+    let mut settings = test_settings(&spawn_bridge(app).await);
+    settings.mt5_timeout_ms = 1000;
+    settings.mt5_market_timeout_ms = Some(100);
+    settings.mt5_order_timeout_ms = Some(2000);
+    settings.mt5_retry_attempts = 1;
+    let client = MT5BridgeClient::new(Arc::new(settings)).await.unwrap();
+
+    let started = Instant::now();
+    let market = client.get_market_data("EURUSD").await;
+    let market_elapsed = started.elapsed();
+    let order = client.execute_order(&test_order()).await;
+
+    assert!(matches!(market, Err(MT5Error::Timeout)), "{:?}", market);
+    assert!(market_elapsed < Duration::from_millis(350), "market timeout not applied: {:?}", market_elapsed);
+    assert_eq!(order.unwrap().ticket, 9);
+}
+
+/// Bridge quoting EURUSD with a 12 point spread
+fn wide_spread_bridge() -> Router {
+    Router::new()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .route(
+            "/market/{symbol}",
+            get(|Path(symbol): Path<String>| async move {
+                Json(serde_json::json!({
+                    "success": true,
+                    "data": {
+                        "symbol": symbol,
+                        "bid": 1.08500,
+                        "ask": 1.08512,
+                        "last": 1.08500,
+                        "volume": 100.0,
+                        "time": 1699113600,
+                        "spread": 12.0,
+                        "digits": 5,
+                    },
+                }))
+            }),
+        )
+        .route(
+            "/orders",
+            post(|| async { Json(serde_json::json!({ "success": true, "data": { "ticket": 9 } })) }),
+        )
+}
+
+async fn client_with_max_spread(max_spread: f64) -> MT5BridgeClient {
+    let mut settings = test_settings(&spawn_bridge(wide_spread_bridge()).await);
+    settings.mt5_max_spread_points = Some(max_spread);
+    MT5BridgeClient::new(Arc::new(settings)).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_spread_within_limit_is_accepted() {
+    let client = client_with_max_spread(20.0).await;
+    assert_eq!(client.execute_order(&test_order()).await.unwrap().ticket, 9);
+}
+
+#[tokio::test]
+async fn test_spread_over_limit_is_rejected() {
+    let client = client_with_max_spread(10.0).await;
+
+    let error = client.execute_order(&test_order()).await.unwrap_err();
+    assert!(
+        matches!(&error, MT5Error::OrderRejected { retcode: None, message } if message.contains("spread of 12 points")),
+        "{:?}",
+        error
+    );
+
+    let mut skipped = test_order();
+    skipped.skip_spread_check = true;
+    assert_eq!(client.execute_order(&skipped).await.unwrap().ticket, 9);
+
+    let mut pending = test_order();
+    pending.order_type = MT5OrderType::BuyLimit;
+    pending.price = 1.0800;
+    assert_eq!(client.execute_order(&pending).await.unwrap().ticket, 9);
+}
+
+#[tokio::test]
+async fn test_modify_order_sends_only_provided_levels() {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let app = Router::new()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .route(
+            "/orders/{ticket}",
+            patch(
+                |State(received): State<Recorded<(u64, serde_json::Value)>>,
+                 Path(ticket): Path<u64>,
+                 Json(body): Json<serde_json::Value>| async move {
+                    received.lock().unwrap().push((ticket, body));
+                    StatusCode::OK
+                },
+            ),
+        )
+        .with_state(received.clone());
+    let url = spawn_bridge(app).await;
+
+    let client = MT5BridgeClient::new(Arc::new(test_settings(&url))).await.unwrap();
+    client.modify_order(42, Some(1.0800), None).await.unwrap();
+    client.modify_order(42, Some(1.0800), Some(1.0900)).await.unwrap();
+
+    let received = received.lock().unwrap();
+    assert_eq!(received[0], (42, serde_json::json!({ "stop_loss": 1.0800 })));
+    assert_eq!(
+        received[1],
+        (42, serde_json::json!({ "stop_loss": 1.0800, "take_profit": 1.0900 }))
+    );
+}
+
+#[tokio::test]
+async fn test_get_order_errors_are_not_rejections() {
+    let app = Router::new()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .route(
+            "/orders/{ticket}",
+            get(|Path(ticket): Path<u64>| async move {
+                match ticket {
+                    1 => StatusCode::NOT_FOUND,
+                    2 => StatusCode::FORBIDDEN,
+                    _ => StatusCode::INTERNAL_SERVER_ERROR,
+                }
+            }),
+        );
+    let mut settings = test_settings(&spawn_bridge(app).await);
+    settings.mt5_retry_attempts = 1;
+    let client = MT5BridgeClient::new(Arc::new(settings)).await.unwrap();
+
+    assert!(matches!(client.get_order(1).await, Err(MT5Error::NotFound(_))));
+    assert!(matches!(client.get_order(2).await, Err(MT5Error::Bridge(_))));
+    assert!(matches!(client.get_order(3).await, Err(MT5Error::BridgeUnavailable(_))));
+}
+
+fn market_data_route() -> Router<Arc<Mutex<Vec<String>>>> {
+    Router::new().route(
+        "/market/{symbol}",
+        get(
+            |State(requested): State<Arc<Mutex<Vec<String>>>>, Path(symbol): Path<String>| async move {
+                requested.lock().unwrap().push(symbol.clone());
+                Json(serde_json::json!({
+                    "success": true,
+                    "data": {
+                        "symbol": symbol,
+                        "bid": 1.0850,
+                        "ask": 1.0851,
+                        "last": 1.0850,
+                        "volume": 100.0,
+                        "time": 1699113600,
+                        "spread": 1.0,
+                        "digits": 5,
+                    },
+                }))
+            },
+        ),
+    )
+}
+
+#[tokio::test]
+async fn test_symbol_prefix_applied_and_stripped() {
+    let bridge = MockBridge::new()
+        .order(serde_json::json!({ "ticket": 1 }))
+        .market(vec![quote("m.EURUSD", 1.0850, 1.0851)])
+        .spawn()
+        .await;
+
+    let mut settings = test_settings(&bridge.url);
+    settings.mt5_symbol_prefix = "m.".to_string();
+    let client = MT5BridgeClient::new(Arc::new(settings)).await.unwrap();
+
+    client.execute_order(&test_order()).await.unwrap();
+    let data = client.get_market_data("EURUSD").await.unwrap();
+
+    assert_eq!(bridge.requests.orders()[0]["symbol"], "m.EURUSD");
+    assert_eq!(bridge.requests.to("GET", "/market/m.EURUSD").len(), 1);
+    assert_eq!(data.symbol, "EURUSD");
+}
+
+#[tokio::test]
+async fn test_empty_symbol_prefix_is_noop() {
+    let requested = Arc::new(Mutex::new(Vec::new()));
+    let app = market_data_route()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .with_state(requested.clone());
+    let url = spawn_bridge(app).await;
+
+    let mut settings = test_settings(&url);
+    settings.mt5_symbol_prefix = String::new();
+    let client = MT5BridgeClient::new(Arc::new(settings)).await.unwrap();
+
+    let data = client.get_market_data("EURUSD").await.unwrap();
+
+    assert_eq!(*requested.lock().unwrap(), vec!["EURUSD"]);
+    assert_eq!(data.symbol, "EURUSD");
+}
+
+#[tokio::test]
+async fn test_symbol_uppercase_normalizes_wire_symbol() {
+    let bridge = MockBridge::new()
+        .order(serde_json::json!({ "ticket": 1 }))
+        .market(vec![quote("m.EURUSD", 1.0850, 1.0851)])
+        .spawn()
+        .await;
+    let mut settings = test_settings(&bridge.url);
+    settings.mt5_symbol_prefix = "m.".to_string();
+    settings.mt5_symbol_uppercase = true;
+    let client = MT5BridgeClient::new(Arc::new(settings)).await.unwrap();
+
+    let mut order = test_order();
+    order.symbol = "eurusd".to_string();
+    client.execute_order(&order).await.unwrap();
+    let data = client.get_market_data("eurusd").await.unwrap();
+
+    assert_eq!(bridge.requests.orders()[0]["symbol"], "m.EURUSD");
+    assert_eq!(bridge.requests.to("GET", "/market/m.EURUSD").len(), 1);
+    assert_eq!(data.symbol, "EURUSD");
+}
+
+#[tokio::test]
+async fn test_symbol_case_untouched_by_default() {
+    let bridge = MockBridge::new()
+        .order(serde_json::json!({ "ticket": 1 }))
+        .spawn()
+        .await;
+    let client = MT5BridgeClient::new(Arc::new(test_settings(&bridge.url))).await.unwrap();
+
+    let mut order = test_order();
+    order.symbol = "eurusd".to_string();
+    client.execute_order(&order).await.unwrap();
+    assert!(client.get_market_data("eurusd").await.is_err());
+
+    assert_eq!(bridge.requests.orders()[0]["symbol"], "eurusd");
+    assert_eq!(bridge.requests.to("GET", "/market/eurusd").len(), 1);
+}
+
+#[tokio::test]
+async fn test_get_candles_deserializes_series() {
+    let query = Arc::new(Mutex::new(HashMap::new()));
+    let app = Router::new()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .route(
+            "/market/{symbol}/candles",
+            get(
+                |State(query): State<Arc<Mutex<HashMap<String, String>>>>,
+                 Path(symbol): Path<String>,
+                 Query(params): Query<HashMap<String, String>>| async move {
+                    assert_eq!(symbol, "EURUSD");
+                    *query.lock().unwrap() = params;
+                    Json(serde_json::json!({
+                        "success": true,
+                        "data": [
+                            { "time": 1700000000, "open": 1.0850, "high": 1.0862, "low": 1.0845, "close": 1.0858, "tick_volume": 412 },
+                            { "time": 1700003600, "open": 1.0858, "high": 1.0871, "low": 1.0851, "close": 1.0866, "tick_volume": 389 },
+                        ],
+                    }))
+                },
+            ),
+        )
+        .with_state(query.clone());
+    let url = spawn_bridge(app).await;
+    let client = MT5BridgeClient::new(Arc::new(test_settings(&url))).await.unwrap();
+
+    let candles = client.get_candles("EURUSD", "H1", 2).await.unwrap();
+
+    assert_eq!(candles.len(), 2);
+    assert_eq!(candles[0].time, 1700000000);
+    assert_eq!(candles[1].close, 1.0866);
+    assert_eq!(candles[1].tick_volume, 389);
+    let query = query.lock().unwrap();
+    assert_eq!(query["timeframe"], "H1");
+    assert_eq!(query["count"], "2");
+}
+
+/// Writer collecting log output in memory
+#[derive(Clone, Default)]
+struct LogBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl std::io::Write for LogBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_bridge_major_version_mismatch_warns() {
+    let client_versions = Arc::new(Mutex::new(Vec::new()));
+    let app = Router::new()
+        .route(
+            "/health",
+            get(
+                |State(seen): State<Arc<Mutex<Vec<String>>>>, headers: HeaderMap| async move {
+                    let version = headers[CLIENT_VERSION_HEADER].to_str().unwrap().to_string();
+                    seen.lock().unwrap().push(version);
+                    ([(BRIDGE_VERSION_HEADER, "99.1.0")], StatusCode::OK)
+                },
+            ),
+        )
+        .with_state(client_versions.clone());
+    let url = spawn_bridge(app).await;
+
+    let logs = LogBuffer::default();
+    let writer = logs.clone();
+    let subscriber = subscriber(LogFormat::Json, "test", EnvFilter::new("warn"), move || writer.clone());
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let client = MT5BridgeClient::new(Arc::new(test_settings(&url))).await.unwrap();
+
+    assert_eq!(*client_versions.lock().unwrap(), vec![fks_meta::PLUGIN_VERSION]);
+    assert_eq!(client.connection_stats().bridge_version.as_deref(), Some("99.1.0"));
+    let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+    let warning: serde_json::Value = serde_json::from_str(logs.lines().next().unwrap()).unwrap();
+    assert_eq!(warning["level"], "WARN");
+    assert_eq!(warning["bridge_version"], "99.1.0");
+    assert_eq!(warning["client_version"], fks_meta::PLUGIN_VERSION);
+}
+
+#[tokio::test]
+async fn test_bridge_same_major_version_does_not_warn() {
+    let major = fks_meta::PLUGIN_VERSION.split('.').next().unwrap();
+    let bridge_version = format!("v{}.99.0", major);
+    let app = Router::new().route(
+        "/health",
+        get(move || async move { ([(BRIDGE_VERSION_HEADER, bridge_version)], StatusCode::OK) }),
+    );
+    let url = spawn_bridge(app).await;
+
+    let logs = LogBuffer::default();
+    let writer = logs.clone();
+    let subscriber = subscriber(LogFormat::Json, "test", EnvFilter::new("warn"), move || writer.clone());
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let client = MT5BridgeClient::new(Arc::new(test_settings(&url))).await.unwrap();
+
+    assert!(client.connection_stats().bridge_version.is_some());
+    assert!(logs.0.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_client_recovers_when_bridge_comes_back() {
+    // Reserve a port with nothing listening on it yet
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+
+    let client = MT5BridgeClient::new(Arc::new(test_settings(&format!("http://{}", addr))))
+        .await
+        .unwrap();
+    assert_eq!(client.connection_state().await, ConnectionState::Reconnecting);
+    assert!(client.execute_order(&test_order()).await.is_err());
+
+    // Bring the bridge up on the same address
+    let app = Router::new()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .route(
+            "/orders",
+            post(|| async { Json(serde_json::json!({ "success": true, "data": { "ticket": 9 } })) }),
+        );
+    let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !client.is_connected().await {
+        assert!(Instant::now() < deadline, "client did not reconnect");
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    assert_eq!(client.connection_state().await, ConnectionState::Connected);
+    assert_eq!(client.execute_order(&test_order()).await.unwrap().ticket, 9);
+}
+
+#[tokio::test]
+async fn test_connection_events_report_disconnect() {
+    let healthy = Arc::new(AtomicBool::new(true));
+    let app = Router::new()
+        .route(
+            "/health",
+            get(|State(healthy): State<Arc<AtomicBool>>| async move {
+                if healthy.load(Ordering::SeqCst) {
+                    StatusCode::OK
+                } else {
+                    StatusCode::SERVICE_UNAVAILABLE
+                }
+            }),
+        )
+        .with_state(healthy.clone());
+    let url = spawn_bridge(app).await;
+    let client = MT5BridgeClient::new(Arc::new(test_settings(&url))).await.unwrap();
+    assert_eq!(client.connection_state().await, ConnectionState::Connected);
+
+    let mut events = client.subscribe_connection_events();
+    healthy.store(false, Ordering::SeqCst);
+    client.reconnect().await;
+
+    let event = tokio::time::timeout(Duration::from_secs(1), events.recv())
+        .await
+        .expect("no connection event")
+        .unwrap();
+    assert_eq!(event.previous, ConnectionState::Connected);
+    assert_eq!(event.state, ConnectionState::Disconnected);
+    let event = events.recv().await.unwrap();
+    assert_eq!(event.previous, ConnectionState::Disconnected);
+    assert_eq!(event.state, ConnectionState::Reconnecting);
+}
+
+#[tokio::test]
+async fn test_order_waits_for_bridge_to_come_back() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+
+    let mut settings = test_settings(&format!("http://{}", addr));
+    settings.mt5_order_queue_wait_ms = 3000;
+    // Keep the background reconnect out of the way so the order's own probes recover it
+    settings.mt5_retry_delay_ms = 10_000;
+    let client = MT5BridgeClient::new(Arc::new(settings)).await.unwrap();
+    assert!(!client.is_connected().await);
+
+    // Bring the bridge up shortly after the order is submitted
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        let app = Router::new()
+            .route("/health", get(|| async { StatusCode::OK }))
+            .route(
+                "/orders",
+                post(|| async { Json(serde_json::json!({ "success": true, "data": { "ticket": 12 } })) }),
+            );
+        let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    let started = Instant::now();
+    let result = client.execute_order(&test_order()).await.unwrap();
+
+    assert_eq!(result.ticket, 12);
+    assert!(started.elapsed() >= Duration::from_millis(250));
+    assert!(client.is_connected().await);
+}
+
+#[tokio::test]
+async fn test_order_queue_wait_is_bounded() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+
+    let mut settings = test_settings(&format!("http://{}", addr));
+    settings.mt5_order_queue_wait_ms = 300;
+    let client = MT5BridgeClient::new(Arc::new(settings)).await.unwrap();
+
+    let started = Instant::now();
+    let result = client.execute_order(&test_order()).await;
+
+    assert!(matches!(result, Err(MT5Error::NotConnected)));
+    let waited = started.elapsed();
+    assert!(waited >= Duration::from_millis(300), "{:?}", waited);
+    assert!(waited < Duration::from_secs(2), "{:?}", waited);
+}
+
+#[tokio::test]
+async fn test_concurrent_orders_share_one_reconnect_probe() {
+    let up = Arc::new(AtomicBool::new(false));
+    let health_hits = Arc::new(AtomicUsize::new(0));
+    let app = Router::new()
+        .route(
+            "/health",
+            get({
+                let up = up.clone();
+                let health_hits = health_hits.clone();
+                move || async move {
+                    health_hits.fetch_add(1, Ordering::SeqCst);
+                    // Slow enough that every order arrives while the probe is in flight
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    if up.load(Ordering::SeqCst) {
+                        StatusCode::OK
+                    } else {
+                        StatusCode::SERVICE_UNAVAILABLE
+                    }
+                }
+            }),
+        )
+        .route(
+            "/orders",
+            post(|| async { Json(serde_json::json!({ "success": true, "data": { "ticket": 9 } })) }),
+        );
+    let mut settings = test_settings(&spawn_bridge(app).await);
+    // Keep the background reconnection out of the way
+    settings.mt5_retry_delay_ms = 60_000;
+    let client = Arc::new(MT5BridgeClient::new(Arc::new(settings)).await.unwrap());
+    assert_eq!(client.connection_state().await, ConnectionState::Reconnecting);
+
+    up.store(true, Ordering::SeqCst);
+    health_hits.store(0, Ordering::SeqCst);
+    let orders = (0..10).map(|_| {
+        let client = client.clone();
+        tokio::spawn(async move { client.execute_order(&test_order()).await })
+    });
+    for order in futures_util::future::join_all(orders).await {
+        assert_eq!(order.unwrap().unwrap().ticket, 9);
+    }
+
+    assert_eq!(health_hits.load(Ordering::SeqCst), 1);
+    assert_eq!(client.connection_state().await, ConnectionState::Connected);
+}
+
+#[tokio::test]
+async fn test_execute_orders_reports_partial_failure() {
+    let app = Router::new()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .route(
+            "/orders/batch",
+            post(|Json(body): Json<serde_json::Value>| async move {
+                assert_eq!(body["orders"].as_array().unwrap().len(), 3);
+                Json(serde_json::json!({
+                    "success": true,
+                    "data": [
+                        { "success": true, "data": { "ticket": 101 } },
+                        { "success": false, "error": "No money" },
+                        { "success": true, "data": { "ticket": 103, "retcode": 10009, "price": 1.0852, "volume": 0.1 } },
+                    ],
+                }))
+            }),
+        );
+    let url = spawn_bridge(app).await;
+
+    let client = MT5BridgeClient::new(Arc::new(test_settings(&url))).await.unwrap();
+    let orders = vec![test_order(), test_order(), test_order()];
+    let results = client.execute_orders(&orders).await.unwrap();
+
+    let tickets: Vec<_> = results.iter().map(|r| r.as_ref().map(|r| r.ticket)).collect();
+    assert_eq!(tickets, vec![Ok(101), Err(&"No money".to_string()), Ok(103)]);
+    assert!(!results[0].as_ref().unwrap().is_filled());
+    let filled = results[2].as_ref().unwrap();
+    assert_eq!((filled.retcode, filled.fill_price), (Some(10009), Some(1.0852)));
+}
+
+fn history_route() -> Router<Recorded<HashMap<String, String>>> {
+    Router::new()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .route(
+            "/history/orders",
+            get(
+                |State(queries): State<Recorded<HashMap<String, String>>>,
+                 Query(query): Query<HashMap<String, String>>| async move {
+                    queries.lock().unwrap().push(query);
+                    let mut order = serde_json::to_value(test_order()).unwrap();
+                    order["ticket"] = 55.into();
+                    Json(serde_json::json!({ "success": true, "data": [order] }))
+                },
+            ),
+        )
+}
+
+#[tokio::test]
+async fn test_get_order_history_unfiltered() {
+    let queries = Arc::new(Mutex::new(Vec::new()));
+    let url = spawn_bridge(history_route().with_state(queries.clone())).await;
+
+    let client = MT5BridgeClient::new(Arc::new(test_settings(&url))).await.unwrap();
+    let orders = client.get_order_history(1_700_000_000, 1_700_086_400, None).await.unwrap();
+
+    assert_eq!(orders.len(), 1);
+    assert_eq!(orders[0].ticket, 55);
+    let queries = queries.lock().unwrap();
+    assert_eq!(queries[0]["from"], "1700000000");
+    assert_eq!(queries[0]["to"], "1700086400");
+    assert!(!queries[0].contains_key("symbol"));
+}
+
+#[tokio::test]
+async fn test_get_order_history_filtered_by_symbol() {
+    let queries = Arc::new(Mutex::new(Vec::new()));
+    let url = spawn_bridge(history_route().with_state(queries.clone())).await;
+
+    let client = MT5BridgeClient::new(Arc::new(test_settings(&url))).await.unwrap();
+    client
+        .get_order_history(1_700_000_000, 1_700_086_400, Some("EURUSD"))
+        .await
+        .unwrap();
+
+    assert_eq!(queries.lock().unwrap()[0]["symbol"], "EURUSD");
+}
+
+#[tokio::test]
+async fn test_bridge_token_sent_as_bearer_header() {
+    let app = Router::new()
+        .route(
+            "/health",
+            get(|headers: HeaderMap| async move {
+                match headers.get("authorization").and_then(|v| v.to_str().ok()) {
+                    Some("Bearer s3cret") => StatusCode::OK,
+                    _ => StatusCode::UNAUTHORIZED,
+                }
+            }),
+        );
+    let url = spawn_bridge(app).await;
+
+    let anonymous = MT5BridgeClient::new(Arc::new(test_settings(&url))).await.unwrap();
+    assert!(!anonymous.is_connected().await);
+
+    let mut settings = test_settings(&url);
+    settings.mt5_bridge_token = Some("s3cret".to_string());
+    let authenticated = MT5BridgeClient::new(Arc::new(settings)).await.unwrap();
+    assert!(authenticated.is_connected().await);
+}
+
+#[tokio::test]
+async fn test_connection_stats_track_recent_requests() {
+    let requested = Arc::new(Mutex::new(Vec::new()));
+    let app = market_data_route()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .with_state(requested);
+    let url = spawn_bridge(app).await;
+
+    let client = MT5BridgeClient::new(Arc::new(test_settings(&url))).await.unwrap();
+    let stats = client.connection_stats();
+    assert_eq!(stats.bridge_url, url);
+    assert_eq!(stats.last_success_ms, None);
+    assert_eq!(stats.avg_latency_ms, None);
+
+    client.get_market_data("EURUSD").await.unwrap();
+    client.get_market_data("GBPUSD").await.unwrap();
+
+    let stats = client.connection_stats();
+    assert!(stats.avg_latency_ms.unwrap() > 0.0);
+    assert!(stats.last_success_ms.unwrap() < 1_000);
+}
+
+/// Mock bridge that fails the first two order submissions and records the
+/// idempotency header and payload key of every attempt
+fn flaky_order_route(seen: Recorded<(String, String)>) -> Router {
+    Router::new()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .route(
+            "/orders",
+            post(
+                |State(seen): State<Recorded<(String, String)>>,
+                 headers: HeaderMap,
+                 Json(body): Json<serde_json::Value>| async move {
+                    let mut seen = seen.lock().unwrap();
+                    seen.push((
+                        headers["idempotency-key"].to_str().unwrap().to_string(),
+                        body["client_order_id"].as_str().unwrap().to_string(),
+                    ));
+                    if seen.len() < 3 {
+                        Err(StatusCode::BAD_GATEWAY)
+                    } else {
+                        Ok(Json(serde_json::json!({ "success": true, "data": { "ticket": 1 } })))
+                    }
+                },
+            ),
+        )
+        .with_state(seen)
+}
+
+#[tokio::test]
+async fn test_idempotency_key_constant_across_retries() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let url = spawn_bridge(flaky_order_route(seen.clone())).await;
+    let client = MT5BridgeClient::new(Arc::new(test_settings(&url))).await.unwrap();
+
+    client.execute_order(&test_order()).await.unwrap();
+
+    let seen = seen.lock().unwrap();
+    assert_eq!(seen.len(), 3);
+    let (header, key) = &seen[0];
+    assert!(!key.is_empty());
+    assert_eq!(header, key);
+    assert!(seen.iter().all(|attempt| attempt == &seen[0]));
+}
+
+#[tokio::test]
+async fn test_caller_supplied_idempotency_key_is_sent() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let url = spawn_bridge(flaky_order_route(seen.clone())).await;
+    let client = MT5BridgeClient::new(Arc::new(test_settings(&url))).await.unwrap();
+
+    let mut order = test_order();
+    order.client_order_id = Some("strategy-7-entry".to_string());
+    client.execute_order(&order).await.unwrap();
+
+    let expected = ("strategy-7-entry".to_string(), "strategy-7-entry".to_string());
+    assert_eq!(*seen.lock().unwrap(), vec![expected; 3]);
+}
+
+#[tokio::test]
+async fn test_dry_run_never_contacts_bridge() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let app = Router::new()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .route(
+            "/orders",
+            post(|State(calls): State<Arc<AtomicUsize>>| async move {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Json(serde_json::json!({ "success": true, "data": { "ticket": 42 } }))
+            }),
+        )
+        .route(
+            "/orders/batch",
+            post(|State(calls): State<Arc<AtomicUsize>>| async move {
+                calls.fetch_add(1, Ordering::SeqCst);
+                StatusCode::INTERNAL_SERVER_ERROR
+            }),
+        )
+        .with_state(calls.clone());
+    let mut settings = test_settings(&spawn_bridge(app).await);
+    settings.mt5_dry_run = true;
+    let client = MT5BridgeClient::new(Arc::new(settings)).await.unwrap();
+
+    let first = client.execute_order(&test_order()).await.unwrap();
+    let second = client.execute_order(&test_order()).await.unwrap();
+    let batch = client.execute_orders(&[test_order()]).await.unwrap();
+
+    assert!(first.dry_run);
+    assert_eq!((first.ticket, second.ticket), (1, 2));
+    assert_eq!(batch[0].as_ref().map(|r| (r.ticket, r.dry_run)), Ok((3, true)));
+    assert_eq!(calls.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+async fn test_dry_run_still_validates() {
+    let app = Router::new().route("/health", get(|| async { StatusCode::OK }));
+    let mut settings = test_settings(&spawn_bridge(app).await);
+    settings.mt5_dry_run = true;
+    let client = MT5BridgeClient::new(Arc::new(settings)).await.unwrap();
+
+    let mut order = test_order();
+    order.volume = 0.0;
+
+    assert!(matches!(
+        client.execute_order(&order).await,
+        Err(MT5Error::InvalidOrder(_))
+    ));
+}
+
+/// Unpriced market order of `order_type` for EURUSD
+fn market_order(order_type: MT5OrderType) -> MT5Order {
+    MT5Order {
+        order_type,
+        price: 0.0,
+        ..test_order()
+    }
+}
+
+#[tokio::test]
+async fn test_market_orders_priced_from_quote_side() {
+    let bridge = MockBridge::new()
+        .order(serde_json::json!({ "ticket": 1 }))
+        .market(vec![quote("EURUSD", 1.0850, 1.0852)])
+        .spawn()
+        .await;
+    let client = MT5BridgeClient::new(Arc::new(test_settings(&bridge.url))).await.unwrap();
+
+    client.execute_order(&market_order(MT5OrderType::Buy)).await.unwrap();
+    client.execute_order(&market_order(MT5OrderType::Sell)).await.unwrap();
+    // A price the caller set is kept
+    client.execute_order(&test_order()).await.unwrap();
+
+    let prices: Vec<f64> = bridge
+        .requests
+        .orders()
+        .iter()
+        .map(|order| order["price"].as_f64().unwrap())
+        .collect();
+    assert_eq!(prices, [1.0852, 1.0850, 1.0850]);
+}
+
+#[tokio::test]
+async fn test_price_source_mid_override() {
+    let bridge = MockBridge::new()
+        .order(serde_json::json!({ "ticket": 1 }))
+        .market(vec![quote("EURUSD", 1.0850, 1.0852)])
+        .spawn()
+        .await;
+    let mut settings = test_settings(&bridge.url);
+    settings.mt5_price_source = PriceSource::Mid;
+    let client = MT5BridgeClient::new(Arc::new(settings)).await.unwrap();
+
+    client.execute_order(&market_order(MT5OrderType::Buy)).await.unwrap();
+    client.execute_order(&market_order(MT5OrderType::Sell)).await.unwrap();
+
+    for order in bridge.requests.orders() {
+        assert!((order["price"].as_f64().unwrap() - 1.0851).abs() < 1e-9, "{}", order);
+    }
+}
+
+#[tokio::test]
+async fn test_dry_run_market_orders_fill_at_quote_side() {
+    let bridge = MockBridge::new()
+        .market(vec![quote("EURUSD", 1.0850, 1.0852)])
+        .spawn()
+        .await;
+    let mut settings = test_settings(&bridge.url);
+    settings.mt5_dry_run = true;
+    let client = MT5BridgeClient::new(Arc::new(settings)).await.unwrap();
+
+    let buy = client.execute_order(&market_order(MT5OrderType::Buy)).await.unwrap();
+    let sell = client.execute_order(&market_order(MT5OrderType::Sell)).await.unwrap();
+
+    assert_eq!(buy.fill_price, Some(1.0852));
+    assert_eq!(sell.fill_price, Some(1.0850));
+    assert!(bridge.requests.orders().is_empty());
+}
+
+/// Bridge order payload as returned by `GET /orders`
+fn bridge_order(ticket: u64, symbol: &str, order_type: &str) -> serde_json::Value {
+    serde_json::json!({
+        "ticket": ticket,
+        "symbol": symbol,
+        "order_type": order_type,
+        "volume": 0.1,
+        "price": 1.0800,
+        "stop_limit": null,
+        "stop_loss": null,
+        "take_profit": null,
+        "comment": "FKS",
+        "magic": 123456,
+        "expiration": null,
+    })
+}
+
+#[tokio::test]
+async fn test_get_orders_deserializes_open_orders() {
+    let app = Router::new()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .route(
+            "/orders",
+            get(|| async {
+                Json(serde_json::json!({
+                    "success": true,
+                    "data": [
+                        bridge_order(1, "m.EURUSD", "OP_BUYLIMIT"),
+                        bridge_order(2, "m.GBPUSD", "OP_SELLSTOP"),
+                        bridge_order(3, "m.EURUSD", "OP_BUYSTOPLIMIT"),
+                    ],
+                }))
+            }),
+        )
+        .route(
+            "/orders/{ticket}",
+            get(|Path(ticket): Path<u64>| async move {
+                Json(serde_json::json!({
+                    "success": true,
+                    "data": bridge_order(ticket, "m.EURUSD", "OP_BUYLIMIT"),
+                }))
+            }),
+        );
+    let mut settings = test_settings(&spawn_bridge(app).await);
+    settings.mt5_symbol_prefix = "m.".to_string();
+    let client = MT5BridgeClient::new(Arc::new(settings)).await.unwrap();
+
+    let orders = client.get_orders().await.unwrap();
+    // A single order is reported under the same symbol as in the list
+    assert_eq!(client.get_order(1).await.unwrap().symbol, "EURUSD");
+
+    let summary: Vec<_> = orders
+        .iter()
+        .map(|order| (order.ticket, order.symbol.as_str(), order.order_type))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            (1, "EURUSD", MT5OrderType::BuyLimit),
+            (2, "GBPUSD", MT5OrderType::SellStop),
+            (3, "EURUSD", MT5OrderType::BuyStopLimit),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_get_position_by_ticket_deserializes_position() {
+    let app = Router::new()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .route(
+            "/positions/ticket/{ticket}",
+            get(|Path(ticket): Path<u64>| async move {
+                if ticket != 42 {
+                    return Err(StatusCode::NOT_FOUND);
+                }
+                Ok(Json(serde_json::json!({
+                    "success": true,
+                    "data": {
+                        "ticket": 42,
+                        "symbol": "m.EURUSD",
+                        "type": 1,
+                        "volume": 0.2,
+                        "price_open": 1.0850,
+                        "price_current": 1.0840,
+                        "profit": 20.0,
+                        "swap": -0.5,
+                        "commission": -1.0,
+                        "stop_loss": 1.0900,
+                        "take_profit": null,
+                        "comment": "FKS",
+                        "magic": 123456,
+                        "time_open": 1700000000,
+                    },
+                })))
+            }),
+        );
+    let mut settings = test_settings(&spawn_bridge(app).await);
+    settings.mt5_symbol_prefix = "m.".to_string();
+    let client = MT5BridgeClient::new(Arc::new(settings)).await.unwrap();
+
+    let position = client.get_position_by_ticket(42).await.unwrap().unwrap();
+    let missing = client.get_position_by_ticket(7).await.unwrap();
+
+    assert_eq!(position.ticket, 42);
+    assert_eq!(position.symbol, "EURUSD");
+    assert_eq!(position.position_type, MT5Side::Sell);
+    assert_eq!(position.volume, 0.2);
+    assert_eq!(position.stop_loss, Some(1.0900));
+    assert_eq!(position.take_profit, None);
+    assert!(missing.is_none());
+}
+
+#[tokio::test]
+async fn test_client_builds_with_custom_pool_settings() {
+    let requested = Arc::new(Mutex::new(Vec::new()));
+    let app = market_data_route()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .with_state(requested.clone());
+    let mut settings = test_settings(&spawn_bridge(app).await);
+    settings.mt5_bridge_pool_max_idle = 1;
+    settings.mt5_bridge_pool_idle_timeout_ms = 250;
+
+    let client = MT5BridgeClient::new(Arc::new(settings)).await.unwrap();
+    client.get_market_data("EURUSD").await.unwrap();
+    // Outlive the idle timeout so the next request needs a fresh connection
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    client.get_market_data("EURUSD").await.unwrap();
+
+    assert!(client.is_connected().await);
+    assert_eq!(requested.lock().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_circuit_breaker_transitions() {
+    let healthy = Arc::new(AtomicBool::new(false));
+    let calls = Arc::new(AtomicUsize::new(0));
+    let app = Router::new()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .route(
+            "/account",
+            get(
+                |State((healthy, calls)): State<(Arc<AtomicBool>, Arc<AtomicUsize>)>| async move {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    if !healthy.load(Ordering::SeqCst) {
+                        return Err(StatusCode::SERVICE_UNAVAILABLE);
+                    }
+                    Ok(Json(serde_json::json!({
+                        "success": true,
+                        "data": {
+                            "balance": 1000.0,
+                            "equity": 1000.0,
+                            "margin": 0.0,
+                            "free_margin": 1000.0,
+                            "margin_level": 0.0,
+                            "currency": "USD",
+                            "leverage": 100,
+                        },
+                    })))
+                },
+            ),
+        )
+        .with_state((healthy.clone(), calls.clone()));
+    let mut settings = test_settings(&spawn_bridge(app).await);
+    settings.mt5_retry_attempts = 1;
+    settings.mt5_circuit_failure_threshold = 2;
+    settings.mt5_circuit_cooldown_ms = 200;
+    let client = MT5BridgeClient::new(Arc::new(settings)).await.unwrap();
+
+    // Closed -> Open after two consecutive failures
+    assert!(client.get_account_info().await.is_err());
+    assert_eq!(client.connection_stats().circuit_state, CircuitState::Closed);
+    assert!(client.get_account_info().await.is_err());
+    assert_eq!(client.connection_stats().circuit_state, CircuitState::Open);
+
+    // Open fails fast without reaching the bridge
+    assert!(matches!(
+        client.get_account_info().await,
+        Err(MT5Error::BridgeUnavailable(_))
+    ));
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+    // Half-open probe that fails re-opens the breaker
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    assert_eq!(client.connection_stats().circuit_state, CircuitState::HalfOpen);
+    assert!(client.get_account_info().await.is_err());
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+    assert_eq!(client.connection_stats().circuit_state, CircuitState::Open);
+
+    // Half-open probe that succeeds closes it
+    healthy.store(true, Ordering::SeqCst);
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    assert_eq!(client.connection_stats().circuit_state, CircuitState::HalfOpen);
+    client.get_account_info().await.unwrap();
+    assert_eq!(client.connection_stats().circuit_state, CircuitState::Closed);
+}
+
+#[tokio::test]
+async fn test_circuit_breaker_recovers_from_dropped_probe() {
+    let healthy = Arc::new(AtomicBool::new(false));
+    let app = Router::new()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .route(
+            "/account",
+            get(|State(healthy): State<Arc<AtomicBool>>| async move {
+                if !healthy.load(Ordering::SeqCst) {
+                    return Err(StatusCode::SERVICE_UNAVAILABLE);
+                }
+                // Healthy again, but slow enough for the caller to give up
+                tokio::time::sleep(Duration::from_millis(500)).await;
+                Ok(Json(serde_json::json!({
+                    "success": true,
+                    "data": {
+                        "balance": 1000.0,
+                        "equity": 1000.0,
+                        "margin": 0.0,
+                        "free_margin": 1000.0,
+                        "margin_level": 0.0,
+                        "currency": "USD",
+                        "leverage": 100,
+                    },
+                })))
+            }),
+        )
+        .with_state(healthy.clone());
+    let mut settings = test_settings(&spawn_bridge(app).await);
+    settings.mt5_retry_attempts = 1;
+    settings.mt5_circuit_failure_threshold = 1;
+    settings.mt5_circuit_cooldown_ms = 200;
+    let client = MT5BridgeClient::new(Arc::new(settings)).await.unwrap();
+
+    assert!(client.get_account_info().await.is_err());
+    assert_eq!(client.connection_stats().circuit_state, CircuitState::Open);
+
+    // The half-open probe is abandoned before the bridge answers
+    healthy.store(true, Ordering::SeqCst);
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    let probe = tokio::time::timeout(Duration::from_millis(50), client.get_account_info()).await;
+    assert!(probe.is_err());
+    assert_eq!(client.connection_stats().circuit_state, CircuitState::Open);
+
+    // The next cooldown admits a fresh probe, which closes the breaker
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    assert_eq!(client.connection_stats().circuit_state, CircuitState::HalfOpen);
+    client.get_account_info().await.unwrap();
+    assert_eq!(client.connection_stats().circuit_state, CircuitState::Closed);
+}
+
+/// Client for a bridge whose market data fails while `healthy` is false
+///
+/// One failure opens the circuit breaker.
+async fn flaky_market_client(healthy: Arc<AtomicBool>, stale_ttl_ms: u64) -> MT5BridgeClient {
+    let app = Router::new()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .route(
+            "/market/{symbol}",
+            get(|State(healthy): State<Arc<AtomicBool>>, Path(symbol): Path<String>| async move {
+                if !healthy.load(Ordering::SeqCst) {
+                    return Err(StatusCode::SERVICE_UNAVAILABLE);
+                }
+                Ok(Json(serde_json::json!({
+                    "success": true,
+                    "data": {
+                        "symbol": symbol,
+                        "bid": 1.0850,
+                        "ask": 1.0851,
+                        "last": 1.0850,
+                        "volume": 100.0,
+                        "time": 1699113600,
+                        "spread": 1.0,
+                        "digits": 5,
+                    },
+                })))
+            }),
+        )
+        .with_state(healthy);
+    let mut settings = test_settings(&spawn_bridge(app).await);
+    settings.mt5_retry_attempts = 1;
+    settings.mt5_circuit_failure_threshold = 1;
+    settings.mt5_circuit_cooldown_ms = 60_000;
+    settings.mt5_market_stale_ttl_ms = stale_ttl_ms;
+    MT5BridgeClient::new(Arc::new(settings)).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_fresh_market_data_is_not_stale() {
+    let client = flaky_market_client(Arc::new(AtomicBool::new(true)), 60_000).await;
+
+    let data = client.get_market_data("EURUSD").await.unwrap();
+
+    assert!(!data.stale);
+    assert_eq!(data.bid, 1.0850);
+}
+
+#[tokio::test]
+async fn test_cached_market_data_served_stale_while_circuit_open() {
+    let healthy = Arc::new(AtomicBool::new(true));
+    let client = flaky_market_client(healthy.clone(), 60_000).await;
+    client.get_market_data("EURUSD").await.unwrap();
+
+    healthy.store(false, Ordering::SeqCst);
+    let data = client.get_market_data("EURUSD").await.unwrap();
+
+    assert_eq!(client.connection_stats().circuit_state, CircuitState::Open);
+    assert!(data.stale);
+    assert_eq!(data.bid, 1.0850);
+    // Symbols never fetched have nothing to fall back on
+    assert!(client.get_market_data("GBPUSD").await.is_err());
+}
+
+#[tokio::test]
+async fn test_expired_market_data_is_not_served() {
+    let healthy = Arc::new(AtomicBool::new(true));
+    let client = flaky_market_client(healthy.clone(), 50).await;
+    client.get_market_data("EURUSD").await.unwrap();
+
+    healthy.store(false, Ordering::SeqCst);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    assert!(client.get_market_data("EURUSD").await.is_err());
+    assert_eq!(client.connection_stats().circuit_state, CircuitState::Open);
+}
+
+/// Bridge serving EURUSD-style symbol info that records every order payload
+async fn symbol_info_bridge(
+    configure: impl FnOnce(&mut Settings),
+) -> (MT5BridgeClient, Recorded<serde_json::Value>, Arc<AtomicUsize>) {
+    let payloads = Arc::new(Mutex::new(Vec::new()));
+    let symbol_lookups = Arc::new(AtomicUsize::new(0));
+    let app = Router::new()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .route(
+            "/symbols/{symbol}",
+            get(
+                |State((_, lookups)): State<(Recorded<serde_json::Value>, Arc<AtomicUsize>)>,
+                 Path(symbol): Path<String>| async move {
+                    lookups.fetch_add(1, Ordering::SeqCst);
+                    Json(serde_json::json!({
+                        "success": true,
+                        "data": {
+                            "symbol": symbol,
+                            "digits": 5,
+                            "point": 0.00001,
+                            "tick_size": 0.00001,
+                            "tick_value": 1.0,
+                            "volume_min": 0.01,
+                            "volume_max": 100.0,
+                            "volume_step": 0.01,
+                            "contract_size": 100000.0,
+                            "trade_allowed": true,
+                        },
+                    }))
+                },
+            ),
+        )
+        .route(
+            "/orders",
+            post(
+                |State((payloads, _)): State<(Recorded<serde_json::Value>, Arc<AtomicUsize>)>,
+                 Json(body): Json<serde_json::Value>| async move {
+                    payloads.lock().unwrap().push(body);
+                    Json(serde_json::json!({ "success": true, "data": { "ticket": 1 } }))
+                },
+            ),
+        )
+        .with_state((payloads.clone(), symbol_lookups.clone()));
+    let mut settings = test_settings(&spawn_bridge(app).await);
+    configure(&mut settings);
+    let client = MT5BridgeClient::new(Arc::new(settings)).await.unwrap();
+    (client, payloads, symbol_lookups)
+}
+
+
+#[tokio::test]
+async fn test_orders_rounded_to_symbol_digits() {
+    let (client, payloads, symbol_lookups) = symbol_info_bridge(|_| {}).await;
+
+    let mut order = test_order();
+    order.price = 1.085012345;
+    order.volume = 0.123;
+    client.execute_order(&order).await.unwrap();
+    client.execute_order(&order).await.unwrap();
+
+    let payloads = payloads.lock().unwrap();
+    assert_eq!(payloads[0]["price"], 1.08501);
+    assert_eq!(payloads[0]["volume"], 0.12);
+    assert_eq!(payloads[1]["price"], 1.08501);
+    // Second order is served from the symbol info cache
+    assert_eq!(symbol_lookups.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_volume_below_symbol_minimum_is_rejected() {
+    let (client, payloads, _) = symbol_info_bridge(|_| {}).await;
+
+    let mut order = test_order();
+    order.volume = 0.006;
+    let err = client.execute_order(&order).await.unwrap_err();
+
+    assert!(matches!(
+        err,
+        MT5Error::InvalidOrder(OrderValidationError::VolumeBelowMinimum { .. })
+    ));
+    assert!(payloads.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_max_order_volume_applies_to_rounded_volume() {
+    let (client, payloads, _) = symbol_info_bridge(|settings| settings.mt5_max_order_volume = Some(0.125)).await;
+
+    let mut order = test_order();
+    order.volume = 0.1251;
+    let err = client.execute_order(&order).await.unwrap_err();
+    assert!(matches!(
+        err,
+        MT5Error::InvalidOrder(OrderValidationError::VolumeAboveMaximum { volume, .. }) if volume == 0.13
+    ));
+
+    order.volume = 0.1249;
+    client.execute_order(&order).await.unwrap();
+    let payloads = payloads.lock().unwrap();
+    assert_eq!(payloads.len(), 1);
+    assert_eq!(payloads[0]["volume"], 0.12);
+}
+
+#[tokio::test]
+async fn test_dry_run_applies_live_volume_checks() {
+    let (client, payloads, _) = symbol_info_bridge(|settings| {
+        settings.mt5_dry_run = true;
+        settings.mt5_max_order_volume = Some(0.125);
+    })
+    .await;
+
+    let mut order = test_order();
+    order.volume = 0.006;
+    let err = client.execute_order(&order).await.unwrap_err();
+    assert!(matches!(
+        err,
+        MT5Error::InvalidOrder(OrderValidationError::VolumeBelowMinimum { .. })
+    ));
+
+    order.volume = 0.1251;
+    let err = client.execute_order(&order).await.unwrap_err();
+    assert!(matches!(
+        err,
+        MT5Error::InvalidOrder(OrderValidationError::VolumeAboveMaximum { .. })
+    ));
+
+    // Accepted orders are rounded as live ones would be, and still not sent
+    order.volume = 0.123;
+    let result = client.execute_order(&order).await.unwrap();
+    assert!(result.dry_run);
+    assert_eq!(result.fill_volume, Some(0.12));
+    assert!(payloads.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_body_logging_preserves_responses() {
+    let requested = Arc::new(Mutex::new(Vec::new()));
+    let app = market_data_route()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .route(
+            "/orders",
+            post(|| async {
+                Json(serde_json::json!({ "success": true, "data": { "ticket": 77 } }))
+            }),
+        )
+        .with_state(requested);
+    let mut settings = test_settings(&spawn_bridge(app).await);
+    settings.mt5_log_bridge_bodies = true;
+    let client = MT5BridgeClient::new(Arc::new(settings)).await.unwrap();
+
+    assert_eq!(client.execute_order(&test_order()).await.unwrap().ticket, 77);
+    assert_eq!(client.get_market_data("EURUSD").await.unwrap().bid, 1.0850);
+}
+
+fn account_json() -> serde_json::Value {
+    serde_json::json!({
+        "balance": 1000.0,
+        "equity": 1010.0,
+        "margin": 50.0,
+        "free_margin": 960.0,
+        "margin_level": 2020.0,
+        "currency": "USD",
+        "leverage": 100,
+    })
+}
+
+#[tokio::test]
+async fn test_bare_data_response_is_accepted() {
+    let bridge = MockBridge::new()
+        .route("/account", get(|| async { Json(account_json()) }))
+        .route("/positions", get(|| async { Json(serde_json::json!([])) }))
+        .spawn()
+        .await;
+    let client = MT5BridgeClient::new(Arc::new(test_settings(&bridge.url))).await.unwrap();
+
+    let account = client.get_account_info().await.unwrap();
+    assert_eq!(account.currency, "USD");
+    assert_eq!(account.leverage, 100);
+    assert!(client.get_positions().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_html_error_body_is_reported_clearly() {
+    let page = "<html>\n  <head><title>502 Bad Gateway</title></head>\n  <body>nginx</body>\n</html>";
+    let bridge = MockBridge::new()
+        .route("/account", get(move || async move { (StatusCode::BAD_GATEWAY, page) }))
+        .spawn()
+        .await;
+    let mut settings = test_settings(&bridge.url);
+    settings.mt5_retry_attempts = 1;
+    let client = MT5BridgeClient::new(Arc::new(settings)).await.unwrap();
+
+    match client.get_account_info().await {
+        Err(MT5Error::Deserialize(message)) => {
+            assert!(message.contains("502"), "{}", message);
+            assert!(message.contains("non-JSON"), "{}", message);
+            assert!(message.contains("<title>502 Bad Gateway</title>"), "{}", message);
+            assert!(!message.contains('\n'), "{}", message);
+        }
+        other => panic!("expected a deserialize error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_unsuccessful_response_without_envelope_keeps_error() {
+    let bridge = MockBridge::new()
+        .route(
+            "/account",
+            get(|| async { (StatusCode::BAD_REQUEST, Json(serde_json::json!({ "error": "terminal not logged in" }))) }),
+        )
+        .spawn()
+        .await;
+    let client = MT5BridgeClient::new(Arc::new(test_settings(&bridge.url))).await.unwrap();
+
+    match client.get_account_info().await {
+        Err(MT5Error::Bridge(message)) => assert!(message.contains("terminal not logged in"), "{}", message),
+        other => panic!("expected a bridge error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_user_agent_and_extra_headers_reach_bridge() {
+    let bridge = MockBridge::new()
+        .route("/account", get(|| async { Json(account_json()) }))
+        .spawn()
+        .await;
+    let mut settings = test_settings(&bridge.url);
+    settings.mt5_bridge_extra_headers = Some("X-Desk: fx, X-Route:eu:1".to_string());
+    let client = MT5BridgeClient::new(Arc::new(settings)).await.unwrap();
+
+    client.get_account_info().await.unwrap();
+
+    let requests = bridge.requests.to("GET", "/account");
+    let headers = &requests[0].headers;
+    assert_eq!(headers["user-agent"], format!("fks_meta/{}", env!("CARGO_PKG_VERSION")).as_str());
+    assert_eq!(headers["x-desk"], "fx");
+    assert_eq!(headers["x-route"], "eu:1");
+}
+
+#[tokio::test]
+async fn test_missing_capability_returns_unsupported() {
+    let bridge = MockBridge::new()
+        .route(
+            "/capabilities",
+            get(|| async { Json(mock_bridge::envelope(serde_json::json!({ "candles": false }))) }),
+        )
+        .route("/market/EURUSD/candles", get(|| async { Json(mock_bridge::envelope(serde_json::json!([]))) }))
+        .route("/account", get(|| async { Json(mock_bridge::envelope(account_json())) }))
+        .spawn()
+        .await;
+    let client = MT5BridgeClient::new(Arc::new(test_settings(&bridge.url))).await.unwrap();
+
+    assert_eq!(
+        client.capabilities().await.unwrap(),
+        BridgeCapabilities { history: true, candles: false, account: true }
+    );
+    match client.get_candles("EURUSD", "H1", 10).await {
+        Err(MT5Error::Unsupported(BridgeFeature::Candles)) => {}
+        other => panic!("expected Unsupported, got {:?}", other),
+    }
+    assert!(bridge.requests.to("GET", "/market/EURUSD/candles").is_empty());
+
+    // Reported features still work, and the capabilities were fetched once
+    assert_eq!(client.get_account_info().await.unwrap().currency, "USD");
+    assert_eq!(bridge.requests.to("GET", "/capabilities").len(), 1);
+}
+
+#[tokio::test]
+async fn test_bridge_without_capabilities_endpoint_supports_everything() {
+    let bridge = MockBridge::new()
+        .route("/market/EURUSD/candles", get(|| async { Json(mock_bridge::envelope(serde_json::json!([]))) }))
+        .spawn()
+        .await;
+    let client = MT5BridgeClient::new(Arc::new(test_settings(&bridge.url))).await.unwrap();
+
+    assert_eq!(client.capabilities().await.unwrap(), BridgeCapabilities::default());
+    assert!(client.get_candles("EURUSD", "H1", 10).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_get_positions_sorted_and_unique() {
+    let mut fresher = position(7, "EURUSD", 0, 0.2);
+    fresher["price_current"] = serde_json::json!(1.0875);
+    let bridge = MockBridge::new()
+        .positions(vec![
+            position(12, "GBPUSD", 1, 0.1),
+            position(7, "EURUSD", 0, 0.2),
+            position(3, "USDJPY", 0, 0.3),
+            position(12, "GBPUSD", 1, 0.1),
+            fresher,
+        ])
+        .spawn()
+        .await;
+    let client = MT5BridgeClient::new(Arc::new(test_settings(&bridge.url))).await.unwrap();
+
+    let positions = client.get_positions().await.unwrap();
+
+    let tickets: Vec<u64> = positions.iter().map(|position| position.ticket).collect();
+    assert_eq!(tickets, [3, 7, 12]);
+    // The later listing of a duplicated ticket wins
+    assert_eq!(positions[1].price_current, 1.0875);
+}
+
+#[tokio::test]
+async fn test_list_symbols_cached_per_group() {
+    let bridge = MockBridge::new()
+        .route(
+            "/symbols",
+            get(|Query(query): Query<HashMap<String, String>>| async move {
+                let symbols = match query.get("group").map(String::as_str) {
+                    Some("metals") => serde_json::json!(["m.XAUUSD"]),
+                    _ => serde_json::json!(["m.EURUSD", "m.XAUUSD"]),
+                };
+                Json(mock_bridge::envelope(symbols))
+            }),
+        )
+        .spawn()
+        .await;
+    let mut settings = test_settings(&bridge.url);
+    settings.mt5_symbol_prefix = "m.".to_string();
+    let client = MT5BridgeClient::new(Arc::new(settings)).await.unwrap();
+
+    assert_eq!(client.list_symbols(None).await.unwrap(), ["EURUSD", "XAUUSD"]);
+    assert_eq!(client.list_symbols(None).await.unwrap(), ["EURUSD", "XAUUSD"]);
+    assert_eq!(bridge.requests.to("GET", "/symbols").len(), 1);
+
+    // Each group is fetched and cached separately
+    assert_eq!(client.list_symbols(Some("metals")).await.unwrap(), ["XAUUSD"]);
+    assert_eq!(client.list_symbols(Some("metals")).await.unwrap(), ["XAUUSD"]);
+    assert_eq!(bridge.requests.to("GET", "/symbols").len(), 2);
+}
+
+#[tokio::test]
+async fn test_retries_counted_by_operation() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let app = Router::new()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .route(
+            "/symbols",
+            get(|State(calls): State<Arc<AtomicUsize>>| async move {
+                if calls.fetch_add(1, Ordering::SeqCst) < 2 {
+                    Err(StatusCode::SERVICE_UNAVAILABLE)
+                } else {
+                    Ok(Json(mock_bridge::envelope(serde_json::json!(["EURUSD"]))))
+                }
+            }),
+        )
+        .with_state(calls.clone());
+    let client = MT5BridgeClient::new(Arc::new(test_settings(&spawn_bridge(app).await)))
+        .await
+        .unwrap();
+    let retries = || fks_meta::metrics::metrics().bridge_retries.get("list_symbols");
+    let before = retries();
+
+    assert_eq!(client.list_symbols(Some("retried")).await.unwrap(), ["EURUSD"]);
+
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+    assert_eq!(retries() - before, 2);
+}
+
+/// Bridge whose `/account` takes `delay` to answer, recording the most
+/// requests it ever had in flight at once
+fn slow_account_bridge(delay: Duration, peak: Arc<AtomicUsize>) -> Router {
+    let in_flight = Arc::new(AtomicUsize::new(0));
+    Router::new()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .route(
+            "/account",
+            get(move || {
+                let in_flight = in_flight.clone();
+                let peak = peak.clone();
+                async move {
+                    let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(delay).await;
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                    Json(mock_bridge::envelope(serde_json::json!({
+                        "balance": 1000.0,
+                        "equity": 1000.0,
+                        "margin": 0.0,
+                        "free_margin": 1000.0,
+                        "margin_level": 0.0,
+                        "currency": "USD",
+                        "leverage": 100,
+                    })))
+                }
+            }),
+        )
+}
+
+#[tokio::test]
+async fn test_concurrent_requests_are_capped() {
+    let peak = Arc::new(AtomicUsize::new(0));
+    let url = spawn_bridge(slow_account_bridge(Duration::from_millis(50), peak.clone())).await;
+    let mut settings = test_settings(&url);
+    settings.mt5_max_concurrent_requests = 2;
+    let client = MT5BridgeClient::new(Arc::new(settings)).await.unwrap();
+
+    let results = futures_util::future::join_all((0..6).map(|_| client.get_account_info())).await;
+
+    assert!(results.iter().all(|result| result.is_ok()));
+    assert_eq!(peak.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_queued_request_fails_as_overloaded() {
+    let peak = Arc::new(AtomicUsize::new(0));
+    let url = spawn_bridge(slow_account_bridge(Duration::from_millis(300), peak)).await;
+    let mut settings = test_settings(&url);
+    settings.mt5_max_concurrent_requests = 1;
+    settings.mt5_request_queue_timeout_ms = 50;
+    let client = MT5BridgeClient::new(Arc::new(settings)).await.unwrap();
+
+    let (first, second) = tokio::join!(client.get_account_info(), client.get_account_info());
+
+    assert!(first.is_ok());
+    assert!(matches!(second, Err(MT5Error::Overloaded)));
+}
+
+/// Bridge whose `/health` answers 503 until `ready` is set
+fn warming_bridge(ready: Arc<AtomicBool>) -> Router {
+    Router::new().route(
+        "/health",
+        get(move || {
+            let ready = ready.clone();
+            async move {
+                if ready.load(Ordering::SeqCst) {
+                    StatusCode::OK
+                } else {
+                    StatusCode::SERVICE_UNAVAILABLE
+                }
+            }
+        }),
+    )
+}
+
+#[tokio::test]
+async fn test_wait_for_bridge_blocks_until_ready() {
+    let ready = Arc::new(AtomicBool::new(false));
+    let mut settings = test_settings(&spawn_bridge(warming_bridge(ready.clone())).await);
+    settings.mt5_wait_for_bridge = true;
+    settings.mt5_bridge_wait_timeout_ms = 5000;
+
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        ready.store(true, Ordering::SeqCst);
+    });
+    let started = Instant::now();
+    let client = MT5BridgeClient::new(Arc::new(settings)).await.unwrap();
+
+    assert!(started.elapsed() >= Duration::from_millis(300));
+    assert_eq!(client.connection_state().await, ConnectionState::Connected);
+}
+
+#[tokio::test]
+async fn test_wait_for_bridge_times_out() {
+    let ready = Arc::new(AtomicBool::new(false));
+    let mut settings = test_settings(&spawn_bridge(warming_bridge(ready)).await);
+    settings.mt5_wait_for_bridge = true;
+    settings.mt5_bridge_wait_timeout_ms = 300;
+
+    let error = MT5BridgeClient::new(Arc::new(settings)).await.err().unwrap();
+    assert!(error.to_string().contains("not ready after 300ms"));
+}
+
+#[tokio::test]
+async fn test_batch_rejects_orders_over_spread_limit() {
+    let bridge = MockBridge::new()
+        .market(vec![quote("EURUSD", 1.08500, 1.08512), quote("GBPUSD", 1.26500, 1.26501)])
+        .route(
+            "/orders/batch",
+            post(|Json(body): Json<serde_json::Value>| async move {
+                let results: Vec<serde_json::Value> = (1..=body["orders"].as_array().unwrap().len())
+                    .map(|ticket| serde_json::json!({ "success": true, "data": { "ticket": ticket } }))
+                    .collect();
+                Json(mock_bridge::envelope(results.into()))
+            }),
+        )
+        .spawn()
+        .await;
+    let mut settings = test_settings(&bridge.url);
+    settings.mt5_max_spread_points = Some(5.0);
+    let client = MT5BridgeClient::new(Arc::new(settings)).await.unwrap();
+
+    let mut gbpusd = test_order();
+    gbpusd.symbol = "GBPUSD".to_string();
+    let mut skipped = test_order();
+    skipped.skip_spread_check = true;
+    let results = client.execute_orders(&[test_order(), gbpusd, skipped]).await.unwrap();
+
+    assert!(results[0].as_ref().unwrap_err().contains("spread of 12 points"));
+    assert_eq!(results[1].as_ref().map(|r| r.ticket), Ok(1));
+    assert_eq!(results[2].as_ref().map(|r| r.ticket), Ok(2));
+    let batch = bridge.requests.to("POST", "/orders/batch").remove(0).body.unwrap();
+    let symbols: Vec<&str> = batch["orders"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|order| order["symbol"].as_str().unwrap())
+        .collect();
+    assert_eq!(symbols, ["GBPUSD", "EURUSD"]);
+}
+
+#[tokio::test]
+async fn test_batch_rejects_invalid_orders_without_sending_them() {
+    let bridge = MockBridge::new()
+        .route(
+            "/orders/batch",
+            post(|Json(body): Json<serde_json::Value>| async move {
+                let results: Vec<serde_json::Value> = (1..=body["orders"].as_array().unwrap().len())
+                    .map(|ticket| serde_json::json!({ "success": true, "data": { "ticket": ticket } }))
+                    .collect();
+                Json(mock_bridge::envelope(results.into()))
+            }),
+        )
+        .spawn()
+        .await;
+    let client = MT5BridgeClient::new(Arc::new(test_settings(&bridge.url))).await.unwrap();
+
+    let mut empty = test_order();
+    empty.volume = 0.0;
+    let mut wrong_side = test_order();
+    wrong_side.stop_loss = Some(1.0900);
+    let results = client.execute_orders(&[empty, test_order(), wrong_side]).await.unwrap();
+
+    assert!(results[0].as_ref().unwrap_err().contains("volume"));
+    assert_eq!(results[1].as_ref().map(|r| r.ticket), Ok(1));
+    assert!(results[2].as_ref().unwrap_err().contains("stop_loss"));
+    let batch = bridge.requests.to("POST", "/orders/batch").remove(0).body.unwrap();
+    assert_eq!(batch["orders"].as_array().unwrap().len(), 1);
+}
@@ -0,0 +1,503 @@
+//! Integration tests for the MT5 client against a mock bridge
+
+#[path = "../common/mock_bridge.rs"]
+mod mock_bridge;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    routing::{delete, get},
+    Json, Router,
+};
+use fks_meta::{MT5Client, Settings};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use fks_meta::OrderState;
+use mock_bridge::{envelope, MockBridge};
+use std::sync::{Arc, Mutex};
+
+/// Start a mock bridge on an ephemeral port and return its base URL
+async fn spawn_bridge(app: Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    format!("http://{}", addr)
+}
+
+async fn test_client(bridge_url: &str) -> MT5Client {
+    let mut settings = Settings::from_env().unwrap();
+    settings.mt5_bridge_url = Some(bridge_url.to_string());
+    settings.mt5_retry_attempts = 1;
+    MT5Client::new(Arc::new(settings)).await.unwrap()
+}
+
+async fn uppercase_client(bridge_url: &str) -> MT5Client {
+    let mut settings = Settings::from_env().unwrap();
+    settings.mt5_bridge_url = Some(bridge_url.to_string());
+    settings.mt5_retry_attempts = 1;
+    settings.mt5_symbol_uppercase = true;
+    MT5Client::new(Arc::new(settings)).await.unwrap()
+}
+
+/// Bridge position payload as returned by `GET /positions`
+fn bridge_position(ticket: u64, symbol: &str, position_type: u32) -> serde_json::Value {
+    serde_json::json!({
+        "ticket": ticket,
+        "symbol": symbol,
+        "type": position_type,
+        "volume": 0.1,
+        "price_open": 1.0850,
+        "price_current": 1.0860,
+        "profit": 10.0,
+        "swap": 0.0,
+        "commission": -0.5,
+        "stop_loss": null,
+        "take_profit": null,
+        "comment": null,
+        "magic": 123456,
+        "time_open": 1699113600,
+    })
+}
+
+/// Mock bridge listing the given positions and failing to close `failing_ticket`
+///
+/// `GET /positions/{symbol}` serves the first position in the symbol.
+fn positions_bridge(
+    positions: Vec<serde_json::Value>,
+    failing_ticket: u64,
+    closed: Arc<Mutex<Vec<u64>>>,
+) -> Router {
+    let by_symbol = positions.clone();
+    Router::new()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .route(
+            "/positions",
+            get(move || async move { Json(serde_json::json!({ "success": true, "data": positions })) }),
+        )
+        .route(
+            "/positions/{ticket}",
+            get(move |Path(symbol): Path<String>| async move {
+                by_symbol
+                    .into_iter()
+                    .find(|position| position["symbol"] == symbol.as_str())
+                    .map(|position| Json(envelope(position)))
+                    .ok_or(StatusCode::NOT_FOUND)
+            })
+            .delete(
+                move |State(closed): State<Arc<Mutex<Vec<u64>>>>, Path(ticket): Path<u64>| async move {
+                    closed.lock().unwrap().push(ticket);
+                    if ticket == failing_ticket {
+                        StatusCode::INTERNAL_SERVER_ERROR
+                    } else {
+                        StatusCode::OK
+                    }
+                },
+            ),
+        )
+        .with_state(closed)
+}
+
+#[tokio::test]
+async fn test_close_all_positions_continues_after_failure() {
+    let closed = Arc::new(Mutex::new(Vec::new()));
+    let positions = vec![
+        bridge_position(1, "EURUSD", 0),
+        bridge_position(2, "GBPUSD", 1),
+        bridge_position(3, "EURUSD", 1),
+    ];
+    let url = spawn_bridge(positions_bridge(positions, 2, closed.clone())).await;
+
+    let client = test_client(&url).await;
+    let results = client.close_all_positions(None).await.unwrap();
+
+    assert_eq!(*closed.lock().unwrap(), vec![1, 2, 3]);
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0], (1, Ok(())));
+    assert!(results[1].1.is_err());
+    assert_eq!(results[2], (3, Ok(())));
+}
+
+#[tokio::test]
+async fn test_close_all_positions_filters_by_symbol() {
+    let closed = Arc::new(Mutex::new(Vec::new()));
+    let positions = vec![
+        bridge_position(1, "EURUSD", 0),
+        bridge_position(2, "GBPUSD", 1),
+        bridge_position(3, "EURUSD", 1),
+    ];
+    let url = spawn_bridge(positions_bridge(positions, 0, closed.clone())).await;
+
+    let client = test_client(&url).await;
+    let results = client.close_all_positions(Some("EURUSD")).await.unwrap();
+
+    assert_eq!(*closed.lock().unwrap(), vec![1, 3]);
+    assert!(results.iter().all(|(_, result)| result.is_ok()));
+}
+
+#[tokio::test]
+async fn test_close_all_positions_matches_uppercased_symbol() {
+    let closed = Arc::new(Mutex::new(Vec::new()));
+    let positions = vec![
+        bridge_position(1, "EURUSD", 0),
+        bridge_position(2, "GBPUSD", 1),
+        bridge_position(3, "EURUSD", 1),
+    ];
+    let url = spawn_bridge(positions_bridge(positions, 0, closed.clone())).await;
+
+    let client = uppercase_client(&url).await;
+    let results = client.close_all_positions(Some("eurusd")).await.unwrap();
+
+    assert_eq!(*closed.lock().unwrap(), vec![1, 3]);
+    assert_eq!(results.len(), 2);
+}
+
+#[tokio::test]
+async fn test_close_position_by_symbol_resolves_ticket() {
+    let closed = Arc::new(Mutex::new(Vec::new()));
+    let positions = vec![bridge_position(1, "EURUSD", 0), bridge_position(2, "GBPUSD", 1)];
+    let url = spawn_bridge(positions_bridge(positions, 0, closed.clone())).await;
+
+    let client = test_client(&url).await;
+
+    assert_eq!(client.close_position_by_symbol("GBPUSD").await.unwrap(), vec![2]);
+    assert_eq!(*closed.lock().unwrap(), vec![2]);
+}
+
+#[tokio::test]
+async fn test_close_position_by_symbol_closes_every_hedged_position() {
+    let closed = Arc::new(Mutex::new(Vec::new()));
+    let positions = vec![
+        bridge_position(1, "EURUSD", 0),
+        bridge_position(2, "GBPUSD", 1),
+        bridge_position(3, "EURUSD", 1),
+    ];
+    let url = spawn_bridge(positions_bridge(positions, 0, closed.clone())).await;
+
+    let client = test_client(&url).await;
+
+    assert_eq!(client.close_position_by_symbol("EURUSD").await.unwrap(), vec![1, 3]);
+    assert_eq!(*closed.lock().unwrap(), vec![1, 3]);
+}
+
+#[tokio::test]
+async fn test_close_position_by_symbol_reports_failed_close() {
+    let closed = Arc::new(Mutex::new(Vec::new()));
+    let positions = vec![bridge_position(1, "EURUSD", 0), bridge_position(3, "EURUSD", 1)];
+    let url = spawn_bridge(positions_bridge(positions, 1, closed.clone())).await;
+
+    let client = test_client(&url).await;
+
+    let error = client.close_position_by_symbol("EURUSD").await.unwrap_err();
+    assert!(error.to_string().contains("position 1"), "{}", error);
+    // The failure doesn't stop the other position from being closed
+    assert_eq!(*closed.lock().unwrap(), vec![1, 3]);
+}
+
+#[tokio::test]
+async fn test_close_position_by_symbol_without_position() {
+    let closed = Arc::new(Mutex::new(Vec::new()));
+    let url = spawn_bridge(positions_bridge(vec![bridge_position(1, "EURUSD", 0)], 0, closed.clone())).await;
+
+    let client = test_client(&url).await;
+
+    match client.close_position_by_symbol("USDJPY").await {
+        Err(fks_meta::MT5Error::NotFound(_)) => {}
+        other => panic!("expected NotFound, got {:?}", other),
+    }
+    assert!(closed.lock().unwrap().is_empty());
+}
+
+/// Mock bridge serving order 5 and counting how often it is fetched
+fn order_bridge(fetches: Arc<AtomicUsize>) -> Router {
+    Router::new()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .route(
+            "/orders/{ticket}",
+            get(|State(fetches): State<Arc<AtomicUsize>>, Path(ticket): Path<u64>| async move {
+                fetches.fetch_add(1, Ordering::SeqCst);
+                Json(serde_json::json!({
+                    "success": true,
+                    "data": {
+                        "ticket": ticket,
+                        "symbol": "EURUSD",
+                        "order_type": "OP_BUYLIMIT",
+                        "volume": 0.1,
+                        "price": 1.0800,
+                        "stop_limit": null,
+                        "stop_loss": null,
+                        "take_profit": null,
+                        "comment": null,
+                        "magic": 123456,
+                        "expiration": null,
+                    },
+                }))
+            })
+            .patch(|| async { StatusCode::OK })
+            .delete(|| async { StatusCode::OK }),
+        )
+        .with_state(fetches)
+}
+
+async fn caching_client(bridge_url: &str, ttl_ms: u64) -> MT5Client {
+    let mut settings = Settings::from_env().unwrap();
+    settings.mt5_bridge_url = Some(bridge_url.to_string());
+    settings.mt5_order_cache_ttl_ms = ttl_ms;
+    MT5Client::new(Arc::new(settings)).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_order_cache_serves_repeat_reads() {
+    let fetches = Arc::new(AtomicUsize::new(0));
+    let url = spawn_bridge(order_bridge(fetches.clone())).await;
+    let client = caching_client(&url, 60_000).await;
+
+    assert_eq!(client.get_order(5).await.unwrap().ticket, 5);
+    assert_eq!(client.get_order(5).await.unwrap().ticket, 5);
+    assert_eq!(fetches.load(Ordering::SeqCst), 1);
+
+    // Other tickets are cached separately
+    client.get_order(6).await.unwrap();
+    assert_eq!(fetches.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_order_cache_invalidated_by_mutations() {
+    let fetches = Arc::new(AtomicUsize::new(0));
+    let url = spawn_bridge(order_bridge(fetches.clone())).await;
+    let client = caching_client(&url, 60_000).await;
+
+    client.get_order(5).await.unwrap();
+    client.modify_order(5, Some(1.0750), None).await.unwrap();
+    client.get_order(5).await.unwrap();
+    assert_eq!(fetches.load(Ordering::SeqCst), 2);
+
+    client.cancel_order(5).await.unwrap();
+    client.get_order(5).await.unwrap();
+    assert_eq!(fetches.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_order_cache_expires_and_can_be_disabled() {
+    let fetches = Arc::new(AtomicUsize::new(0));
+    let url = spawn_bridge(order_bridge(fetches.clone())).await;
+
+    let client = caching_client(&url, 20).await;
+    client.get_order(5).await.unwrap();
+    tokio::time::sleep(std::time::Duration::from_millis(40)).await;
+    client.get_order(5).await.unwrap();
+    assert_eq!(fetches.load(Ordering::SeqCst), 2);
+
+    let client = caching_client(&url, 0).await;
+    client.get_order(5).await.unwrap();
+    client.get_order(5).await.unwrap();
+    assert_eq!(fetches.load(Ordering::SeqCst), 4);
+}
+
+#[tokio::test]
+async fn test_order_cache_purges_stale_tickets() {
+    let fetches = Arc::new(AtomicUsize::new(0));
+    let url = spawn_bridge(order_bridge(fetches.clone())).await;
+    let client = caching_client(&url, 500).await;
+
+    for ticket in 1..=5 {
+        client.get_order(ticket).await.unwrap();
+    }
+    assert_eq!(client.cached_order_count(), 5);
+
+    tokio::time::sleep(std::time::Duration::from_millis(600)).await;
+    client.get_order(6).await.unwrap();
+    assert_eq!(client.cached_order_count(), 1);
+}
+
+#[tokio::test]
+async fn test_concurrent_market_data_requests_are_coalesced() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let app = Router::new()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .route(
+            "/market/{symbol}",
+            get(|State(calls): State<Arc<AtomicUsize>>, Path(symbol): Path<String>| async move {
+                calls.fetch_add(1, Ordering::SeqCst);
+                // Keep the request in flight long enough for every caller to join it
+                tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+                Json(serde_json::json!({
+                    "success": true,
+                    "data": {
+                        "symbol": symbol,
+                        "bid": 1.0850,
+                        "ask": 1.0851,
+                        "last": 1.0850,
+                        "volume": 100.0,
+                        "time": 1699113600,
+                        "spread": 1.0,
+                        "digits": 5,
+                    },
+                }))
+            }),
+        )
+        .with_state(calls.clone());
+    let client = Arc::new(test_client(&spawn_bridge(app).await).await);
+
+    let requests = (0..20).map(|_| {
+        let client = client.clone();
+        tokio::spawn(async move { client.get_market_data("EURUSD").await })
+    });
+    for result in futures_util::future::join_all(requests).await {
+        assert_eq!(result.unwrap().unwrap().bid, 1.0850);
+    }
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    // Once it completes, the next request goes to the bridge again
+    client.get_market_data("EURUSD").await.unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+    // Multi-symbol fetches join requests already in flight
+    let single = tokio::spawn({
+        let client = client.clone();
+        async move { client.get_market_data("EURUSD").await }
+    });
+    tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    let results = client.get_market_data_multi(&["EURUSD", "EURUSD"]).await;
+    assert!(results.iter().all(|(_, result)| result.is_ok()));
+    single.await.unwrap().unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
+
+/// Bridge order payload as returned by `GET /orders`
+fn bridge_order(ticket: u64, symbol: &str, order_type: &str) -> serde_json::Value {
+    serde_json::json!({
+        "ticket": ticket,
+        "symbol": symbol,
+        "order_type": order_type,
+        "volume": 0.1,
+        "price": 1.0800,
+        "magic": 123456,
+    })
+}
+
+/// Mock bridge listing `orders` and failing to cancel `failing_ticket`
+async fn orders_bridge(orders: Vec<serde_json::Value>, failing_ticket: u64) -> mock_bridge::RunningBridge {
+    let list = envelope(serde_json::Value::Array(orders));
+    MockBridge::new()
+        .route("/orders", get(move || async move { Json(list) }))
+        .route(
+            "/orders/{ticket}",
+            delete(move |Path(ticket): Path<u64>| async move {
+                if ticket == failing_ticket {
+                    StatusCode::INTERNAL_SERVER_ERROR
+                } else {
+                    StatusCode::OK
+                }
+            }),
+        )
+        .spawn()
+        .await
+}
+
+fn cancelled_tickets(bridge: &mock_bridge::RunningBridge) -> Vec<String> {
+    bridge
+        .requests
+        .all()
+        .into_iter()
+        .filter(|request| request.method == "DELETE")
+        .map(|request| request.path)
+        .collect()
+}
+
+#[tokio::test]
+async fn test_cancel_all_orders_continues_after_failure() {
+    let orders = vec![
+        bridge_order(11, "EURUSD", "OP_BUYLIMIT"),
+        bridge_order(12, "GBPUSD", "OP_SELLSTOP"),
+        bridge_order(13, "EURUSD", "OP_SELLLIMIT"),
+    ];
+    let bridge = orders_bridge(orders, 12).await;
+
+    let client = test_client(&bridge.url).await;
+    let results = client.cancel_all_orders(None).await.unwrap();
+
+    assert_eq!(cancelled_tickets(&bridge), vec!["/orders/11", "/orders/12", "/orders/13"]);
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0], (11, Ok(())));
+    assert_eq!(results[1].0, 12);
+    assert!(results[1].1.is_err());
+    assert_eq!(results[2], (13, Ok(())));
+}
+
+#[tokio::test]
+async fn test_cancel_all_orders_filters_by_symbol_and_skips_market_orders() {
+    let orders = vec![
+        bridge_order(21, "EURUSD", "OP_BUYLIMIT"),
+        bridge_order(22, "GBPUSD", "OP_BUYSTOP"),
+        bridge_order(23, "EURUSD", "OP_BUY"),
+    ];
+    let bridge = orders_bridge(orders, 0).await;
+
+    let client = test_client(&bridge.url).await;
+    let results = client.cancel_all_orders(Some("EURUSD")).await.unwrap();
+
+    assert_eq!(cancelled_tickets(&bridge), vec!["/orders/21"]);
+    assert_eq!(results, vec![(21, Ok(()))]);
+}
+
+#[tokio::test]
+async fn test_cancel_all_orders_matches_uppercased_symbol() {
+    let orders = vec![
+        bridge_order(31, "EURUSD", "OP_BUYLIMIT"),
+        bridge_order(32, "GBPUSD", "OP_BUYSTOP"),
+    ];
+    let bridge = orders_bridge(orders, 0).await;
+
+    let client = uppercase_client(&bridge.url).await;
+    let results = client.cancel_all_orders(Some("eurusd")).await.unwrap();
+
+    assert_eq!(cancelled_tickets(&bridge), vec!["/orders/31"]);
+    assert_eq!(results, vec![(31, Ok(()))]);
+}
+
+#[tokio::test]
+async fn test_get_order_returns_live_pending_order() {
+    let bridge = MockBridge::new()
+        .route(
+            "/orders/{ticket}",
+            get(|| async { Json(envelope(bridge_order(31, "EURUSD", "OP_BUYLIMIT"))) }),
+        )
+        .spawn()
+        .await;
+
+    let client = test_client(&bridge.url).await;
+    let order = client.get_order(31).await.unwrap();
+
+    assert_eq!(order.ticket, 31);
+    assert_eq!(order.state, OrderState::Pending);
+    assert!(bridge.requests.to("GET", "/history/orders").is_empty());
+}
+
+#[tokio::test]
+async fn test_get_order_falls_back_to_history_once_filled() {
+    let mut cancelled = bridge_order(42, "GBPUSD", "OP_SELLLIMIT");
+    cancelled["state"] = "cancelled".into();
+    let history = envelope(serde_json::json!([
+        bridge_order(41, "EURUSD", "OP_BUY"),
+        cancelled,
+    ]));
+    let bridge = MockBridge::new()
+        .route("/orders/{ticket}", get(|| async { StatusCode::NOT_FOUND }))
+        .route("/history/orders", get(move || async move { Json(history) }))
+        .spawn()
+        .await;
+
+    let client = test_client(&bridge.url).await;
+
+    let filled = client.get_order(41).await.unwrap();
+    assert_eq!(filled.ticket, 41);
+    assert_eq!(filled.state, OrderState::Filled);
+
+    let cancelled = client.get_order(42).await.unwrap();
+    assert_eq!(cancelled.state, OrderState::Cancelled);
+
+    let missing = client.get_order(43).await.unwrap_err();
+    assert!(matches!(missing, fks_meta::MT5Error::NotFound(_)), "{:?}", missing);
+    assert_eq!(bridge.requests.to("GET", "/history/orders").len(), 3);
+}
@@ -1,9 +1,68 @@
 //! Integration tests for MT5 plugin
 
+#[path = "../common/mock_bridge.rs"]
+mod mock_bridge;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    routing::{delete, get, patch, post},
+    Json, Router,
+};
+use fks_meta::mt5::plugin::{
+    order_comment, ExecutionErrorCode, ExecutionPlugin, Order, OrderSide, OrderType, DEFAULT_COMMENT_TEMPLATE,
+    MAX_COMMENT_LEN,
+};
+use fks_meta::logging::{subscriber, LogFormat};
+use fks_meta::models::RejectionClass;
+use fks_meta::{MT5Error, MT5Plugin};
+use mock_bridge::MockBridge;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tracing_subscriber::EnvFilter;
+
+/// Start a mock bridge on an ephemeral port and return its base URL
+async fn spawn_bridge(app: Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    format!("http://{}", addr)
+}
+
+#[test]
+fn test_plugin_health_reports_plugin_mode() {
+    let health = serde_json::to_value(MT5Plugin::new("mt5-primary").health()).unwrap();
+    assert_eq!(health["mode"], "plugin");
+    assert_eq!(health["plugin_name"], "mt5-primary");
+}
+
 #[tokio::test]
 async fn test_plugin_initialization() {
-    // TODO: Implement integration tests
-    // This would require MT5 terminal to be available
+    let health_checks = Arc::new(AtomicUsize::new(0));
+    let app = Router::new()
+        .route(
+            "/health",
+            get(|State(health_checks): State<Arc<AtomicUsize>>| async move {
+                health_checks.fetch_add(1, Ordering::SeqCst);
+                StatusCode::OK
+            }),
+        )
+        .with_state(health_checks.clone());
+    let url = spawn_bridge(app).await;
+
+    let mut plugin = MT5Plugin::new("mt5");
+    plugin
+        .init(serde_json::json!({ "bridge_url": url }))
+        .await
+        .unwrap();
+
+    assert_eq!(health_checks.load(Ordering::SeqCst), 1);
+
+    // Health checks probe the bridge live
+    assert!(plugin.health_check().await.unwrap());
+    assert_eq!(health_checks.load(Ordering::SeqCst), 2);
 }
 
 #[tokio::test]
@@ -16,3 +75,573 @@ async fn test_market_data_fetch() {
     // TODO: Implement market data tests
 }
 
+
+/// Plugin initialized against a bridge that records DELETE requests
+async fn plugin_with_recording_bridge(deleted: Arc<Mutex<Vec<String>>>) -> MT5Plugin {
+    let app = Router::new()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .route(
+            "/orders/{ticket}",
+            delete(|State(deleted): State<Arc<Mutex<Vec<String>>>>, Path(ticket): Path<u64>| async move {
+                deleted.lock().unwrap().push(format!("order {}", ticket));
+                StatusCode::OK
+            }),
+        )
+        .route(
+            "/positions",
+            get(|| async {
+                Json(serde_json::json!({
+                    "success": true,
+                    "data": [{
+                        "ticket": 7,
+                        "symbol": "EURUSD",
+                        "type": 0,
+                        "volume": 0.1,
+                        "price_open": 1.0850,
+                        "price_current": 1.0860,
+                        "profit": 10.0,
+                        "swap": 0.0,
+                        "commission": -0.5,
+                        "stop_loss": null,
+                        "take_profit": null,
+                        "comment": null,
+                        "magic": 123456,
+                        "time_open": 1699113600,
+                    }],
+                }))
+            }),
+        )
+        .route(
+            "/positions/{id}",
+            get(|Path(symbol): Path<String>| async move {
+                if symbol != "EURUSD" {
+                    return Err(StatusCode::NOT_FOUND);
+                }
+                Ok(Json(serde_json::json!({
+                    "success": true,
+                    "data": {
+                        "ticket": 7,
+                        "symbol": symbol,
+                        "type": 0,
+                        "volume": 0.1,
+                        "price_open": 1.0850,
+                        "price_current": 1.0860,
+                        "profit": 10.0,
+                        "swap": 0.0,
+                        "commission": -0.5,
+                        "stop_loss": null,
+                        "take_profit": null,
+                        "comment": null,
+                        "magic": 123456,
+                        "time_open": 1699113600,
+                    },
+                })))
+            })
+            .delete(|State(deleted): State<Arc<Mutex<Vec<String>>>>, Path(ticket): Path<u64>| async move {
+                deleted.lock().unwrap().push(format!("position {}", ticket));
+                StatusCode::OK
+            }),
+        )
+        .with_state(deleted);
+    let url = spawn_bridge(app).await;
+
+    let mut plugin = MT5Plugin::new("mt5");
+    plugin.init(serde_json::json!({ "bridge_url": url })).await.unwrap();
+    plugin
+}
+
+#[tokio::test]
+async fn test_cancel_order() {
+    let deleted = Arc::new(Mutex::new(Vec::new()));
+    let plugin = plugin_with_recording_bridge(deleted.clone()).await;
+
+    plugin.cancel_order("42").await.unwrap();
+    assert!(plugin.cancel_order("not-a-ticket").await.is_err());
+
+    assert_eq!(*deleted.lock().unwrap(), vec!["order 42"]);
+}
+
+#[tokio::test]
+async fn test_close_position() {
+    let deleted = Arc::new(Mutex::new(Vec::new()));
+    let plugin = plugin_with_recording_bridge(deleted.clone()).await;
+
+    plugin.close_position("EURUSD").await.unwrap();
+    let error = plugin.close_position("GBPUSD").await.unwrap_err();
+    assert!(matches!(error.downcast_ref::<MT5Error>(), Some(MT5Error::NotFound(_))), "{}", error);
+
+    assert_eq!(*deleted.lock().unwrap(), vec!["position 7"]);
+}
+
+/// Plugin initialized against a bridge that records order and modify payloads
+async fn plugin_with_order_bridge(received: Arc<Mutex<Vec<serde_json::Value>>>) -> MT5Plugin {
+    let app = Router::new()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .route(
+            "/orders",
+            post(
+                |State(received): State<Arc<Mutex<Vec<serde_json::Value>>>>,
+                 Json(body): Json<serde_json::Value>| async move {
+                    received.lock().unwrap().push(body);
+                    Json(serde_json::json!({ "success": true, "data": { "ticket": 11 } }))
+                },
+            ),
+        )
+        .route(
+            "/orders/{ticket}",
+            patch(
+                |State(received): State<Arc<Mutex<Vec<serde_json::Value>>>>,
+                 Path(ticket): Path<u64>,
+                 Json(body): Json<serde_json::Value>| async move {
+                    received.lock().unwrap().push(serde_json::json!({ "modify": ticket, "body": body }));
+                    StatusCode::OK
+                },
+            ),
+        )
+        .route(
+            "/positions/{symbol}",
+            get(|Path(symbol): Path<String>| async move {
+                Json(serde_json::json!({
+                    "success": true,
+                    "data": {
+                        "ticket": 7,
+                        "symbol": symbol,
+                        "type": 0,
+                        "volume": 0.1,
+                        "price_open": 1.0850,
+                        "price_current": 1.0860,
+                        "profit": 10.0,
+                        "swap": 0.0,
+                        "commission": -0.5,
+                        "stop_loss": null,
+                        "take_profit": null,
+                        "comment": null,
+                        "magic": 123456,
+                        "time_open": 1699113600,
+                    },
+                }))
+            }),
+        )
+        .with_state(received);
+    let url = spawn_bridge(app).await;
+
+    let mut plugin = MT5Plugin::new("mt5");
+    plugin.init(serde_json::json!({ "bridge_url": url })).await.unwrap();
+    plugin
+}
+
+fn fks_order(side: OrderSide, order_type: OrderType, price: Option<f64>) -> Order {
+    Order {
+        symbol: "EURUSD".to_string(),
+        side,
+        order_type,
+        quantity: 0.1,
+        price,
+        stop_price: None,
+        stop_loss: None,
+        take_profit: None,
+        confidence: 0.8,
+    }
+}
+
+#[tokio::test]
+async fn test_stop_limit_order_conversion() {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let plugin = plugin_with_order_bridge(received.clone()).await;
+
+    let mut order = fks_order(OrderSide::Buy, OrderType::StopLimit, Some(1.0870));
+    order.stop_price = Some(1.0880);
+    let result = plugin.execute_order(order).await.unwrap();
+
+    assert!(result.success);
+    let body = received.lock().unwrap()[0].clone();
+    assert_eq!(body["price"], 1.0880);
+    assert_eq!(body["stop_limit"], 1.0870);
+
+    // Missing stop price is reported with the side and type
+    let order = fks_order(OrderSide::Sell, OrderType::StopLimit, Some(1.0870));
+    let error = plugin.execute_order(order).await.unwrap_err().to_string();
+    assert!(error.contains("Sell StopLimit"), "{}", error);
+}
+
+#[test]
+fn test_order_comment_substitutes_placeholders() {
+    let order = fks_order(OrderSide::Sell, OrderType::Market, None);
+
+    assert_eq!(order_comment(DEFAULT_COMMENT_TEMPLATE, &order, false), "FKS order (confidence: 0.8)");
+    assert_eq!(order_comment("{side} {symbol} c={confidence}", &order, false), "Sell EURUSD c=0.8");
+    assert_eq!(order_comment("static", &order, false), "static");
+}
+
+#[test]
+fn test_order_comment_truncates_to_mt5_limit() {
+    let order = fks_order(OrderSide::Buy, OrderType::Market, None);
+
+    let comment = order_comment("strategy-alpha {symbol} {side} confidence {confidence}", &order, false);
+
+    assert_eq!(comment.chars().count(), MAX_COMMENT_LEN);
+    assert_eq!(comment, "strategy-alpha EURUSD Buy confi");
+
+    // The dry-run marker counts towards the limit
+    assert_eq!(order_comment("static", &order, true), "static [dry-run]");
+    let comment = order_comment(DEFAULT_COMMENT_TEMPLATE, &order, true);
+    assert_eq!(comment.chars().count(), MAX_COMMENT_LEN);
+    assert_eq!(comment, "FKS order (confidence: 0.8) [dr");
+}
+
+#[tokio::test]
+async fn test_order_comment_template_from_config() {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let app = Router::new()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .route(
+            "/orders",
+            post(
+                |State(received): State<Arc<Mutex<Vec<serde_json::Value>>>>,
+                 Json(body): Json<serde_json::Value>| async move {
+                    received.lock().unwrap().push(body);
+                    Json(serde_json::json!({ "success": true, "data": { "ticket": 11 } }))
+                },
+            ),
+        )
+        .with_state(received.clone());
+    let url = spawn_bridge(app).await;
+    let mut plugin = MT5Plugin::new("mt5");
+    plugin
+        .init(serde_json::json!({ "bridge_url": url, "order_comment_template": "FKS {side} {symbol}" }))
+        .await
+        .unwrap();
+
+    plugin
+        .execute_order(fks_order(OrderSide::Buy, OrderType::Market, Some(1.0850)))
+        .await
+        .unwrap();
+
+    assert_eq!(received.lock().unwrap()[0]["comment"], "FKS Buy EURUSD");
+}
+
+#[tokio::test]
+async fn test_take_profit_attaches_to_position() {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let plugin = plugin_with_order_bridge(received.clone()).await;
+
+    let order = fks_order(OrderSide::Sell, OrderType::TakeProfit, Some(1.0950));
+    let result = plugin.execute_order(order).await.unwrap();
+
+    assert!(result.success);
+    assert_eq!(result.order_id.as_deref(), Some("7"));
+    assert_eq!(
+        received.lock().unwrap()[0],
+        serde_json::json!({ "modify": 7, "body": { "take_profit": 1.0950 } })
+    );
+}
+
+#[tokio::test]
+async fn test_stop_loss_attaches_to_position() {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let plugin = plugin_with_order_bridge(received.clone()).await;
+
+    let mut order = fks_order(OrderSide::Sell, OrderType::StopLoss, None);
+    order.stop_loss = Some(1.0800);
+    plugin.execute_order(order).await.unwrap();
+
+    assert_eq!(
+        received.lock().unwrap()[0],
+        serde_json::json!({ "modify": 7, "body": { "stop_loss": 1.0800 } })
+    );
+
+    // A Buy stop loss can't protect the (long) mock position
+    let order = fks_order(OrderSide::Buy, OrderType::StopLoss, Some(1.0800));
+    let error = plugin.execute_order(order).await.unwrap_err().to_string();
+    assert!(error.contains("Buy StopLoss"), "{}", error);
+}
+
+#[tokio::test]
+async fn test_dry_run_order_is_not_sent() {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let app = Router::new()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .route(
+            "/orders",
+            post(
+                |State(received): State<Arc<Mutex<Vec<serde_json::Value>>>>,
+                 Json(body): Json<serde_json::Value>| async move {
+                    received.lock().unwrap().push(body);
+                    Json(serde_json::json!({ "success": true, "data": { "ticket": 11 } }))
+                },
+            ),
+        )
+        .with_state(received.clone());
+    let url = spawn_bridge(app).await;
+
+    let mut plugin = MT5Plugin::new("mt5");
+    plugin
+        .init(serde_json::json!({ "bridge_url": url, "dry_run": true }))
+        .await
+        .unwrap();
+    let result = plugin
+        .execute_order(fks_order(OrderSide::Buy, OrderType::Market, None))
+        .await
+        .unwrap();
+
+    assert!(result.success);
+    assert!(result.dry_run);
+    assert_eq!(result.order_id.as_deref(), Some("1"));
+    assert!(received.lock().unwrap().is_empty());
+}
+
+/// Plugin initialized against a bridge that answers orders with `data`
+async fn plugin_with_fill_bridge(data: serde_json::Value) -> MT5Plugin {
+    let url = MockBridge::new().order(data).spawn().await.url;
+
+    let mut plugin = MT5Plugin::new("mt5");
+    plugin
+        .init(serde_json::json!({ "bridge_url": url }))
+        .await
+        .unwrap();
+    plugin
+}
+
+#[tokio::test]
+async fn test_market_order_reports_slipped_fill() {
+    let plugin = plugin_with_fill_bridge(serde_json::json!({
+        "ticket": 21,
+        "retcode": 10009,
+        "price": 1.08537,
+        "volume": 0.1,
+    }))
+    .await;
+
+    let result = plugin
+        .execute_order(fks_order(OrderSide::Buy, OrderType::Market, Some(1.0850)))
+        .await
+        .unwrap();
+
+    assert!(result.success);
+    assert!(!result.dry_run);
+    assert_eq!(result.average_price, 1.08537);
+    assert_eq!(result.filled_quantity, 0.1);
+}
+
+#[tokio::test]
+async fn test_pending_order_reports_no_fill() {
+    let plugin = plugin_with_fill_bridge(serde_json::json!({ "ticket": 22, "retcode": 10008 })).await;
+
+    let result = plugin
+        .execute_order(fks_order(OrderSide::Buy, OrderType::Limit, Some(1.0800)))
+        .await
+        .unwrap();
+
+    assert!(result.success);
+    assert_eq!(result.order_id.as_deref(), Some("22"));
+    assert_eq!(result.average_price, 0.0);
+    assert_eq!(result.filled_quantity, 0.0);
+}
+
+#[tokio::test]
+async fn test_rejected_order_reports_error_code() {
+    let bridge = MockBridge::new()
+        .order_response(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            serde_json::json!({
+                "success": false,
+                "error": "No money",
+                "data": { "ticket": 0, "retcode": 10019 },
+            }),
+        )
+        .spawn()
+        .await;
+    let url = bridge.url;
+    let mut plugin = MT5Plugin::new("mt5");
+    plugin
+        .init(serde_json::json!({ "bridge_url": url }))
+        .await
+        .unwrap();
+
+    let result = plugin
+        .execute_order(fks_order(OrderSide::Buy, OrderType::Market, Some(1.0850)))
+        .await
+        .unwrap();
+
+    assert!(!result.success);
+    assert_eq!(result.error_code, Some(ExecutionErrorCode::Rejected));
+    assert_eq!(result.rejection_class, Some(RejectionClass::InsufficientFunds));
+    assert!(result.error.unwrap().contains("No money"));
+}
+
+#[tokio::test]
+async fn test_invalid_order_reports_error_code() {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let plugin = plugin_with_order_bridge(received.clone()).await;
+
+    let mut order = fks_order(OrderSide::Buy, OrderType::Limit, Some(1.0800));
+    order.stop_loss = Some(1.0900);
+    let result = plugin.execute_order(order).await.unwrap();
+    assert!(!result.success);
+    assert_eq!(result.error_code, Some(ExecutionErrorCode::InvalidOrder));
+    assert_eq!(result.rejection_class, None);
+    assert!(result.error.unwrap().contains("stop_loss"));
+
+    let mut order = fks_order(OrderSide::Sell, OrderType::Market, None);
+    order.quantity = -1.0;
+    let result = plugin.execute_order(order).await.unwrap();
+    assert_eq!(result.error_code, Some(ExecutionErrorCode::InvalidOrder));
+
+    assert!(received.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_fill_is_posted_to_webhook() {
+    let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let app = Router::new()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .route(
+            "/orders",
+            post(|| async {
+                Json(serde_json::json!({
+                    "success": true,
+                    "data": { "ticket": 31, "retcode": 10009, "price": 1.0851, "volume": 0.1 },
+                }))
+            }),
+        )
+        .route(
+            "/fills",
+            post(move |Json(body): Json<serde_json::Value>| async move {
+                // Fail the first delivery so the retry is exercised
+                if attempts.fetch_add(1, Ordering::SeqCst) == 0 {
+                    return StatusCode::SERVICE_UNAVAILABLE;
+                }
+                sender.send(body).unwrap();
+                StatusCode::OK
+            }),
+        );
+    let url = spawn_bridge(app).await;
+    let mut plugin = MT5Plugin::new("mt5");
+    plugin
+        .init(serde_json::json!({ "bridge_url": url, "fill_webhook_url": format!("{}/fills", url) }))
+        .await
+        .unwrap();
+
+    let result = plugin
+        .execute_order(fks_order(OrderSide::Buy, OrderType::Market, Some(1.0850)))
+        .await
+        .unwrap();
+    assert!(result.success);
+
+    let payload = tokio::time::timeout(std::time::Duration::from_secs(5), receiver.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(payload["success"], true);
+    assert_eq!(payload["order_id"], "31");
+    assert_eq!(payload["filled_quantity"], 0.1);
+    assert_eq!(payload["average_price"], 1.0851);
+    assert_eq!(payload["error_code"], serde_json::Value::Null);
+}
+
+#[derive(Clone, Default)]
+struct LogBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl std::io::Write for LogBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_shutdown_waits_for_fill_delivery() {
+    let delivered = Arc::new(AtomicUsize::new(0));
+    let counter = delivered.clone();
+    let bridge = MockBridge::new()
+        .order(serde_json::json!({ "ticket": 32, "retcode": 10009, "price": 1.0851, "volume": 0.1 }))
+        .route(
+            "/hooks/secret-token",
+            post(move || async move {
+                tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+                counter.fetch_add(1, Ordering::SeqCst);
+                StatusCode::OK
+            }),
+        )
+        .spawn()
+        .await;
+    let url = bridge.url;
+
+    let logs = LogBuffer::default();
+    let writer = logs.clone();
+    let subscriber = subscriber(LogFormat::Json, "test", EnvFilter::new("info"), move || writer.clone());
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let mut plugin = MT5Plugin::new("mt5");
+    plugin
+        .init(serde_json::json!({ "bridge_url": url, "fill_webhook_url": format!("{}/hooks/secret-token", url) }))
+        .await
+        .unwrap();
+    let result = plugin
+        .execute_order(fks_order(OrderSide::Buy, OrderType::Market, Some(1.0850)))
+        .await
+        .unwrap();
+    assert!(result.filled_quantity > 0.0);
+
+    assert!(plugin.shutdown().await);
+    assert_eq!(delivered.load(Ordering::SeqCst), 1);
+
+    // Only the receiver's origin is logged; its path is the secret
+    let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+    assert!(logs.contains("Fill webhook delivered"), "{}", logs);
+    assert!(!logs.contains("secret-token"), "{}", logs);
+}
+
+#[tokio::test]
+async fn test_dry_run_fill_is_not_posted_to_webhook() {
+    let deliveries = Arc::new(AtomicUsize::new(0));
+    let counter = deliveries.clone();
+    let bridge = MockBridge::new()
+        .market(vec![mock_bridge::quote("EURUSD", 1.0850, 1.0851)])
+        .route(
+            "/fills",
+            post(move || async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                StatusCode::OK
+            }),
+        )
+        .spawn()
+        .await;
+    let url = bridge.url;
+    let mut plugin = MT5Plugin::new("mt5");
+    plugin
+        .init(serde_json::json!({
+            "bridge_url": url,
+            "dry_run": true,
+            "fill_webhook_url": format!("{}/fills", url),
+        }))
+        .await
+        .unwrap();
+
+    let result = plugin
+        .execute_order(fks_order(OrderSide::Buy, OrderType::Market, None))
+        .await
+        .unwrap();
+    assert!(result.success);
+    assert!(result.filled_quantity > 0.0);
+
+    tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+    assert_eq!(deliveries.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+async fn test_init_rejects_invalid_settings() {
+    let mut plugin = MT5Plugin::new("mt5");
+    let error = plugin
+        .init(serde_json::json!({ "bridge_url": "http://127.0.0.1:9", "timeout_ms": 0 }))
+        .await
+        .unwrap_err();
+
+    assert!(error.to_string().contains("MT5_TIMEOUT_MS"), "{}", error);
+    assert!(!plugin.health_check().await.unwrap());
+}
@@ -0,0 +1,68 @@
+//! Unit tests for log output formats
+
+use fks_meta::logging::{subscriber, LogFormat};
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+use tracing::info;
+use tracing_subscriber::EnvFilter;
+
+/// Writer collecting log output in memory
+#[derive(Clone, Default)]
+struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Buffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Buffer {
+    fn contents(&self) -> String {
+        String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+    }
+}
+
+fn log_with(format: LogFormat) -> String {
+    let buffer = Buffer::default();
+    let writer = buffer.clone();
+    let subscriber = subscriber(format, "fks_meta_test", EnvFilter::new("info"), move || writer.clone());
+    tracing::subscriber::with_default(subscriber, || {
+        info!(ticket = 42u64, symbol = "EURUSD", filled = true, "Order executed successfully");
+    });
+    buffer.contents()
+}
+
+#[test]
+fn test_log_format_parses() {
+    assert_eq!("json".parse::<LogFormat>(), Ok(LogFormat::Json));
+    assert_eq!("TEXT".parse::<LogFormat>(), Ok(LogFormat::Text));
+    assert!("xml".parse::<LogFormat>().is_err());
+    assert_eq!(LogFormat::default(), LogFormat::Text);
+}
+
+#[test]
+fn test_text_subscriber_logs() {
+    let output = log_with(LogFormat::Text);
+    assert!(output.contains("Order executed successfully"), "{}", output);
+    assert!(output.contains("ticket") && output.contains("42"), "{}", output);
+}
+
+#[test]
+fn test_json_subscriber_logs_structured_fields() {
+    let output = log_with(LogFormat::Json);
+    let line: serde_json::Value = serde_json::from_str(output.trim()).unwrap();
+
+    assert_eq!(line["message"], "Order executed successfully");
+    assert_eq!(line["level"], "INFO");
+    assert_eq!(line["service"], "fks_meta_test");
+    assert_eq!(line["version"], fks_meta::PLUGIN_VERSION);
+    assert_eq!(line["ticket"], 42);
+    assert_eq!(line["symbol"], "EURUSD");
+    assert_eq!(line["filled"], true);
+    assert!(line["timestamp"].is_string());
+}
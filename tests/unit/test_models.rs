@@ -1,20 +1,30 @@
 //! Unit tests for models
 
-use fks_meta::models::{MT5Order, MT5Position, MT5MarketData};
+use fks_meta::models::{
+    classify_rejection, epoch_to_utc, estimate_margin, net_position, retcode_description, summarize, MT5AccountInfo,
+    MT5MarketData, MT5Order, MT5OrderType, MT5Position, MT5Side, MT5SymbolInfo, MT5TradeType, OrderState,
+    OrderValidationError, RejectionClass, TRADE_ACTION_DEAL, TRADE_ACTION_PENDING,
+};
+use fks_meta::MT5Error;
 
 #[test]
 fn test_mt5_order_serialization() {
     let order = MT5Order {
         ticket: 12345,
         symbol: "EURUSD".to_string(),
-        order_type: "OP_BUY".to_string(),
+        order_type: MT5OrderType::Buy,
         volume: 0.1,
         price: 1.0850,
+        stop_limit: None,
         stop_loss: Some(1.0800),
         take_profit: Some(1.0900),
         comment: Some("Test order".to_string()),
         magic: 123456,
         expiration: None,
+        client_order_id: None,
+        deviation: None,
+        skip_spread_check: false,
+        state: OrderState::Pending,
     };
     
     let json = serde_json::to_string(&order).unwrap();
@@ -29,7 +39,7 @@ fn test_mt5_position_serialization() {
     let position = MT5Position {
         ticket: 12345,
         symbol: "EURUSD".to_string(),
-        position_type: "OP_BUY".to_string(),
+        position_type: MT5Side::Buy,
         volume: 0.1,
         price_open: 1.0850,
         price_current: 1.0860,
@@ -50,3 +60,669 @@ fn test_mt5_position_serialization() {
     assert_eq!(deserialized.profit, 10.0);
 }
 
+
+#[test]
+fn test_mt5_order_type_round_trip() {
+    for order_type in MT5OrderType::ALL {
+        let json = serde_json::to_string(&order_type).unwrap();
+        assert_eq!(json, format!("\"{}\"", order_type.as_str()));
+
+        let deserialized: MT5OrderType = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, order_type);
+        assert_eq!(order_type.as_str().parse::<MT5OrderType>().unwrap(), order_type);
+    }
+}
+
+#[test]
+fn test_mt5_order_type_rejects_unknown_code() {
+    assert!("OP_BUYY".parse::<MT5OrderType>().is_err());
+    assert!(MT5OrderType::try_from("op_buy").is_err());
+    assert!(serde_json::from_str::<MT5OrderType>("\"OP_HOLD\"").is_err());
+}
+
+#[test]
+fn test_mt5_account_info_deserialization() {
+    let json = r#"{
+        "balance": 10000.0,
+        "equity": 10125.5,
+        "margin": 217.0,
+        "free_margin": 9908.5,
+        "margin_level": 4666.13,
+        "currency": "USD",
+        "leverage": 500
+    }"#;
+
+    let account: MT5AccountInfo = serde_json::from_str(json).unwrap();
+
+    assert_eq!(account.balance, 10000.0);
+    assert_eq!(account.free_margin, 9908.5);
+    assert_eq!(account.currency, "USD");
+    assert_eq!(account.leverage, 500);
+}
+
+fn eurusd_info() -> MT5SymbolInfo {
+    MT5SymbolInfo {
+        symbol: "EURUSD".to_string(),
+        digits: 5,
+        point: 0.00001,
+        tick_size: 0.00001,
+        tick_value: 1.0,
+        volume_min: 0.01,
+        volume_max: 100.0,
+        volume_step: 0.01,
+        contract_size: 100000.0,
+        trade_allowed: true,
+    }
+}
+
+#[test]
+fn test_normalize_volume_rounds_to_step() {
+    let info = eurusd_info();
+
+    assert_eq!(info.normalize_volume(0.1).unwrap(), 0.1);
+    assert_eq!(info.normalize_volume(0.123).unwrap(), 0.12);
+    assert_eq!(info.normalize_volume(0.125000001).unwrap(), 0.13);
+    assert_eq!(info.normalize_volume(0.3).unwrap(), 0.3);
+    assert_eq!(info.normalize_volume(1.0 / 3.0).unwrap(), 0.33);
+}
+
+#[test]
+fn test_estimate_margin_across_leverages() {
+    let info = eurusd_info();
+
+    // 1 lot of EURUSD = 100,000 units at 1.1 = 110,000 notional
+    assert!((estimate_margin(1.0, 1.1, &info, 100) - 1100.0).abs() < 1e-9);
+    assert!((estimate_margin(1.0, 1.1, &info, 500) - 220.0).abs() < 1e-9);
+    assert!((estimate_margin(0.1, 1.1, &info, 30) - 366.666_666_666).abs() < 1e-6);
+    assert!((estimate_margin(1.0, 1.1, &info, 1) - 110_000.0).abs() < 1e-6);
+    // Unknown leverage is treated as 1:1 rather than dividing by zero
+    assert_eq!(estimate_margin(1.0, 1.1, &info, 0), estimate_margin(1.0, 1.1, &info, 1));
+}
+
+#[test]
+fn test_pip_value_per_point() {
+    let mut info = eurusd_info();
+    assert!((info.pip_value(1.0) - 1.0).abs() < 1e-12);
+    assert!((info.pip_value(0.5) - 0.5).abs() < 1e-12);
+
+    // Ticks of 5 points are worth 5 each, so one point is still 1
+    info.tick_size = 0.00005;
+    info.tick_value = 5.0;
+    assert!((info.pip_value(1.0) - 1.0).abs() < 1e-9);
+
+    info.tick_size = 0.0;
+    assert_eq!(info.pip_value(1.0), 0.0);
+}
+
+#[test]
+fn test_normalize_volume_accepts_bounds() {
+    let mut info = eurusd_info();
+
+    assert_eq!(info.normalize_volume(100.0).unwrap(), 100.0);
+    assert_eq!(info.normalize_volume(0.01).unwrap(), 0.01);
+
+    info.volume_min = 0.1;
+    info.volume_step = 0.1;
+    assert_eq!(info.normalize_volume(0.7000000001).unwrap(), 0.7);
+    // Float noise just under the minimum still counts as the minimum
+    assert_eq!(info.normalize_volume(0.1 - 1e-12).unwrap(), 0.1);
+}
+
+#[test]
+fn test_normalize_volume_rejects_out_of_bounds() {
+    let mut info = eurusd_info();
+
+    assert!(matches!(
+        info.normalize_volume(0.001),
+        Err(OrderValidationError::VolumeBelowMinimum { min, .. }) if min == 0.01
+    ));
+    assert!(matches!(info.normalize_volume(-5.0), Err(OrderValidationError::InvalidVolume(_))));
+    assert!(matches!(info.normalize_volume(f64::NAN), Err(OrderValidationError::InvalidVolume(_))));
+    // Not capped at the maximum either
+    assert!(matches!(
+        info.normalize_volume(250.0),
+        Err(OrderValidationError::VolumeAboveSymbolMaximum { max, .. }) if max == 100.0
+    ));
+    assert!(matches!(
+        info.normalize_volume(100.006),
+        Err(OrderValidationError::VolumeAboveSymbolMaximum { .. })
+    ));
+
+    // Not rounded up to the minimum, even when the nearest step would be
+    info.volume_min = 0.1;
+    info.volume_step = 0.1;
+    assert!(matches!(
+        info.normalize_volume(0.06),
+        Err(OrderValidationError::VolumeBelowMinimum { .. })
+    ));
+}
+
+fn buy_limit() -> MT5Order {
+    MT5Order {
+        ticket: 0,
+        symbol: "EURUSD".to_string(),
+        order_type: MT5OrderType::BuyLimit,
+        volume: 0.1,
+        price: 1.0850,
+        stop_limit: None,
+        stop_loss: Some(1.0800),
+        take_profit: Some(1.0900),
+        comment: None,
+        magic: 123456,
+        expiration: None,
+        client_order_id: None,
+        deviation: None,
+        skip_spread_check: false,
+        state: OrderState::Pending,
+    }
+}
+
+#[test]
+fn test_validate_accepts_well_formed_orders() {
+    assert!(buy_limit().validate().is_ok());
+
+    let mut sell = buy_limit();
+    sell.order_type = MT5OrderType::SellLimit;
+    sell.stop_loss = Some(1.0900);
+    sell.take_profit = Some(1.0800);
+    assert!(sell.validate().is_ok());
+
+    // Market order without a price skips the side check
+    let mut market = buy_limit();
+    market.order_type = MT5OrderType::Buy;
+    market.price = 0.0;
+    assert!(market.validate().is_ok());
+}
+
+#[test]
+fn test_validate_rejects_bad_volume() {
+    for volume in [0.0, -0.1, f64::NAN, f64::INFINITY] {
+        let mut order = buy_limit();
+        order.volume = volume;
+        assert!(matches!(order.validate(), Err(OrderValidationError::InvalidVolume(_))));
+    }
+}
+
+#[test]
+fn test_validate_rejects_bad_price() {
+    for price in [-1.0, f64::NAN, f64::INFINITY] {
+        let mut order = buy_limit();
+        order.price = price;
+        assert!(matches!(order.validate(), Err(OrderValidationError::InvalidPrice(_))));
+    }
+}
+
+#[test]
+fn test_validate_price_requirement_per_order_type() {
+    // (order type, whether it needs a price)
+    let cases = [
+        (MT5OrderType::Buy, false),
+        (MT5OrderType::Sell, false),
+        (MT5OrderType::BuyLimit, true),
+        (MT5OrderType::SellLimit, true),
+        (MT5OrderType::BuyStop, true),
+        (MT5OrderType::SellStop, true),
+        (MT5OrderType::BuyStopLimit, true),
+        (MT5OrderType::SellStopLimit, true),
+    ];
+
+    for (order_type, needs_price) in cases {
+        let mut order = buy_limit();
+        order.order_type = order_type;
+        order.stop_loss = None;
+        order.take_profit = None;
+        order.stop_limit = Some(1.0840);
+        order.price = 0.0;
+        let result = order.validate();
+        if needs_price {
+            assert_eq!(result, Err(OrderValidationError::MissingPrice(order_type)), "{}", order_type);
+        } else {
+            assert!(result.is_ok(), "{}: {:?}", order_type, result);
+        }
+
+        order.price = 1.0850;
+        assert!(order.validate().is_ok(), "{}", order_type);
+    }
+
+    let error = OrderValidationError::MissingPrice(MT5OrderType::BuyLimit).to_string();
+    assert!(error.contains("requires a price"), "{}", error);
+}
+
+#[test]
+fn test_validate_rejects_non_finite_levels() {
+    let mut order = buy_limit();
+    order.stop_loss = Some(f64::NAN);
+    assert!(matches!(
+        order.validate(),
+        Err(OrderValidationError::InvalidLevel { field: "stop_loss", .. })
+    ));
+
+    let mut order = buy_limit();
+    order.take_profit = Some(f64::NEG_INFINITY);
+    assert!(matches!(
+        order.validate(),
+        Err(OrderValidationError::InvalidLevel { field: "take_profit", .. })
+    ));
+}
+
+#[test]
+fn test_validate_rejects_levels_on_wrong_side() {
+    let mut order = buy_limit();
+    order.stop_loss = Some(1.0900);
+    assert!(matches!(
+        order.validate(),
+        Err(OrderValidationError::LevelWrongSide { field: "stop_loss", .. })
+    ));
+
+    let mut order = buy_limit();
+    order.take_profit = Some(1.0800);
+    assert!(matches!(
+        order.validate(),
+        Err(OrderValidationError::LevelWrongSide { field: "take_profit", .. })
+    ));
+
+    let mut order = buy_limit();
+    order.order_type = MT5OrderType::SellStop;
+    assert!(matches!(
+        order.validate(),
+        Err(OrderValidationError::LevelWrongSide { field: "stop_loss", .. })
+    ));
+}
+
+#[test]
+fn test_retcode_descriptions() {
+    assert_eq!(retcode_description(10004), Some("Requote"));
+    assert_eq!(retcode_description(10009), Some("Request completed"));
+    assert_eq!(
+        retcode_description(10019),
+        Some("There is not enough money to complete the request")
+    );
+    assert_eq!(retcode_description(10018), Some("Market is closed"));
+    assert_eq!(retcode_description(42), None);
+}
+
+#[test]
+fn test_order_rejection_includes_retcode() {
+    let error = MT5Error::OrderRejected {
+        retcode: Some(10019),
+        message: "No money".to_string(),
+    };
+    assert_eq!(
+        error.to_string(),
+        "Order rejected (retcode 10019: There is not enough money to complete the request): No money"
+    );
+
+    let error = MT5Error::OrderRejected {
+        retcode: None,
+        message: "No money".to_string(),
+    };
+    assert_eq!(error.to_string(), "Order rejected: No money");
+}
+
+#[test]
+fn test_classify_rejection() {
+    let expected = [
+        (10019, RejectionClass::InsufficientFunds),
+        (10015, RejectionClass::InvalidPrice),
+        (10016, RejectionClass::InvalidPrice),
+        (10018, RejectionClass::MarketClosed),
+        (10004, RejectionClass::Requote),
+        (10020, RejectionClass::Requote),
+        (10017, RejectionClass::TradeDisabled),
+        (10027, RejectionClass::TradeDisabled),
+        (10006, RejectionClass::Other),
+        (42, RejectionClass::Other),
+    ];
+    for (retcode, class) in expected {
+        assert_eq!(classify_rejection(retcode), class, "retcode {}", retcode);
+    }
+}
+
+#[test]
+fn test_error_rejection_class() {
+    let error = MT5Error::rejection(Some(10018), "Market closed".to_string());
+    assert_eq!(error.rejection_class(), Some(RejectionClass::MarketClosed));
+    let error = MT5Error::rejection(Some(10004), "Requote".to_string());
+    assert_eq!(error.rejection_class(), Some(RejectionClass::Requote));
+    let error = MT5Error::rejection(None, "spread too wide".to_string());
+    assert_eq!(error.rejection_class(), None);
+    assert_eq!(MT5Error::Timeout.rejection_class(), None);
+}
+
+#[test]
+fn test_validate_accepts_future_expiration() {
+    let mut order = buy_limit();
+    order.expiration = Some(chrono::Utc::now().timestamp() + 3600);
+    assert!(order.validate().is_ok());
+}
+
+#[test]
+fn test_validate_rejects_past_expiration() {
+    let mut order = buy_limit();
+    order.expiration = Some(chrono::Utc::now().timestamp() - 60);
+    assert!(matches!(
+        order.validate(),
+        Err(OrderValidationError::ExpirationInPast { .. })
+    ));
+}
+
+#[test]
+fn test_validate_rejects_expiration_on_market_order() {
+    let mut order = buy_limit();
+    order.order_type = MT5OrderType::Buy;
+    order.price = 0.0;
+    order.expiration = Some(chrono::Utc::now().timestamp() + 3600);
+    assert_eq!(
+        order.validate(),
+        Err(OrderValidationError::ExpirationOnMarketOrder(MT5OrderType::Buy))
+    );
+}
+
+#[test]
+fn test_round_price_to_digits() {
+    let info = eurusd_info();
+    assert_eq!(info.round_price(1.085012345), 1.08501);
+    assert_eq!(info.round_price(1.085016), 1.08502);
+    assert_eq!(info.round_price(0.0), 0.0);
+}
+
+#[test]
+fn test_normalize_order_rounds_prices_and_volume() {
+    let mut order = buy_limit();
+    order.price = 1.085012345;
+    order.stop_loss = Some(1.080004);
+    order.take_profit = Some(1.0900049);
+    order.volume = 0.123;
+
+    eurusd_info().normalize_order(&mut order).unwrap();
+
+    assert_eq!(order.price, 1.08501);
+    assert_eq!(order.stop_loss, Some(1.08));
+    assert_eq!(order.take_profit, Some(1.09));
+    assert_eq!(order.volume, 0.12);
+}
+
+fn position(position_type: MT5Side, price_open: f64, price_current: f64) -> MT5Position {
+    MT5Position {
+        ticket: 1,
+        symbol: "EURUSD".to_string(),
+        position_type,
+        volume: 0.1,
+        price_open,
+        price_current,
+        profit: 10.0,
+        swap: -1.5,
+        commission: -0.5,
+        stop_loss: None,
+        take_profit: None,
+        comment: None,
+        magic: 123456,
+        time_open: 1699113600,
+    }
+}
+
+#[test]
+fn test_position_type_wire_format() {
+    let long = position(MT5Side::Buy, 1.0850, 1.0860);
+    let json = serde_json::to_value(&long).unwrap();
+    assert_eq!(json["position_type"], "OP_BUY");
+
+    let mut json = json;
+    json["position_type"] = "OP_SELL".into();
+    let short: MT5Position = serde_json::from_value(json).unwrap();
+    assert_eq!(short.position_type, MT5Side::Sell);
+}
+
+#[test]
+fn test_position_net_profit() {
+    assert_eq!(position(MT5Side::Buy, 1.0850, 1.0860).net_profit(), 8.0);
+}
+
+#[test]
+fn test_position_is_long() {
+    assert!(position(MT5Side::Buy, 1.0850, 1.0860).is_long());
+    assert!(!position(MT5Side::Sell, 1.0850, 1.0860).is_long());
+}
+
+#[test]
+fn test_position_unrealized_pips() {
+    let point = 0.0001;
+
+    let long = position(MT5Side::Buy, 1.0850, 1.0860);
+    assert!((long.unrealized_pips(point) - 10.0).abs() < 1e-9);
+
+    // A short gains when price falls
+    let short = position(MT5Side::Sell, 1.0850, 1.0830);
+    assert!((short.unrealized_pips(point) - 20.0).abs() < 1e-9);
+    let short = position(MT5Side::Sell, 1.0850, 1.0860);
+    assert!((short.unrealized_pips(point) + 10.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_position_profit_in_account_currency() {
+    let rates = std::collections::HashMap::new();
+    let eurusd = position(MT5Side::Buy, 1.0850, 1.0860);
+    assert_eq!(eurusd.profit_in("USD", &rates), Some(10.0));
+    assert_eq!(eurusd.profit_in("usd", &rates), Some(10.0));
+}
+
+#[test]
+fn test_position_profit_in_converts_cross_currency() {
+    let mut usdjpy = position(MT5Side::Buy, 150.00, 150.50);
+    usdjpy.symbol = "USDJPY".to_string();
+    usdjpy.profit = 1500.0;
+
+    let mut rates = std::collections::HashMap::new();
+    assert_eq!(usdjpy.profit_in("EUR", &rates), None);
+
+    // The inverse pair is divided by
+    rates.insert("EURJPY".to_string(), 160.0);
+    assert!((usdjpy.profit_in("EUR", &rates).unwrap() - 9.375).abs() < 1e-9);
+
+    // The direct pair is preferred
+    rates.insert("JPYEUR".to_string(), 0.006);
+    assert!((usdjpy.profit_in("EUR", &rates).unwrap() - 9.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_order_type_side() {
+    assert_eq!(MT5OrderType::BuyStopLimit.side(), MT5Side::Buy);
+    assert_eq!(MT5OrderType::SellLimit.side(), MT5Side::Sell);
+    assert_eq!(MT5Side::Buy.opposite(), MT5Side::Sell);
+}
+
+#[test]
+fn test_order_type_trade_type() {
+    let expected = [
+        (MT5OrderType::Buy, TRADE_ACTION_DEAL, 0),
+        (MT5OrderType::Sell, TRADE_ACTION_DEAL, 1),
+        (MT5OrderType::BuyLimit, TRADE_ACTION_PENDING, 2),
+        (MT5OrderType::SellLimit, TRADE_ACTION_PENDING, 3),
+        (MT5OrderType::BuyStop, TRADE_ACTION_PENDING, 4),
+        (MT5OrderType::SellStop, TRADE_ACTION_PENDING, 5),
+        (MT5OrderType::BuyStopLimit, TRADE_ACTION_PENDING, 6),
+        (MT5OrderType::SellStopLimit, TRADE_ACTION_PENDING, 7),
+    ];
+    for (order_type, action, mt5_type) in expected {
+        assert_eq!(
+            order_type.trade_type(),
+            MT5TradeType { action, order_type: mt5_type },
+            "{}",
+            order_type
+        );
+    }
+    assert_eq!(TRADE_ACTION_DEAL, 1);
+    assert_eq!(TRADE_ACTION_PENDING, 5);
+}
+
+#[test]
+fn test_epoch_to_utc_accepts_seconds_and_millis() {
+    let expected = chrono::DateTime::parse_from_rfc3339("2023-11-04T16:00:00Z").unwrap();
+
+    assert_eq!(epoch_to_utc(1699113600), expected);
+    assert_eq!(epoch_to_utc(1699113600000), expected);
+    assert_eq!(epoch_to_utc(1699113600123).timestamp_subsec_millis(), 123);
+    assert_eq!(epoch_to_utc(0).timestamp(), 0);
+}
+
+#[test]
+fn test_timestamp_helpers() {
+    let mut long = position(MT5Side::Buy, 1.0850, 1.0860);
+    assert_eq!(long.time_open_utc().timestamp(), 1699113600);
+    long.time_open = 1699113600000;
+    assert_eq!(long.time_open_utc().timestamp(), 1699113600);
+
+    let mut order = buy_limit();
+    assert_eq!(order.expiration_utc(), None);
+    order.expiration = Some(1699113600000);
+    assert_eq!(order.expiration_utc().unwrap().timestamp(), 1699113600);
+}
+
+#[test]
+fn test_validate_compares_millisecond_expiration() {
+    let mut order = buy_limit();
+    order.expiration = Some(chrono::Utc::now().timestamp_millis() - 60_000);
+    assert!(matches!(
+        order.validate(),
+        Err(OrderValidationError::ExpirationInPast { .. })
+    ));
+
+    order.expiration = Some(chrono::Utc::now().timestamp_millis() + 3_600_000);
+    assert!(order.validate().is_ok());
+}
+
+#[test]
+fn test_summarize_mixed_positions() {
+    let mut eurusd_long = position(MT5Side::Buy, 1.0850, 1.0860);
+    eurusd_long.volume = 0.3;
+    let mut eurusd_short = position(MT5Side::Sell, 1.0870, 1.0860);
+    eurusd_short.volume = 0.1;
+    eurusd_short.profit = 5.0;
+    let mut gbpusd_short = position(MT5Side::Sell, 1.2500, 1.2520);
+    gbpusd_short.symbol = "GBPUSD".to_string();
+    gbpusd_short.volume = 0.2;
+    gbpusd_short.profit = -40.0;
+
+    let summary = summarize(&[gbpusd_short, eurusd_long, eurusd_short]);
+
+    assert_eq!(summary.position_count, 3);
+    assert!((summary.total_profit - -25.0).abs() < 1e-9);
+    assert!((summary.total_swap - -4.5).abs() < 1e-9);
+    assert!((summary.total_commission - -1.5).abs() < 1e-9);
+    assert!((summary.net_profit - -31.0).abs() < 1e-9);
+    assert_eq!(summary.long.position_count, 1);
+    assert_eq!(summary.short.position_count, 2);
+    assert!((summary.short.volume - 0.3).abs() < 1e-9);
+
+    let symbols: Vec<&str> = summary.symbols.iter().map(|s| s.symbol.as_str()).collect();
+    assert_eq!(symbols, ["EURUSD", "GBPUSD"]);
+    let eurusd = &summary.symbols[0];
+    assert!((eurusd.net_volume - 0.2).abs() < 1e-9);
+    assert!((eurusd.long.net_profit - 8.0).abs() < 1e-9);
+    assert!((eurusd.short.net_profit - 3.0).abs() < 1e-9);
+    let gbpusd = &summary.symbols[1];
+    assert_eq!(gbpusd.long.position_count, 0);
+    assert!((gbpusd.net_volume - -0.2).abs() < 1e-9);
+}
+
+#[test]
+fn test_summarize_no_positions() {
+    let summary = summarize(&[]);
+    assert_eq!(summary.position_count, 0);
+    assert_eq!(summary.net_profit, 0.0);
+    assert!(summary.symbols.is_empty());
+}
+
+#[test]
+fn test_net_position_mixed_long_and_short() {
+    let mut long = position(MT5Side::Buy, 1.0850, 1.0860);
+    long.volume = 0.3;
+    let mut short = position(MT5Side::Sell, 1.0870, 1.0860);
+    short.volume = 0.1;
+    let mut other = position(MT5Side::Sell, 1.2500, 1.2520);
+    other.symbol = "GBPUSD".to_string();
+
+    let net = net_position("EURUSD", &[long, other, short]).unwrap();
+
+    assert_eq!(net.position_count, 2);
+    assert!((net.net_volume - 0.2).abs() < 1e-9);
+    assert_eq!(net.side, Some(MT5Side::Buy));
+    assert!(!net.is_hedged());
+    // (0.3 * 1.0850 - 0.1 * 1.0870) / 0.2
+    assert!((net.average_price.unwrap() - 1.0840).abs() < 1e-9);
+}
+
+#[test]
+fn test_net_position_net_short() {
+    let mut first = position(MT5Side::Sell, 1.0800, 1.0860);
+    first.volume = 0.1;
+    let mut second = position(MT5Side::Sell, 1.0900, 1.0860);
+    second.volume = 0.3;
+
+    let net = net_position("EURUSD", &[first, second]).unwrap();
+
+    assert!((net.net_volume - -0.4).abs() < 1e-9);
+    assert_eq!(net.side, Some(MT5Side::Sell));
+    // (0.1 * 1.0800 + 0.3 * 1.0900) / 0.4
+    assert!((net.average_price.unwrap() - 1.0875).abs() < 1e-9);
+}
+
+#[test]
+fn test_net_position_fully_hedged() {
+    let mut long = position(MT5Side::Buy, 1.0850, 1.0860);
+    long.volume = 0.3;
+    let mut first_short = position(MT5Side::Sell, 1.0870, 1.0860);
+    first_short.volume = 0.1;
+    let mut second_short = position(MT5Side::Sell, 1.0880, 1.0860);
+    second_short.volume = 0.2;
+
+    let net = net_position("EURUSD", &[long, first_short, second_short]).unwrap();
+
+    assert_eq!(net.position_count, 3);
+    assert_eq!(net.net_volume, 0.0);
+    assert!(net.is_hedged());
+    assert_eq!(net.side, None);
+    assert_eq!(net.average_price, None);
+}
+
+#[test]
+fn test_net_position_without_positions_in_symbol() {
+    let mut other = position(MT5Side::Buy, 1.2500, 1.2520);
+    other.symbol = "GBPUSD".to_string();
+    assert_eq!(net_position("EURUSD", &[other]), None);
+    assert_eq!(net_position("EURUSD", &[]), None);
+}
+
+fn quote_at(time: i64) -> MT5MarketData {
+    MT5MarketData {
+        symbol: "EURUSD".to_string(),
+        bid: 1.1000,
+        ask: 1.1002,
+        last: 1.1001,
+        volume: 0.0,
+        time,
+        spread: 2.0,
+        digits: 5,
+        stale: false,
+    }
+}
+
+#[test]
+fn test_market_data_age_and_freshness() {
+    let now = chrono::Utc::now();
+    let fresh = quote_at(now.timestamp());
+    assert!(fresh.age(now) < std::time::Duration::from_secs(1));
+    assert!(!fresh.is_stale(std::time::Duration::from_secs(60)));
+
+    let old = quote_at(now.timestamp() - 120);
+    assert_eq!(old.age(now).as_secs(), 120);
+    assert!(old.is_stale(std::time::Duration::from_secs(60)));
+}
+
+#[test]
+fn test_market_data_future_timestamp_is_not_stale() {
+    let now = chrono::Utc::now();
+    let ahead = quote_at(now.timestamp() + 300);
+    assert_eq!(ahead.age(now), std::time::Duration::ZERO);
+    assert!(!ahead.is_stale(std::time::Duration::from_secs(1)));
+}
@@ -0,0 +1,69 @@
+//! Unit tests for position reconciliation
+
+use fks_meta::models::{MT5Position, MT5Side};
+use fks_meta::mt5::reconcile::{diff_positions, PositionChange, Reconciler};
+
+fn position(ticket: u64, symbol: &str, volume: f64) -> MT5Position {
+    MT5Position {
+        ticket,
+        symbol: symbol.to_string(),
+        position_type: MT5Side::Buy,
+        volume,
+        price_open: 1.0850,
+        price_current: 1.0860,
+        profit: 10.0,
+        swap: 0.0,
+        commission: 0.0,
+        stop_loss: None,
+        take_profit: None,
+        comment: None,
+        magic: 123456,
+        time_open: 1699113600,
+    }
+}
+
+#[test]
+fn test_diff_detects_opened_closed_and_resized_positions() {
+    let previous = vec![
+        position(1, "EURUSD", 0.1),
+        position(2, "GBPUSD", 0.5),
+        position(3, "USDJPY", 1.0),
+    ];
+    let current = vec![
+        position(3, "USDJPY", 1.0),
+        position(2, "GBPUSD", 0.2),
+        position(4, "EURUSD", 0.3),
+    ];
+
+    assert_eq!(
+        diff_positions(&previous, &current),
+        vec![
+            PositionChange::Closed(position(1, "EURUSD", 0.1)),
+            PositionChange::VolumeChanged {
+                ticket: 2,
+                symbol: "GBPUSD".to_string(),
+                previous: 0.5,
+                current: 0.2,
+            },
+            PositionChange::Opened(position(4, "EURUSD", 0.3)),
+        ]
+    );
+}
+
+#[test]
+fn test_diff_of_unchanged_positions_is_empty() {
+    let positions = vec![position(1, "EURUSD", 0.1)];
+    assert!(diff_positions(&positions, &positions).is_empty());
+}
+
+#[test]
+fn test_reconciler_reports_changes_after_baseline() {
+    let reconciler = Reconciler::new();
+    assert!(reconciler.snapshot().is_none());
+
+    assert!(reconciler.update(vec![position(1, "EURUSD", 0.1)]).is_empty());
+    let changes = reconciler.update(vec![position(1, "EURUSD", 0.1), position(2, "EURUSD", 0.2)]);
+
+    assert_eq!(changes, vec![PositionChange::Opened(position(2, "EURUSD", 0.2))]);
+    assert_eq!(reconciler.snapshot().unwrap().len(), 2);
+}
@@ -0,0 +1,53 @@
+//! Unit tests for bridge log redaction
+
+use fks_meta::mt5::redact::{redact_body, redact_headers, redact_json, REDACTED};
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
+
+#[test]
+fn test_redact_json_masks_credentials() {
+    let mut payload = serde_json::json!({
+        "symbol": "EURUSD",
+        "volume": 0.1,
+        "password": "hunter2",
+        "account_number": 12345678,
+        "credentials": { "login": 12345678, "api_token": "abc" },
+        "orders": [{ "Password": "nested", "magic": 123456 }],
+    });
+
+    redact_json(&mut payload);
+
+    assert_eq!(
+        payload,
+        serde_json::json!({
+            "symbol": "EURUSD",
+            "volume": 0.1,
+            "password": REDACTED,
+            "account_number": REDACTED,
+            "credentials": { "login": REDACTED, "api_token": REDACTED },
+            "orders": [{ "Password": REDACTED, "magic": 123456 }],
+        })
+    );
+}
+
+#[test]
+fn test_redact_body_handles_non_json() {
+    assert_eq!(
+        redact_body(br#"{"password":"hunter2"}"#),
+        format!(r#"{{"password":"{}"}}"#, REDACTED)
+    );
+    assert_eq!(redact_body(b"password=hunter2"), "<16 bytes, not JSON>");
+    assert_eq!(redact_body(b""), "");
+}
+
+#[test]
+fn test_redact_headers_masks_tokens() {
+    let mut headers = HeaderMap::new();
+    headers.insert(AUTHORIZATION, HeaderValue::from_static("Bearer secret"));
+    headers.insert("x-bridge-token", HeaderValue::from_static("secret"));
+    headers.insert("idempotency-key", HeaderValue::from_static("abc-123"));
+
+    let rendered = redact_headers(&headers);
+
+    assert!(!rendered.contains("secret"), "{}", rendered);
+    assert!(rendered.contains("idempotency-key: abc-123"), "{}", rendered);
+}
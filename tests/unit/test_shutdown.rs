@@ -0,0 +1,53 @@
+//! Unit tests for shutdown coordination
+
+use fks_meta::shutdown::Shutdown;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+#[tokio::test]
+async fn test_drain_cancels_tasks_within_grace() {
+    let shutdown = Shutdown::new();
+    let cancelled = Arc::new(AtomicBool::new(false));
+
+    let token = shutdown.token();
+    let flag = cancelled.clone();
+    shutdown.spawn(async move {
+        // A long-running task that only stops when asked to
+        loop {
+            tokio::select! {
+                _ = token.cancelled() => break,
+                _ = tokio::time::sleep(Duration::from_millis(5)) => {}
+            }
+        }
+        flag.store(true, Ordering::SeqCst);
+    });
+    assert_eq!(shutdown.running(), 1);
+
+    let started = Instant::now();
+    assert!(shutdown.drain(Duration::from_secs(2)).await);
+
+    assert!(started.elapsed() < Duration::from_secs(1));
+    assert!(cancelled.load(Ordering::SeqCst));
+    assert!(shutdown.is_triggered());
+    assert_eq!(shutdown.running(), 0);
+}
+
+#[tokio::test]
+async fn test_drain_gives_up_after_grace() {
+    let shutdown = Shutdown::new();
+    // Ignores cancellation
+    shutdown.spawn(tokio::time::sleep(Duration::from_secs(60)));
+
+    let started = Instant::now();
+    assert!(!shutdown.drain(Duration::from_millis(50)).await);
+
+    assert!(started.elapsed() >= Duration::from_millis(50));
+    assert_eq!(shutdown.running(), 1);
+}
+
+#[tokio::test]
+async fn test_drain_with_no_tasks() {
+    let shutdown = Shutdown::new();
+    assert!(shutdown.drain(Duration::from_millis(10)).await);
+}
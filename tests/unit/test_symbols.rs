@@ -0,0 +1,44 @@
+//! Unit tests for broker symbol prefixes
+
+use fks_meta::mt5::symbols::{apply_symbol_prefix, strip_symbol_prefix};
+
+#[test]
+fn test_apply_symbol_prefix() {
+    // (symbol, prefix, expected)
+    let cases = [
+        ("EURUSD", "", "EURUSD"),
+        ("EURUSD", "m.", "m.EURUSD"),
+        ("m.EURUSD", "m.", "m.EURUSD"),
+        ("M.EURUSD", "m.", "m.M.EURUSD"),
+        ("EURUSD", "EUR", "EURUSD"),
+        ("", "m.", "m."),
+    ];
+    for (symbol, prefix, expected) in cases {
+        assert_eq!(apply_symbol_prefix(symbol, prefix), expected, "{:?} with {:?}", symbol, prefix);
+    }
+}
+
+#[test]
+fn test_strip_symbol_prefix() {
+    // (symbol, prefix, expected)
+    let cases = [
+        ("EURUSD", "", "EURUSD"),
+        ("m.EURUSD", "m.", "EURUSD"),
+        ("EURUSD", "m.", "EURUSD"),
+        ("M.EURUSD", "m.", "M.EURUSD"),
+        ("m.m.EURUSD", "m.", "m.EURUSD"),
+        ("m.", "m.", ""),
+    ];
+    for (symbol, prefix, expected) in cases {
+        assert_eq!(strip_symbol_prefix(symbol, prefix), expected, "{:?} with {:?}", symbol, prefix);
+    }
+}
+
+#[test]
+fn test_prefix_round_trip() {
+    for symbol in ["EURUSD", "m.EURUSD", "XAUUSD"] {
+        let broker = apply_symbol_prefix(symbol, "m.");
+        assert_eq!(apply_symbol_prefix(&broker, "m."), broker);
+        assert_eq!(strip_symbol_prefix(&broker, "m."), strip_symbol_prefix(symbol, "m."));
+    }
+}
@@ -0,0 +1,94 @@
+//! Unit tests for trailing stop levels
+
+use fks_meta::models::{MT5MarketData, MT5Position, MT5Side, MT5SymbolInfo};
+use fks_meta::mt5::TrailingStop;
+
+fn symbol_info() -> MT5SymbolInfo {
+    MT5SymbolInfo {
+        symbol: "EURUSD".to_string(),
+        digits: 5,
+        point: 0.00001,
+        tick_size: 0.00001,
+        tick_value: 1.0,
+        volume_min: 0.01,
+        volume_max: 100.0,
+        volume_step: 0.01,
+        contract_size: 100000.0,
+        trade_allowed: true,
+    }
+}
+
+fn position(position_type: MT5Side, stop_loss: Option<f64>) -> MT5Position {
+    MT5Position {
+        ticket: 7,
+        symbol: "EURUSD".to_string(),
+        position_type,
+        volume: 0.1,
+        price_open: 1.2000,
+        price_current: 1.2000,
+        profit: 0.0,
+        swap: 0.0,
+        commission: 0.0,
+        stop_loss,
+        take_profit: None,
+        comment: None,
+        magic: 123456,
+        time_open: 1699113600,
+    }
+}
+
+fn market(bid: f64, ask: f64) -> MT5MarketData {
+    MT5MarketData {
+        symbol: "EURUSD".to_string(),
+        bid,
+        ask,
+        last: bid,
+        volume: 100.0,
+        time: 1699113600,
+        spread: ((ask - bid) * 1e5).round(),
+        digits: 5,
+        stale: false,
+    }
+}
+
+const TRAILING: TrailingStop = TrailingStop {
+    distance_points: 25.0,
+    step_points: 5.0,
+};
+
+#[test]
+fn test_next_stop_is_rounded_to_digits() {
+    // 1.23450 - 0.00025 is 1.2342499999999998 in floating point
+    let long = TRAILING.next_stop(&position(MT5Side::Buy, None), &market(1.23450, 1.23460), &symbol_info());
+    assert_eq!(long, Some(1.23425));
+
+    // 1.08522 + 0.00025 is 1.0854700000000002 in floating point
+    let short = TRAILING.next_stop(&position(MT5Side::Sell, None), &market(1.08512, 1.08522), &symbol_info());
+    assert_eq!(short, Some(1.08547));
+}
+
+#[test]
+fn test_next_stop_waits_for_a_full_step() {
+    let info = symbol_info();
+    let quote = market(1.23450, 1.23460);
+
+    assert_eq!(TRAILING.next_stop(&position(MT5Side::Buy, Some(1.23421)), &quote, &info), None);
+    assert_eq!(TRAILING.next_stop(&position(MT5Side::Buy, Some(1.23420)), &quote, &info), Some(1.23425));
+    // Stops never move back
+    assert_eq!(TRAILING.next_stop(&position(MT5Side::Buy, Some(1.23500)), &quote, &info), None);
+}
+
+#[test]
+fn test_next_stop_without_step_skips_unchanged_stop() {
+    let trailing = TrailingStop {
+        step_points: 0.0,
+        ..TRAILING
+    };
+    let info = symbol_info();
+    let quote = market(1.23450, 1.23460);
+
+    assert_eq!(trailing.next_stop(&position(MT5Side::Buy, Some(1.23425)), &quote, &info), None);
+    assert_eq!(trailing.next_stop(&position(MT5Side::Buy, Some(1.23424)), &quote, &info), Some(1.23425));
+    assert_eq!(trailing.next_stop(&position(MT5Side::Sell, Some(1.23485)), &quote, &info), None);
+    assert_eq!(trailing.next_stop(&position(MT5Side::Sell, Some(1.23486)), &quote, &info), Some(1.23485));
+}